@@ -81,6 +81,7 @@ fn benchmark_json_to_toon_conversion(c: &mut Criterion) {
             validate_output: false,
             include_schema: true,
             max_depth: Some(1000),
+            source_map: false,
         };
         b.iter(|| toonconv::convert_json_with_config(black_box(&json), black_box(&config)))
     });
@@ -286,8 +286,8 @@ fn print_bar(
     let empty = bar_width - filled;
 
     let bar_char = if is_primary { "\u{2588}" } else { "\u{2591}" };
-    let bar: String = std::iter::repeat(bar_char).take(filled).collect();
-    let space: String = std::iter::repeat("\u{2591}").take(empty).collect();
+    let bar = bar_char.repeat(filled);
+    let space = "\u{2591}".repeat(empty);
 
     let diff_str = if let Some(pct) = vs_json {
         if pct >= 0.0 {
@@ -366,12 +366,8 @@ fn print_result(res: &BenchmarkResult) {
     let xml_diff = vs_json(res.xml_tokens);
     let bar_width = 20;
     let filled = ((res.xml_tokens as f64 / max_tokens as f64) * bar_width as f64).round() as usize;
-    let bar: String = std::iter::repeat("\u{2591}")
-        .take(filled.min(bar_width))
-        .collect();
-    let space: String = std::iter::repeat("\u{2591}")
-        .take(bar_width - filled.min(bar_width))
-        .collect();
+    let bar = "\u{2591}".repeat(filled.min(bar_width));
+    let space = "\u{2591}".repeat(bar_width - filled.min(bar_width));
     println!(
         "\u{2514}\u{2500} vs XML {}{} {:>6} tokens (+{:.1}%)",
         bar, space, res.xml_tokens, xml_diff
@@ -1,6 +1,6 @@
 use serde_json::json;
 fn main() {
-    let v = json!({"inf": std::f64::INFINITY, "nan": std::f64::NAN});
+    let v = json!({"inf": f64::INFINITY, "nan": f64::NAN});
     println!("VALUE: {:?}", v);
     println!("TO_STRING: {}", v);
     if let serde_json::Value::Object(map) = &v {
@@ -0,0 +1,343 @@
+//! `toonconv benchmark`: conversion timing and token comparison
+//!
+//! Times repeated conversions of one input and sets the size of the TOON
+//! output beside the same data as pretty JSON, compact JSON, YAML and, when
+//! the data is a single table, CSV. Tokens are counted with the `cl100k_base`
+//! encoding, as in `benches/token_efficiency.rs`.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tiktoken_rs::CoreBPE;
+
+use crate::conversion::engine::ConversionEngine;
+use crate::conversion::stats::BenchmarkResults;
+use crate::conversion::{ConversionConfig, ConversionResult};
+
+/// Iterations run when `--iterations` is not given
+pub const DEFAULT_ITERATIONS: u32 = 10;
+
+/// Size of the benchmarked data in one format
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatSize {
+    pub format: &'static str,
+    pub bytes: usize,
+    pub tokens: usize,
+}
+
+/// Timing and format comparison for one benchmarked input
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub timing: BenchmarkResults,
+    /// TOON first, then the formats it is compared against
+    pub formats: Vec<FormatSize>,
+}
+
+impl BenchmarkReport {
+    /// Token count of a format, if it was measured
+    pub fn tokens(&self, format: &str) -> Option<usize> {
+        self.formats
+            .iter()
+            .find(|size| size.format == format)
+            .map(|size| size.tokens)
+    }
+
+    /// Render as a markdown table followed by the timing summary
+    pub fn to_markdown(&self) -> String {
+        let json_tokens = self.tokens("JSON").unwrap_or(0);
+        let mut out = format!(
+            "## Benchmark: {}\n\n| Format | Bytes | Tokens | vs JSON |\n|---|---:|---:|---:|\n",
+            self.timing.test_name
+        );
+        for size in &self.formats {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                size.format,
+                size.bytes,
+                size.tokens,
+                relative_change(size.tokens, json_tokens)
+            ));
+        }
+        out.push_str(&format!("\n```text\n{}\n```", self.timing.report()));
+        out
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("benchmark reports serialize to JSON")
+    }
+}
+
+/// Convert `value` `iterations` times, then compare the output's size
+/// against other formats
+pub fn run_benchmark(
+    name: &str,
+    value: &Value,
+    iterations: u32,
+    config: &ConversionConfig,
+) -> ConversionResult<BenchmarkReport> {
+    let engine = ConversionEngine::new(config.clone());
+    let iterations = iterations.max(1) as usize;
+    let mut times_ms = Vec::with_capacity(iterations);
+    let mut last = None;
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let toon_data = engine.convert(value)?;
+        times_ms.push(start.elapsed().as_millis() as u64);
+        last = Some(toon_data);
+    }
+
+    let toon_data = last.expect("at least one iteration runs");
+    let formats = compare_formats(value, &toon_data.content);
+    let input_size = formats
+        .iter()
+        .find(|size| size.format == "JSON compact")
+        .map_or(0, |size| size.bytes);
+    let timing = BenchmarkResults::new(
+        name.to_string(),
+        input_size as u64,
+        toon_data.metadata.output_size,
+        iterations,
+        times_ms,
+        toon_data.metadata.memory_peak_kb * 1024,
+    );
+
+    Ok(BenchmarkReport { timing, formats })
+}
+
+/// Sizes of `value` as TOON (`toon`), pretty JSON, compact JSON, YAML and,
+/// for tabular data, CSV
+pub fn compare_formats(value: &Value, toon: &str) -> Vec<FormatSize> {
+    let json = serde_json::to_string_pretty(value).expect("JSON values serialize to JSON");
+    let compact = value.to_string();
+    let yaml = serde_yaml::to_string(value).expect("JSON values serialize to YAML");
+
+    let mut outputs = vec![
+        ("TOON", toon.to_string()),
+        ("JSON", json),
+        ("JSON compact", compact),
+        ("YAML", yaml),
+    ];
+    if let Some(csv) = to_csv(value) {
+        outputs.push(("CSV", csv));
+    }
+
+    outputs
+        .into_iter()
+        .map(|(format, text)| FormatSize {
+            format,
+            bytes: text.len(),
+            tokens: count_tokens(&text),
+        })
+        .collect()
+}
+
+/// Count `cl100k_base` tokens
+pub fn count_tokens(text: &str) -> usize {
+    static BPE: OnceLock<CoreBPE> = OnceLock::new();
+    BPE.get_or_init(|| tiktoken_rs::cl100k_base().expect("cl100k_base is bundled"))
+        .encode_with_special_tokens(text)
+        .len()
+}
+
+/// Reader or writer that counts the bytes and `cl100k_base` tokens passing
+/// through it
+///
+/// Text is tokenized a line at a time, so streamed data is counted without
+/// being held in memory. The count matches [`count_tokens`] on the whole text
+/// except where blank lines would have merged into one token.
+pub struct TokenCounter<T> {
+    inner: T,
+    line: Vec<u8>,
+    bytes: u64,
+    tokens: usize,
+    tokenize: bool,
+}
+
+impl<T> TokenCounter<T> {
+    /// Count the data read from or written to `inner`
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+            bytes: 0,
+            tokens: 0,
+            tokenize: true,
+        }
+    }
+
+    /// Count only bytes unless `enabled`, as tokenizing costs far more
+    pub fn with_tokens(mut self, enabled: bool) -> Self {
+        self.tokenize = enabled;
+        self
+    }
+
+    /// Bytes passed through so far
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Tokens in everything passed through so far, or `None` when only
+    /// bytes are counted
+    pub fn tokens(&self) -> Option<usize> {
+        self.tokenize
+            .then(|| self.tokens + count_tokens(&String::from_utf8_lossy(&self.line)))
+    }
+
+    /// The wrapped reader or writer
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    fn count(&mut self, data: &[u8]) {
+        self.bytes += data.len() as u64;
+        if !self.tokenize {
+            return;
+        }
+        for chunk in data.split_inclusive(|&b| b == b'\n') {
+            self.line.extend_from_slice(chunk);
+            if chunk.ends_with(b"\n") {
+                self.tokens += count_tokens(&String::from_utf8_lossy(&self.line));
+                self.line.clear();
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for TokenCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<W: Write> Write for TokenCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Render data that is one table - an array of objects with the same keys
+/// and primitive values, at the root or as the only field - as CSV
+pub fn to_csv(value: &Value) -> Option<String> {
+    let rows = match value {
+        Value::Array(rows) => rows,
+        Value::Object(map) if map.len() == 1 => map.values().next()?.as_array()?,
+        _ => return None,
+    };
+
+    let first = rows.first()?.as_object()?;
+    let columns: Vec<&String> = first.keys().collect();
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns).ok()?;
+
+    for row in rows {
+        let row = row.as_object()?;
+        if row.len() != columns.len() {
+            return None;
+        }
+        let mut record = Vec::with_capacity(columns.len());
+        for column in &columns {
+            record.push(match row.get(*column)? {
+                Value::Null => String::new(),
+                Value::String(s) => s.clone(),
+                Value::Array(_) | Value::Object(_) => return None,
+                other => other.to_string(),
+            });
+        }
+        writer.write_record(&record).ok()?;
+    }
+
+    String::from_utf8(writer.into_inner().ok()?).ok()
+}
+
+fn relative_change(tokens: usize, baseline: usize) -> String {
+    if baseline == 0 {
+        return "-".to_string();
+    }
+    let change = (tokens as f64 - baseline as f64) / baseline as f64 * 100.0;
+    format!("{:+.1}%", change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_benchmark_runs_every_iteration() {
+        let value = json!({"users": [{"id": 1}, {"id": 2}]});
+        let report = run_benchmark("users", &value, 3, &ConversionConfig::default()).unwrap();
+
+        assert_eq!(report.timing.test_name, "users");
+        assert_eq!(report.timing.iterations, 3);
+        assert!(report.timing.report().contains("Iterations: 3"));
+    }
+
+    #[test]
+    fn test_formats_compared_with_csv_for_tables() {
+        let value = json!({"users": [
+            {"id": 1, "name": "Ann", "active": true},
+            {"id": 2, "name": "Bo", "active": false}
+        ]});
+        let report = run_benchmark("users", &value, 1, &ConversionConfig::default()).unwrap();
+
+        let formats: Vec<_> = report.formats.iter().map(|size| size.format).collect();
+        assert_eq!(formats, ["TOON", "JSON", "JSON compact", "YAML", "CSV"]);
+        assert!(report.tokens("TOON").unwrap() < report.tokens("JSON").unwrap());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| Format | Bytes | Tokens | vs JSON |"));
+        assert!(markdown.contains("| JSON | "));
+        assert!(markdown.contains("+0.0% |"));
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["formats"][0]["format"], "TOON");
+        assert_eq!(json["timing"]["iterations"], 1);
+    }
+
+    #[test]
+    fn test_csv_only_for_flat_uniform_rows() {
+        assert_eq!(
+            to_csv(&json!([{"a": 1, "b": "x,y"}, {"a": null, "b": "z"}])).unwrap(),
+            "a,b\n1,\"x,y\"\n,z\n"
+        );
+        assert!(to_csv(&json!([{"a": 1}, {"b": 2}])).is_none());
+        assert!(to_csv(&json!([{"a": [1]}])).is_none());
+        assert!(to_csv(&json!({"a": [], "b": []})).is_none());
+    }
+
+    #[test]
+    fn test_token_counter_matches_whole_text() {
+        let text =
+            "users[2]{id,name,note}:\n  1,Ann,\"hello, world\"\n  2,Bo,\u{e9}t\u{e9}\nok: true";
+        let mut writer = TokenCounter::new(Vec::new());
+        // Split mid-line and inside a multi-byte character
+        for chunk in text.as_bytes().chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.bytes(), text.len() as u64);
+        assert_eq!(writer.tokens(), Some(count_tokens(text)));
+
+        let mut reader = TokenCounter::new(text.as_bytes());
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        assert_eq!(reader.tokens(), Some(count_tokens(text)));
+
+        let mut bytes_only = TokenCounter::new(Vec::new()).with_tokens(false);
+        bytes_only.write_all(text.as_bytes()).unwrap();
+        assert_eq!(
+            (bytes_only.bytes(), bytes_only.tokens()),
+            (text.len() as u64, None)
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! `toonconv examples`: paired JSON/TOON sample files
+//!
+//! Each dataset mirrors one of the generators in `benches/token_efficiency.rs`
+//! so the examples show the same shapes the token benchmarks measure: a
+//! uniform table, nested orders, a numeric time series and an array that
+//! mixes flat and nested records.
+
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use crate::cli::benchmark::count_tokens;
+use crate::conversion::{convert_json_to_toon, ConversionConfig, ConversionResult};
+use crate::error::{ConversionError, ConversionErrorKind};
+
+/// Directory examples are written to when `--output-dir` is not given
+pub const DEFAULT_OUTPUT_DIR: &str = "examples";
+
+/// Records per dataset when `--count` is not given
+pub const DEFAULT_RECORD_COUNT: usize = 10;
+
+/// A named sample dataset
+#[derive(Debug, Clone)]
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: Value,
+}
+
+/// Token counts of one written example
+#[derive(Debug, Clone)]
+pub struct ExampleSavings {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub json_tokens: usize,
+    pub toon_tokens: usize,
+}
+
+impl ExampleSavings {
+    /// Percentage of JSON tokens saved by TOON
+    pub fn reduction(&self) -> f64 {
+        if self.json_tokens == 0 {
+            return 0.0;
+        }
+        (self.json_tokens as f64 - self.toon_tokens as f64) / self.json_tokens as f64 * 100.0
+    }
+}
+
+/// The sample datasets, each with `count` records
+pub fn datasets(count: usize) -> Vec<Example> {
+    vec![
+        Example {
+            name: "employees",
+            description: "Uniform records rendered as a single table",
+            value: employee_records(count),
+        },
+        Example {
+            name: "orders",
+            description: "Orders with nested customers and line-item tables",
+            value: ecommerce_orders(count),
+        },
+        Example {
+            name: "metrics",
+            description: "Daily numeric time series",
+            value: time_series(count),
+        },
+        Example {
+            name: "events",
+            description: "Log events mixing flat entries with nested errors",
+            value: event_logs(count),
+        },
+    ]
+}
+
+/// Write every dataset as `<name>.json` and `<name>.toon`, plus a
+/// `README.md` comparing their token counts, returning the paths written
+pub fn write_examples(
+    output_dir: &Path,
+    count: Option<usize>,
+    config: &ConversionConfig,
+) -> ConversionResult<Vec<PathBuf>> {
+    std::fs::create_dir_all(output_dir).map_err(|e| write_error(output_dir, e))?;
+
+    let mut written = Vec::new();
+    let mut savings = Vec::new();
+
+    for example in datasets(count.unwrap_or(DEFAULT_RECORD_COUNT)) {
+        let json_text =
+            serde_json::to_string_pretty(&example.value).expect("JSON values serialize to JSON");
+        let toon = convert_json_to_toon(&example.value, config)?.content;
+
+        let json_path = output_dir.join(format!("{}.json", example.name));
+        let toon_path = output_dir.join(format!("{}.toon", example.name));
+        write_file(&json_path, &json_text)?;
+        write_file(&toon_path, &toon)?;
+        written.push(json_path);
+        written.push(toon_path);
+
+        savings.push(ExampleSavings {
+            name: example.name,
+            description: example.description,
+            json_tokens: count_tokens(&json_text),
+            toon_tokens: count_tokens(&toon),
+        });
+    }
+
+    let readme_path = output_dir.join("README.md");
+    write_file(&readme_path, &readme(&savings))?;
+    written.push(readme_path);
+
+    Ok(written)
+}
+
+/// Markdown overview of the examples and their token savings
+pub fn readme(savings: &[ExampleSavings]) -> String {
+    let mut out = String::from(
+        "# TOON Examples\n\n\
+         Each dataset is written as pretty-printed JSON and as TOON. Token counts\n\
+         use the `cl100k_base` encoding.\n\n\
+         | Example | Shape | JSON tokens | TOON tokens | Saved |\n\
+         |---|---|---:|---:|---:|\n",
+    );
+    for example in savings {
+        out.push_str(&format!(
+            "| [{name}]({name}.toon) | {} | {} | {} | {:.1}% |\n",
+            example.description,
+            example.json_tokens,
+            example.toon_tokens,
+            example.reduction(),
+            name = example.name,
+        ));
+    }
+    out.push_str("\nRegenerate with `toonconv examples --output-dir <dir> --count <records>`.");
+    out
+}
+
+fn employee_records(count: usize) -> Value {
+    let departments = ["Engineering", "Sales", "Marketing", "HR"];
+    let employees: Vec<Value> = (0..count)
+        .map(|i| {
+            json!({
+                "id": i + 1,
+                "name": format!("Employee {}", i + 1),
+                "email": format!("emp{}@example.com", i + 1),
+                "department": departments[i % departments.len()],
+                "salary": 50000 + i * 1000,
+                "yearsExperience": i % 20 + 1,
+                "active": i % 3 != 0
+            })
+        })
+        .collect();
+    json!({ "employees": employees })
+}
+
+fn ecommerce_orders(count: usize) -> Value {
+    let statuses = ["pending", "shipped", "delivered"];
+    let orders: Vec<Value> = (0..count)
+        .map(|i| {
+            json!({
+                "orderId": format!("ORD-{:05}", i + 1),
+                "customer": {
+                    "id": format!("CUST-{:04}", i + 1),
+                    "name": format!("Customer {}", i + 1),
+                    "address": {
+                        "street": format!("{} Main St", i + 1),
+                        "city": "New York",
+                        "zip": "10001"
+                    }
+                },
+                "items": [
+                    {"sku": "PROD-1", "name": "Widget A", "quantity": i % 3 + 1, "price": 19.99},
+                    {"sku": "PROD-2", "name": "Widget B", "quantity": 1, "price": 29.99}
+                ],
+                "status": statuses[i % statuses.len()],
+                "createdAt": format!("2025-01-{:02}T10:00:00Z", i % 28 + 1)
+            })
+        })
+        .collect();
+    json!({ "orders": orders })
+}
+
+fn time_series(days: usize) -> Value {
+    let metrics: Vec<Value> = (0..days)
+        .map(|i| {
+            json!({
+                "date": format!("2025-01-{:02}", i % 28 + 1),
+                "views": 5000 + i * 100,
+                "clicks": 200 + i * 10,
+                "conversions": 20 + i % 10,
+                "revenue": 7000.25 + i as f64 * 50.5
+            })
+        })
+        .collect();
+    json!({ "metrics": metrics })
+}
+
+fn event_logs(count: usize) -> Value {
+    let logs: Vec<Value> = (0..count)
+        .map(|i| {
+            if i % 5 == 4 {
+                json!({
+                    "timestamp": format!("2025-01-15T10:{:02}:00Z", i % 60),
+                    "level": "ERROR",
+                    "message": format!("Error processing request {}", i + 1),
+                    "error": {"code": 500, "type": "InternalError"}
+                })
+            } else {
+                json!({
+                    "timestamp": format!("2025-01-15T10:{:02}:00Z", i % 60),
+                    "level": "INFO",
+                    "message": format!("Request processed in {}ms", 10 + i % 50)
+                })
+            }
+        })
+        .collect();
+    json!({ "service": "api-gateway", "logs": logs })
+}
+
+fn write_file(path: &Path, content: &str) -> ConversionResult<()> {
+    std::fs::write(path, format!("{}\n", content)).map_err(|e| write_error(path, e))
+}
+
+fn write_error(path: &Path, error: std::io::Error) -> ConversionError {
+    ConversionError::conversion(ConversionErrorKind::io(
+        format!("Failed to write {}: {}", path.display(), error),
+        Some(path.to_path_buf()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::{decode_toon, DecodeOptions};
+
+    #[test]
+    fn test_examples_are_written_in_pairs_with_readme() {
+        let tmp = tempfile::tempdir().unwrap();
+        let written = write_examples(tmp.path(), Some(3), &ConversionConfig::default()).unwrap();
+
+        assert_eq!(written.len(), 9);
+        let toon = std::fs::read_to_string(tmp.path().join("employees.toon")).unwrap();
+        assert!(toon
+            .starts_with("employees[3]{id,name,email,department,salary,yearsExperience,active}:"));
+
+        let readme = std::fs::read_to_string(tmp.path().join("README.md")).unwrap();
+        assert!(readme.contains("| [orders](orders.toon) |"));
+    }
+
+    #[test]
+    fn test_examples_round_trip() {
+        let config = ConversionConfig::default();
+        for example in datasets(6) {
+            let toon = convert_json_to_toon(&example.value, &config).unwrap();
+            let decoded = decode_toon(&toon.content, &DecodeOptions::strict()).unwrap();
+            assert_eq!(decoded.value, example.value, "{}", example.name);
+        }
+    }
+
+    #[test]
+    fn test_reduction_is_relative_to_json() {
+        let savings = ExampleSavings {
+            name: "employees",
+            description: "",
+            json_tokens: 200,
+            toon_tokens: 50,
+        };
+        assert_eq!(savings.reduction(), 75.0);
+    }
+}
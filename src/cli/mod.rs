@@ -2,14 +2,26 @@
 
 use clap::{Parser, Subcommand, ValueEnum};
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use crate::conversion::config::{DelimiterType, QuoteStrategy};
+use crate::conversion::config::{DelimiterType, PerformanceProfile};
+use crate::conversion::stats::{PerformanceTargets, SavingsThresholds};
 use crate::conversion::{ConversionConfig, ConversionResult};
 use crate::error::{ConversionError, ConversionErrorKind};
+use crate::parser::directory::DirectoryWalk;
 
+pub mod benchmark;
+pub mod examples;
 pub mod path_mapping;
+pub mod project_config;
+pub mod report;
+pub mod summary;
+
+use project_config::ProjectConfig;
+
+/// Exit code when a `--min-reduction` or `--max-output-tokens` threshold is missed
+pub const THRESHOLD_EXIT_CODE: i32 = 3;
 
 /// Main CLI arguments
 #[derive(Parser, Debug, Clone)]
@@ -31,9 +43,41 @@ pub struct Args {
     pub stdin: bool,
 
     /// Recursively process directories
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub recursive: bool,
 
+    /// Only take files matching this glob in directory mode (repeatable)
+    #[arg(long, global = true, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories matching this glob in directory mode (repeatable)
+    #[arg(long, global = true, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Also take files with this extension in directory mode, e.g. geojson,jsonl
+    #[arg(long, global = true, value_name = "EXT", value_delimiter = ',')]
+    pub extension: Vec<String>,
+
+    /// Descend at most this many directory levels (1 = only the directory's own files); implies --recursive
+    #[arg(long, global = true, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Follow symbolic links to directories
+    #[arg(long, global = true)]
+    pub follow_symlinks: bool,
+
+    /// Do not read .gitignore and .toonignore files in directory mode
+    #[arg(long, global = true)]
+    pub no_ignore: bool,
+
+    /// Result format: text, json for per-file results, or junit/sarif for validate
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    pub report_format: ReportFormat,
+
+    /// Preset settings: speed, memory, balanced, llm-min or readable (default: balanced)
+    #[arg(long, value_enum)]
+    pub profile: Option<Profile>,
+
     /// Spaces per indentation level (0-8, default: 2)
     #[arg(long)]
     pub indent: Option<u8>,
@@ -42,10 +86,6 @@ pub struct Args {
     #[arg(long)]
     pub delimiter: Option<Delimiter>,
 
-    /// Include length markers in arrays
-    #[arg(long)]
-    pub length_marker: bool,
-
     /// Disable pretty-printing
     #[arg(long)]
     pub plain: bool,
@@ -62,6 +102,30 @@ pub struct Args {
     #[arg(long)]
     pub simd: bool,
 
+    /// Performance target: maximum average conversion time per file in ms (default: 1000)
+    #[arg(long, value_name = "MS")]
+    pub target_avg_ms: Option<f32>,
+
+    /// Performance target: maximum peak memory, e.g. 200MB (default: 100MB)
+    #[arg(long, value_name = "SIZE")]
+    pub target_memory: Option<String>,
+
+    /// Performance target: minimum token reduction in percent (default: 20)
+    #[arg(long, value_name = "PERCENT")]
+    pub target_reduction: Option<f32>,
+
+    /// Performance target: minimum throughput in MB/s (default: 1)
+    #[arg(long, value_name = "MB/S")]
+    pub target_throughput: Option<f32>,
+
+    /// Fail with exit code 3 if a file, or the run as a whole, saves fewer tokens than this percent
+    #[arg(long, value_name = "PERCENT")]
+    pub min_reduction: Option<f32>,
+
+    /// Fail with exit code 3 if a file converts to more tokens than this
+    #[arg(long, value_name = "N")]
+    pub max_output_tokens: Option<usize>,
+
     /// Only validate JSON, don't convert
     #[arg(long)]
     pub validate_only: bool,
@@ -70,23 +134,116 @@ pub struct Args {
     #[arg(long)]
     pub stats: bool,
 
-    /// Enable verbose logging
+    /// Write a source map linking output lines to JSON Pointers next to the output (<output>.map)
     #[arg(long)]
+    pub source_map: bool,
+
+    /// Enable verbose logging
+    #[arg(long, global = true)]
     pub verbose: bool,
 
     /// Suppress non-error output
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub quiet: bool,
 
     /// Continue converting other files when one file fails
     #[arg(long)]
     pub continue_on_error: bool,
 
+    /// Input format: json, json5, ndjson, csv, tsv, xml, toml, msgpack, cbor, or bson (default: detected from file extension)
+    #[arg(long)]
+    pub input_format: Option<String>,
+
+    /// Accept comments, trailing commas, single quotes, unquoted keys and hex numbers in JSON input
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// Recover truncated JSON: close open strings, arrays and objects, drop the cut-off last element
+    #[arg(long, conflicts_with = "lenient")]
+    pub repair: bool,
+
+    /// Field delimiter for CSV/TSV input (default: ',' for csv, tab for tsv)
+    #[arg(long)]
+    pub csv_delimiter: Option<char>,
+
+    /// Treat the first CSV/TSV row as data rather than a header row
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Keep CSV/TSV cells as strings instead of inferring column types
+    #[arg(long)]
+    pub no_type_inference: bool,
+
+    /// Prefix for keys created from XML attributes
+    #[arg(long, default_value = "@")]
+    pub xml_attr_prefix: String,
+
+    /// Key holding the text of XML elements that also have attributes or children
+    #[arg(long, default_value = "#text")]
+    pub xml_text_key: String,
+
+    /// strftime-style format for TOML dates and times (default: RFC 3339 as written)
+    #[arg(long)]
+    pub toml_date_format: Option<String>,
+
+    /// Read settings from this file instead of discovering toonconv.toml files
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Ignore toonconv.toml files
+    #[arg(long, global = true, conflicts_with = "config")]
+    pub no_config: bool,
+
     /// Subcommands for advanced operations
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+impl Args {
+    /// Check if results should be reported as JSON
+    pub fn json_report(&self) -> bool {
+        self.report_format == ReportFormat::Json
+    }
+
+    /// Targets the directory run summary is checked against
+    pub fn performance_targets(&self) -> ConversionResult<PerformanceTargets> {
+        let defaults = PerformanceTargets::default();
+        Ok(PerformanceTargets {
+            max_avg_time_ms: self.target_avg_ms.unwrap_or(defaults.max_avg_time_ms),
+            max_memory_bytes: match &self.target_memory {
+                Some(_) => parse_memory_limit(&self.target_memory)?,
+                None => defaults.max_memory_bytes,
+            },
+            min_token_reduction: self
+                .target_reduction
+                .unwrap_or(defaults.min_token_reduction),
+            min_throughput_mbps: self
+                .target_throughput
+                .unwrap_or(defaults.min_throughput_mbps),
+        })
+    }
+
+    /// Thresholds from --min-reduction and --max-output-tokens
+    pub fn savings_thresholds(&self) -> SavingsThresholds {
+        SavingsThresholds {
+            min_reduction: self.min_reduction,
+            max_output_tokens: self.max_output_tokens,
+        }
+    }
+
+    /// File selection for directory inputs, shared by conversion and validation
+    pub fn directory_walk(&self) -> DirectoryWalk {
+        DirectoryWalk::new()
+            .with_recursive(self.recursive)
+            .with_max_depth(self.max_depth)
+            .with_follow_symlinks(self.follow_symlinks)
+            .with_include(self.include.clone())
+            .with_exclude(self.exclude.clone())
+            .with_extra_extensions(&self.extension)
+            .with_ignore_files(!self.no_ignore)
+    }
+}
+
 /// CLI subcommands
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
@@ -94,20 +251,50 @@ pub enum Commands {
     Validate {
         /// Input path (file or directory)
         input: String,
-        /// Output validation report
+        /// Write the validation report here, in the --report-format format
         #[arg(long)]
         output: Option<PathBuf>,
     },
-    /// Generate example TOON files
+    /// Generate paired JSON/TOON example files and a README of their token savings
     Examples {
-        /// Output directory for examples
+        /// Output directory for examples (default: examples)
         #[arg(long)]
         output_dir: Option<PathBuf>,
-        /// Number of examples to generate
+        /// Records in each example dataset (default: 10)
         #[arg(long)]
         count: Option<u32>,
     },
-    /// Performance benchmarking
+    /// Check a TOON document against a JSON Schema
+    Check {
+        /// TOON file to check ('-' reads standard input)
+        input: String,
+        /// JSON Schema the decoded document must match
+        #[arg(long)]
+        schema: PathBuf,
+        /// Recover from miscounted headers, uneven indentation and wrong cell counts
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// Render a TOON response template from a JSON Schema
+    Template {
+        /// JSON Schema describing the expected response
+        schema: PathBuf,
+        /// Write the template to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Set a value in a TOON file, leaving the rest of the file untouched
+    Set {
+        /// TOON file to edit
+        file: PathBuf,
+        /// Value to set, as a JSON Pointer (/users/0/name) or dotted path (users.0.name)
+        path: String,
+        /// New value as JSON; text that is not valid JSON is set as a string
+        value: String,
+    },
+    /// List the presets accepted by --profile and the settings each one implies
+    Profiles,
+    /// Time conversion and compare TOON's size and tokens with other formats
     Benchmark {
         /// Input file for benchmarking
         input: String,
@@ -117,11 +304,63 @@ pub enum Commands {
         /// Output benchmark results
         #[arg(long)]
         output: Option<PathBuf>,
+        /// Results format: markdown or json
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: BenchmarkFormat,
     },
 }
 
+/// Output formats for benchmark results
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchmarkFormat {
+    Markdown,
+    Json,
+}
+
+/// Formats for conversion and validation results
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// ✓/✗ lines for people
+    Text,
+    /// A single JSON document with a result per input
+    Json,
+    /// JUnit XML with a test case per input (validate only)
+    Junit,
+    /// SARIF 2.1.0 with a result per invalid input (validate only)
+    Sarif,
+}
+
+impl ReportFormat {
+    /// Check if the format only applies to `validate`
+    pub fn is_validation_only(self) -> bool {
+        matches!(self, Self::Junit | Self::Sarif)
+    }
+}
+
+/// Preset names accepted by `--profile`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Speed,
+    Memory,
+    Balanced,
+    LlmMin,
+    Readable,
+}
+
+impl From<Profile> for PerformanceProfile {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Speed => PerformanceProfile::Speed,
+            Profile::Memory => PerformanceProfile::Memory,
+            Profile::Balanced => PerformanceProfile::Balanced,
+            Profile::LlmMin => PerformanceProfile::LlmMin,
+            Profile::Readable => PerformanceProfile::Readable,
+        }
+    }
+}
+
 /// Delimiter types for CLI
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Debug, Clone, PartialEq, Eq)]
 pub enum Delimiter {
     #[value(name = "comma", alias = ",")]
     Comma,
@@ -144,8 +383,13 @@ impl From<Delimiter> for DelimiterType {
 /// CLI configuration
 #[derive(Debug, Clone)]
 pub struct CliConfig {
+    /// Arguments with project settings filled in
     pub args: Args,
     pub conversion_config: ConversionConfig,
+    /// `toonconv.toml` files applied, outermost first
+    pub project_files: Vec<PathBuf>,
+    /// Arguments exactly as given on the command line
+    command_line: Args,
 }
 
 impl CliConfig {
@@ -153,9 +397,63 @@ impl CliConfig {
     pub fn from_args(args: Args) -> ConversionResult<Self> {
         let conversion_config = Self::create_conversion_config(&args)?;
 
+        Ok(Self {
+            command_line: args.clone(),
+            args,
+            conversion_config,
+            project_files: Vec::new(),
+        })
+    }
+
+    /// Create CLI configuration from arguments and the `toonconv.toml` files
+    /// that apply to the input (or the working directory)
+    pub fn discover(args: Args) -> ConversionResult<Self> {
+        if args.no_config {
+            return Self::from_args(args);
+        }
+        if let Some(file) = &args.config {
+            let project = ProjectConfig::load(file)?;
+            let files = vec![file.clone()];
+            return Self::with_project(args, &project, files);
+        }
+
+        let dir = match args.input.as_deref().map(Path::new) {
+            Some(input) if input.is_dir() => input.to_path_buf(),
+            Some(input) if input.is_file() => input
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            _ => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        };
+        let (project, files) = ProjectConfig::discover(&dir)?;
+        Self::with_project(args, &project, files)
+    }
+
+    /// Conversion configuration for files in `dir`, which may have their own
+    /// `toonconv.toml` overriding the ones found for the input
+    pub fn config_for(&self, dir: &Path) -> ConversionResult<ConversionConfig> {
+        if self.command_line.no_config || self.command_line.config.is_some() {
+            return Ok(self.conversion_config.clone());
+        }
+        let (project, files) = ProjectConfig::discover(dir)?;
+        Ok(Self::with_project(self.command_line.clone(), &project, files)?.conversion_config)
+    }
+
+    fn with_project(
+        command_line: Args,
+        project: &ProjectConfig,
+        project_files: Vec<PathBuf>,
+    ) -> ConversionResult<Self> {
+        let mut args = command_line.clone();
+        project.apply_to_args(&mut args)?;
+        let mut conversion_config = Self::create_conversion_config(&args)?;
+        project.apply_to_config(&mut conversion_config)?;
+
         Ok(Self {
             args,
             conversion_config,
+            project_files,
+            command_line,
         })
     }
 
@@ -166,26 +464,34 @@ impl CliConfig {
 
     /// Create conversion configuration from CLI arguments
     fn create_conversion_config(args: &Args) -> ConversionResult<ConversionConfig> {
+        // Flags given explicitly override the settings implied by the profile
+        let base = PerformanceProfile::from(args.profile.unwrap_or(Profile::Balanced)).to_config();
         let delimiter = args
             .delimiter
             .as_ref()
             .map(|d| d.clone().into())
-            .unwrap_or(DelimiterType::Comma);
-        let memory_limit = parse_memory_limit(&args.memory_limit)?;
-        let timeout = Duration::from_secs(args.timeout.unwrap_or(300));
+            .unwrap_or(base.delimiter);
+        let memory_limit = match &args.memory_limit {
+            Some(_) => parse_memory_limit(&args.memory_limit)?,
+            None => base.memory_limit,
+        };
+        let timeout = args.timeout.map_or(base.timeout, Duration::from_secs);
+
+        if args.source_map && args.output.is_none() {
+            return Err(ConversionError::conversion(
+                ConversionErrorKind::configuration("--source-map requires --output".to_string()),
+            ));
+        }
 
         let config = ConversionConfig {
-            indent_size: args.indent.unwrap_or(2),
+            indent_size: args.indent.unwrap_or(base.indent_size),
             delimiter,
-            length_marker: args.length_marker,
-            quote_strings: QuoteStrategy::Smart,
             memory_limit,
             timeout,
-            enable_simd: args.simd,
-            pretty: !args.plain,
-            validate_output: true,
-            include_schema: true,
-            max_depth: Some(1000),
+            enable_simd: args.simd || base.enable_simd,
+            pretty: base.pretty && !args.plain,
+            source_map: args.source_map,
+            ..base
         };
 
         // Validate configuration
@@ -237,6 +543,21 @@ impl CliConfig {
     }
 }
 
+/// Every `--profile` preset with its description and settings
+pub fn describe_profiles() -> String {
+    PerformanceProfile::presets()
+        .iter()
+        .map(|profile| {
+            let mut out = format!("{}: {}\n", profile.name(), profile.description());
+            for (key, value) in profile.to_config().settings() {
+                out.push_str(&format!("  {:<16}{}\n", key, value));
+            }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Parse memory limit string (e.g., "100MB", "1GB", "500KB")
 fn parse_memory_limit(limit: &Option<String>) -> ConversionResult<usize> {
     match limit {
@@ -418,33 +739,92 @@ mod tests {
 
     #[test]
     fn test_cli_config_creation() {
-        let args = Args {
-            input: Some("test.json".to_string()),
-            output: None,
-            stdin: false,
-            recursive: false,
-            indent: Some(4),
-            delimiter: Some(Delimiter::Tab),
-            length_marker: true,
-            plain: false,
-            memory_limit: Some("50MB".to_string()),
-            timeout: Some(600),
-            simd: true,
-            validate_only: false,
-            stats: false,
-            verbose: false,
-            quiet: false,
-            command: None,
-            continue_on_error: false,
-        };
+        let args = Args::parse_from([
+            "toonconv",
+            "test.json",
+            "--indent",
+            "4",
+            "--delimiter",
+            "tab",
+            "--memory-limit",
+            "50MB",
+            "--timeout",
+            "600",
+            "--simd",
+        ]);
 
         let config = CliConfig::from_args(args).unwrap();
         assert_eq!(config.conversion_config.indent_size, 4);
         assert_eq!(config.conversion_config.delimiter, DelimiterType::Tab);
-        assert!(config.conversion_config.length_marker);
         assert!(config.conversion_config.enable_simd);
     }
 
+    #[test]
+    fn test_performance_targets() {
+        let args = Args::parse_from([
+            "toonconv",
+            "--target-reduction",
+            "35",
+            "--target-memory",
+            "1MB",
+        ]);
+        let targets = args.performance_targets().unwrap();
+        assert_eq!(targets.min_token_reduction, 35.0);
+        assert_eq!(targets.max_memory_bytes, 1024 * 1024);
+        assert_eq!(
+            targets.min_throughput_mbps,
+            PerformanceTargets::default().min_throughput_mbps
+        );
+
+        let mut args = Args::parse_from(["toonconv"]);
+        ProjectConfig::parse("target-throughput = 5.0\n")
+            .unwrap()
+            .apply_to_args(&mut args)
+            .unwrap();
+        assert_eq!(args.performance_targets().unwrap().min_throughput_mbps, 5.0);
+    }
+
+    #[test]
+    fn test_profile_flags_override_preset() {
+        let config = |flags: &[&str]| {
+            let args = Args::parse_from(["toonconv"].iter().chain(flags));
+            CliConfig::from_args(args).unwrap().conversion_config
+        };
+
+        let llm_min = config(&["--profile", "llm-min"]);
+        assert_eq!(llm_min.indent_size, 1);
+
+        let overridden = config(&["--profile", "llm-min", "--indent", "3"]);
+        assert_eq!(overridden.indent_size, 3);
+
+        let speed = config(&["--profile", "speed", "--timeout", "5"]);
+        assert!(speed.enable_simd);
+        assert_eq!(speed.timeout, Duration::from_secs(5));
+
+        assert!(describe_profiles().contains("llm-min: "));
+    }
+
+    #[test]
+    fn test_source_map_requires_output() {
+        let args = Args::parse_from(["toonconv", "in.json", "--source-map"]);
+        assert!(CliConfig::from_args(args).is_err());
+
+        let args = Args::parse_from(["toonconv", "in.json", "--source-map", "-o", "out.toon"]);
+        assert!(
+            CliConfig::from_args(args)
+                .unwrap()
+                .conversion_config
+                .source_map
+        );
+    }
+
+    #[test]
+    fn test_global_flags_after_subcommand() {
+        let args = Args::parse_from(["toonconv", "validate", "fixtures", "--recursive", "--quiet"]);
+        assert!(args.recursive && args.quiet);
+        assert!(matches!(args.command, Some(Commands::Validate { .. })));
+    }
+
     #[test]
     fn test_file_size_formatting() {
         assert_eq!(CliUtils::format_file_size(1024), "1.0 KB");
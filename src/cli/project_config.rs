@@ -0,0 +1,260 @@
+//! `toonconv.toml` project configuration
+//!
+//! Settings are read from every `toonconv.toml` between the filesystem root
+//! and the directory being converted; a file closer to the input overrides
+//! the ones above it, and flags given on the command line override them all.
+//! Keys use the same names as the command-line flags:
+//!
+//! ```toml
+//! profile = "llm-min"
+//! indent = 4
+//! delimiter = "tab"
+//! memory-limit = "200MB"
+//! quote-strings = "smart"
+//! ```
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{Args, Delimiter, Profile};
+use crate::conversion::config::QuoteStrategy;
+use crate::conversion::{ConversionConfig, ConversionResult};
+use crate::error::{ConversionError, ConversionErrorKind};
+
+pub use crate::parser::directory::PROJECT_CONFIG_FILE;
+
+/// Settings from one or more `toonconv.toml` files; unset keys are `None`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub profile: Option<String>,
+    pub indent: Option<u8>,
+    pub delimiter: Option<String>,
+    pub plain: Option<bool>,
+    pub memory_limit: Option<String>,
+    pub timeout: Option<u64>,
+    pub simd: Option<bool>,
+    pub quote_strings: Option<String>,
+    pub max_depth: Option<usize>,
+    pub target_avg_ms: Option<f32>,
+    pub target_memory: Option<String>,
+    pub target_reduction: Option<f32>,
+    pub target_throughput: Option<f32>,
+    pub min_reduction: Option<f32>,
+    pub max_output_tokens: Option<usize>,
+}
+
+impl ProjectConfig {
+    /// Parse a `toonconv.toml` document
+    pub fn parse(content: &str) -> ConversionResult<Self> {
+        toml::from_str(content).map_err(|e| configuration(e.message().to_string()))
+    }
+
+    /// Read and parse a `toonconv.toml` file
+    pub fn load(path: &Path) -> ConversionResult<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ConversionError::conversion(ConversionErrorKind::io(
+                format!("Cannot read {}: {}", path.display(), e),
+                Some(path.to_path_buf()),
+            ))
+        })?;
+        toml::from_str(&content)
+            .map_err(|e| configuration(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Config files that apply to `dir`, outermost first
+    pub fn find_files(dir: &Path) -> Vec<PathBuf> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut files: Vec<PathBuf> = dir
+            .ancestors()
+            .map(|ancestor| ancestor.join(PROJECT_CONFIG_FILE))
+            .filter(|file| file.is_file())
+            .collect();
+        files.reverse();
+        files
+    }
+
+    /// Merge every config file that applies to `dir`
+    pub fn discover(dir: &Path) -> ConversionResult<(Self, Vec<PathBuf>)> {
+        let files = Self::find_files(dir);
+        let mut merged = Self::default();
+        for file in &files {
+            merged = merged.merge(Self::load(file)?);
+        }
+        Ok((merged, files))
+    }
+
+    /// Overlay `other` onto `self`; keys set in `other` win
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            profile: other.profile.or(self.profile),
+            indent: other.indent.or(self.indent),
+            delimiter: other.delimiter.or(self.delimiter),
+            plain: other.plain.or(self.plain),
+            memory_limit: other.memory_limit.or(self.memory_limit),
+            timeout: other.timeout.or(self.timeout),
+            simd: other.simd.or(self.simd),
+            quote_strings: other.quote_strings.or(self.quote_strings),
+            max_depth: other.max_depth.or(self.max_depth),
+            target_avg_ms: other.target_avg_ms.or(self.target_avg_ms),
+            target_memory: other.target_memory.or(self.target_memory),
+            target_reduction: other.target_reduction.or(self.target_reduction),
+            target_throughput: other.target_throughput.or(self.target_throughput),
+            min_reduction: other.min_reduction.or(self.min_reduction),
+            max_output_tokens: other.max_output_tokens.or(self.max_output_tokens),
+        }
+    }
+
+    /// Fill in the arguments not given on the command line
+    pub fn apply_to_args(&self, args: &mut Args) -> ConversionResult<()> {
+        if args.profile.is_none() {
+            if let Some(profile) = &self.profile {
+                let parsed = Profile::from_str(profile, true).map_err(|_| {
+                    configuration(format!(
+                        "Invalid profile '{}'. Use 'speed', 'memory', 'balanced', 'llm-min', or 'readable'",
+                        profile
+                    ))
+                })?;
+                args.profile = Some(parsed);
+            }
+        }
+        if args.indent.is_none() {
+            args.indent = self.indent;
+        }
+        if args.delimiter.is_none() {
+            if let Some(delimiter) = &self.delimiter {
+                let parsed = Delimiter::from_str(delimiter, true).map_err(|_| {
+                    configuration(format!(
+                        "Invalid delimiter '{}'. Use 'comma', 'tab', or 'pipe'",
+                        delimiter
+                    ))
+                })?;
+                args.delimiter = Some(parsed);
+            }
+        }
+        if args.memory_limit.is_none() {
+            args.memory_limit = self.memory_limit.clone();
+        }
+        if args.timeout.is_none() {
+            args.timeout = self.timeout;
+        }
+        if args.target_avg_ms.is_none() {
+            args.target_avg_ms = self.target_avg_ms;
+        }
+        if args.target_memory.is_none() {
+            args.target_memory = self.target_memory.clone();
+        }
+        if args.target_reduction.is_none() {
+            args.target_reduction = self.target_reduction;
+        }
+        if args.target_throughput.is_none() {
+            args.target_throughput = self.target_throughput;
+        }
+        if args.min_reduction.is_none() {
+            args.min_reduction = self.min_reduction;
+        }
+        if args.max_output_tokens.is_none() {
+            args.max_output_tokens = self.max_output_tokens;
+        }
+        args.plain |= self.plain.unwrap_or(false);
+        args.simd |= self.simd.unwrap_or(false);
+        Ok(())
+    }
+
+    /// Apply the settings that have no command-line flag
+    pub fn apply_to_config(&self, config: &mut ConversionConfig) -> ConversionResult<()> {
+        if let Some(strategy) = &self.quote_strings {
+            config.quote_strings = match strategy.as_str() {
+                "smart" => QuoteStrategy::Smart,
+                "always" => QuoteStrategy::Always,
+                "never" => QuoteStrategy::Never,
+                other => {
+                    return Err(configuration(format!(
+                        "Invalid quote-strings '{}'. Use 'smart', 'always', or 'never'",
+                        other
+                    )))
+                }
+            };
+        }
+        if let Some(max_depth) = self.max_depth {
+            config.max_depth = Some(max_depth);
+        }
+        config
+            .validate()
+            .map_err(|e| ConversionError::conversion(ConversionErrorKind::configuration(e)))
+    }
+}
+
+fn configuration(message: String) -> ConversionError {
+    ConversionError::conversion(ConversionErrorKind::configuration(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_kebab_case_keys() {
+        let config =
+            ProjectConfig::parse("indent = 4\ndelimiter = \"pipe\"\nmemory-limit = \"1MB\"\n")
+                .unwrap();
+        assert_eq!(config.indent, Some(4));
+        assert_eq!(config.delimiter.as_deref(), Some("pipe"));
+        assert_eq!(config.memory_limit.as_deref(), Some("1MB"));
+
+        assert!(ProjectConfig::parse("indnet = 4").is_err());
+    }
+
+    #[test]
+    fn test_nested_files_override_parents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let nested = tmp.path().join("a/b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            tmp.path().join(PROJECT_CONFIG_FILE),
+            "indent = 4\ntimeout = 60\n",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.path().join("a").join(PROJECT_CONFIG_FILE),
+            "indent = 8\n",
+        )
+        .unwrap();
+
+        let (config, files) = ProjectConfig::discover(&nested).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(config.indent, Some(8));
+        assert_eq!(config.timeout, Some(60));
+    }
+
+    #[test]
+    fn test_command_line_flags_take_precedence() {
+        let project =
+            ProjectConfig::parse("indent = 4\ndelimiter = \"tab\"\nplain = true\n").unwrap();
+        let mut args = Args::parse_from(["toonconv", "in.json", "--indent", "6"]);
+        project.apply_to_args(&mut args).unwrap();
+
+        assert_eq!(args.indent, Some(6));
+        assert_eq!(args.delimiter, Some(Delimiter::Tab));
+        assert!(args.plain);
+    }
+
+    #[test]
+    fn test_profile_key() {
+        let project = ProjectConfig::parse("profile = \"llm-min\"\n").unwrap();
+        let mut args = Args::parse_from(["toonconv", "in.json"]);
+        project.apply_to_args(&mut args).unwrap();
+        assert_eq!(args.profile, Some(Profile::LlmMin));
+
+        let mut args = Args::parse_from(["toonconv", "in.json", "--profile", "readable"]);
+        project.apply_to_args(&mut args).unwrap();
+        assert_eq!(args.profile, Some(Profile::Readable));
+
+        let project = ProjectConfig::parse("profile = \"tiny\"\n").unwrap();
+        assert!(project
+            .apply_to_args(&mut Args::parse_from(["toonconv", "in.json"]))
+            .is_err());
+    }
+}
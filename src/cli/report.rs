@@ -0,0 +1,326 @@
+//! `--report-format json|junit|sarif`: machine-readable results of a run
+//!
+//! Each input gets a [`FileReport`] with its paths, the conversion metadata
+//! or the error it failed with. Totals across the run reuse
+//! [`ConversionStatistics`], so they serialize the same way as
+//! [`ConversionStatistics::to_json`]. Validation runs can also be rendered
+//! as JUnit XML or SARIF, so CI can annotate the failing lines.
+
+use quick_xml::escape::escape;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::cli::summary::SummaryReport;
+use crate::conversion::engine::{ConversionMetadata, ToonData};
+use crate::conversion::stats::ConversionStatistics;
+use crate::error::ErrorReport;
+
+/// Result of converting or validating one input
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// Input path, or `<stdin>`/`<argument>` for inline input
+    pub input: String,
+    pub output: Option<PathBuf>,
+    pub success: bool,
+    pub metadata: Option<ConversionMetadata>,
+    /// TOON output, when it was not written to a file
+    pub content: Option<String>,
+    pub error: Option<ErrorReport>,
+}
+
+impl FileReport {
+    /// A successful conversion; without an output path the TOON is kept inline
+    pub fn converted(input: String, output: Option<PathBuf>, toon_data: &ToonData) -> Self {
+        let content = output.is_none().then(|| toon_data.content.clone());
+        Self {
+            input,
+            output,
+            success: true,
+            metadata: Some(toon_data.metadata.clone()),
+            content,
+            error: None,
+        }
+    }
+
+    /// Mark a finished conversion as failed, e.g. for missing a savings threshold
+    pub fn with_error(mut self, error: &anyhow::Error) -> Self {
+        self.success = false;
+        self.error = Some(ErrorReport::from_error(error));
+        self
+    }
+
+    /// An input that passed validation
+    pub fn valid(input: String) -> Self {
+        Self {
+            input,
+            output: None,
+            success: true,
+            metadata: None,
+            content: None,
+            error: None,
+        }
+    }
+
+    /// An input that failed to convert or validate
+    pub fn failed(input: String, output: Option<PathBuf>, error: &anyhow::Error) -> Self {
+        Self {
+            input,
+            output,
+            success: false,
+            metadata: None,
+            content: None,
+            error: Some(ErrorReport::from_error(error)),
+        }
+    }
+}
+
+/// Results of every input in a run
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    /// `convert` or `validate`
+    pub command: &'static str,
+    pub success: bool,
+    pub files: Vec<FileReport>,
+    /// Totals over the successful conversions
+    pub statistics: Option<ConversionStatistics>,
+    /// Token counts, rankings and performance check of a directory run
+    pub summary: Option<SummaryReport>,
+}
+
+impl RunReport {
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            success: true,
+            files: Vec::new(),
+            statistics: None,
+            summary: None,
+        }
+    }
+
+    /// Add one input's result, folding its metadata into the totals
+    pub fn push(&mut self, file: FileReport) {
+        self.success &= file.success;
+        if let Some(metadata) = &file.metadata {
+            let stats = ConversionStatistics::for_conversion(
+                metadata.input_size,
+                metadata.output_size,
+                Duration::from_millis(metadata.processing_time_ms),
+                metadata.memory_peak_kb * 1024,
+            );
+            match &mut self.statistics {
+                Some(total) => total.combine(&stats),
+                None => self.statistics = Some(stats),
+            }
+        }
+        self.files.push(file);
+    }
+
+    /// Number of inputs that failed
+    pub fn failed_count(&self) -> usize {
+        self.files.iter().filter(|file| !file.success).count()
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("run reports serialize to JSON")
+    }
+
+    /// Render as a JUnit XML test suite with one test case per input
+    pub fn to_junit(&self) -> String {
+        let name = format!("toonconv {}", self.command);
+        let counts = format!(
+            r#"tests="{}" failures="{}""#,
+            self.files.len(),
+            self.failed_count()
+        );
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!("<testsuites name=\"{}\" {}>\n", name, counts));
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" {} errors=\"0\">\n",
+            name, counts
+        ));
+        for file in &self.files {
+            let input = escape(&file.input);
+            let testcase = format!(
+                r#"    <testcase name="{}" classname="toonconv.{}" file="{}""#,
+                input, self.command, input
+            );
+            match &file.error {
+                None => out.push_str(&format!("{}/>\n", testcase)),
+                Some(error) => {
+                    let position = error
+                        .location
+                        .map(|l| format!(":{}:{}", l.line, l.column))
+                        .unwrap_or_default();
+                    out.push_str(&format!("{}>\n", testcase));
+                    out.push_str(&format!(
+                        "      <failure type=\"{}\" message=\"{}\">{}{}: {}</failure>\n",
+                        error.kind,
+                        escape(&error.message),
+                        input,
+                        position,
+                        escape(&error.message)
+                    ));
+                    out.push_str("    </testcase>\n");
+                }
+            }
+        }
+        out.push_str("  </testsuite>\n</testsuites>");
+        out
+    }
+
+    /// Render as a SARIF 2.1.0 log with one result per failed input
+    pub fn to_sarif(&self) -> String {
+        let errors: Vec<_> = self
+            .files
+            .iter()
+            .filter_map(|file| Some((file, file.error.as_ref()?)))
+            .collect();
+
+        // One rule per error kind, in a stable order
+        let kinds: BTreeSet<_> = errors.iter().map(|(_, error)| error.kind).collect();
+        let rules: Vec<_> = kinds
+            .into_iter()
+            .map(|kind| json!({ "id": kind, "name": kind }))
+            .collect();
+
+        let results: Vec<_> = errors
+            .iter()
+            .map(|(file, error)| {
+                let mut location = json!({
+                    "artifactLocation": { "uri": file.input.replace('\\', "/") }
+                });
+                if let Some(at) = error.location {
+                    location["region"] = json!({
+                        "startLine": at.line,
+                        "startColumn": at.column.max(1),
+                    });
+                }
+                json!({
+                    "ruleId": error.kind,
+                    "level": "error",
+                    "message": { "text": error.message },
+                    "locations": [{ "physicalLocation": location }],
+                })
+            })
+            .collect();
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "toonconv",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&log).expect("SARIF logs serialize to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use crate::error::{ConversionError, ConversionErrorKind};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_report_lists_files_and_totals() {
+        let toon_data = convert_json_to_toon(
+            &json!({"users": [{"id": 1}, {"id": 2}]}),
+            &ConversionConfig::default(),
+        )
+        .unwrap();
+
+        let mut report = RunReport::new("convert");
+        report.push(FileReport::converted(
+            "a.json".to_string(),
+            Some(PathBuf::from("a.toon")),
+            &toon_data,
+        ));
+        report.push(FileReport::converted(
+            "<stdin>".to_string(),
+            None,
+            &toon_data,
+        ));
+        report.push(FileReport::failed(
+            "b.json".to_string(),
+            None,
+            &anyhow::anyhow!("broken"),
+        ));
+        report.push(
+            FileReport::converted("c.json".to_string(), None, &toon_data).with_error(
+                &ConversionError::conversion(ConversionErrorKind::threshold_not_met(
+                    "Output tokens: 12 (maximum: 10)".to_string(),
+                ))
+                .into(),
+            ),
+        );
+
+        assert!(!report.success);
+        assert_eq!(report.failed_count(), 2);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["files"][0]["output"], "a.toon");
+        assert!(json["files"][0]["content"].is_null());
+        assert_eq!(json["files"][1]["content"], toon_data.content.as_str());
+        assert_eq!(
+            json["files"][0]["metadata"]["schema_info"]["uniform_arrays"][0]["element_count"],
+            2
+        );
+        assert_eq!(json["files"][2]["error"]["kind"], "other");
+        assert_eq!(json["files"][3]["error"]["kind"], "threshold_not_met");
+        assert!(json["files"][3]["metadata"].is_object());
+        assert_eq!(json["statistics"]["file_count"], 3);
+    }
+
+    fn validation_run() -> RunReport {
+        let mut run = RunReport::new("validate");
+        run.push(FileReport::valid("fixtures/good.json".to_string()));
+        run.push(FileReport::failed(
+            "fixtures/bad <1>.json".to_string(),
+            None,
+            &ConversionError::conversion(ConversionErrorKind::json_parse(
+                "expected value".to_string(),
+                Some((4, 1)),
+            ))
+            .into(),
+        ));
+        run
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let xml = validation_run().to_junit();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains(r#"<testsuite name="toonconv validate" tests="2" failures="1""#));
+        assert!(xml.contains(r#"<testcase name="fixtures/good.json" classname="toonconv.validate" file="fixtures/good.json"/>"#));
+        assert!(xml.contains(r#"<failure type="json_parse""#));
+        assert!(xml.contains(">fixtures/bad &lt;1&gt;.json:4:1: "));
+    }
+
+    #[test]
+    fn test_sarif_report() {
+        let sarif: Value = serde_json::from_str(&validation_run().to_sarif()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "json_parse");
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "json_parse");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "fixtures/bad <1>.json");
+        assert_eq!(location["region"]["startLine"], 4);
+        assert_eq!(location["region"]["startColumn"], 1);
+    }
+}
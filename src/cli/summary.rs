@@ -0,0 +1,321 @@
+//! Run summary printed after a directory conversion
+//!
+//! Every converted file is recorded with its sizes, `cl100k_base` token
+//! counts and conversion time. Totals come from a [`PerformanceTracker`]
+//! and are checked against [`PerformanceTargets`]; the run time is wall
+//! clock, while throughput only counts the time spent converting files.
+//! [`SavingsThresholds`] are checked per file and against the run's overall
+//! token reduction.
+
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::cli::benchmark::count_tokens;
+use crate::conversion::stats::{
+    ConversionStatistics, PerformanceCheck, PerformanceTargets, PerformanceTracker,
+    SavingsThresholds,
+};
+
+/// Files listed under the slowest and worst-compressing headings
+pub const LISTED_FILES: usize = 3;
+
+/// Sizes and timing of one converted file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStatistics {
+    pub path: String,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub processing_time_ms: f64,
+}
+
+impl FileStatistics {
+    /// Measure a conversion of `input` to `output` that took `elapsed`
+    pub fn measure(path: String, input: &str, output: &str, elapsed: Duration) -> Self {
+        Self {
+            path,
+            input_bytes: input.len() as u64,
+            output_bytes: output.len() as u64,
+            input_tokens: count_tokens(input),
+            output_tokens: count_tokens(output),
+            processing_time_ms: elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+
+    /// Percentage of input tokens saved
+    pub fn token_reduction(&self) -> f64 {
+        reduction(self.input_tokens, self.output_tokens)
+    }
+
+    /// Check this file's token savings against `thresholds`
+    pub fn check_savings(&self, thresholds: &SavingsThresholds) -> PerformanceCheck {
+        thresholds.check(self.token_reduction() as f32, self.output_tokens)
+    }
+}
+
+/// Collects file statistics while a directory is converted
+pub struct RunSummary {
+    tracker: PerformanceTracker,
+    files: Vec<FileStatistics>,
+}
+
+impl RunSummary {
+    /// Start timing a run
+    pub fn start() -> Self {
+        // Load the tokenizer first so its one-off setup is not timed
+        count_tokens("");
+        Self {
+            tracker: PerformanceTracker::start(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Record one converted file
+    pub fn record(&mut self, file: FileStatistics, memory_peak: usize) {
+        self.tracker.record(&ConversionStatistics::for_conversion(
+            file.input_bytes,
+            file.output_bytes,
+            Duration::from_secs_f64(file.processing_time_ms / 1000.0),
+            memory_peak,
+        ));
+        self.files.push(file);
+    }
+
+    /// Stop timing and check the totals against `targets` and `thresholds`
+    pub fn finish(
+        self,
+        targets: &PerformanceTargets,
+        thresholds: &SavingsThresholds,
+    ) -> SummaryReport {
+        let input_tokens = self.files.iter().map(|file| file.input_tokens).sum();
+        let output_tokens = self.files.iter().map(|file| file.output_tokens).sum();
+
+        let mut statistics = self.tracker.finish_run();
+        // Real token counts replace the byte-based estimate
+        statistics.token_reduction_percent = reduction(input_tokens, output_tokens) as f32;
+        // Throughput counts time spent converting, not counting tokens
+        let converting_secs: f64 = self
+            .files
+            .iter()
+            .map(|file| file.processing_time_ms / 1000.0)
+            .sum();
+        if converting_secs > 0.0 {
+            statistics.throughput_bytes_per_sec =
+                (statistics.input_size_bytes as f64 / converting_secs) as f32;
+        }
+        let check = statistics.meets_targets(targets);
+        // The output token limit is per file, so only the reduction applies to the run
+        let savings = thresholds.is_enabled().then(|| {
+            SavingsThresholds {
+                max_output_tokens: None,
+                ..thresholds.clone()
+            }
+            .check(statistics.token_reduction_percent, output_tokens)
+        });
+
+        SummaryReport {
+            statistics,
+            input_tokens,
+            output_tokens,
+            files: self.files,
+            check,
+            savings,
+        }
+    }
+}
+
+/// Totals of a finished run
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryReport {
+    pub statistics: ConversionStatistics,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub files: Vec<FileStatistics>,
+    pub check: PerformanceCheck,
+    /// Overall savings threshold check, when thresholds were set
+    pub savings: Option<PerformanceCheck>,
+}
+
+impl SummaryReport {
+    /// Files that took longest to convert, slowest first
+    pub fn slowest(&self, count: usize) -> Vec<&FileStatistics> {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|a, b| b.processing_time_ms.total_cmp(&a.processing_time_ms));
+        files.truncate(count);
+        files
+    }
+
+    /// Files with the smallest token reduction, worst first
+    pub fn worst_compressing(&self, count: usize) -> Vec<&FileStatistics> {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|a, b| a.token_reduction().total_cmp(&b.token_reduction()));
+        files.truncate(count);
+        files
+    }
+
+    /// Render for the terminal
+    pub fn to_text(&self) -> String {
+        let stats = &self.statistics;
+        let mut out = format!(
+            "\nRun summary:\n\
+             \x20 Files:      {}\n\
+             \x20 Bytes:      {} -> {} ({})\n\
+             \x20 Tokens:     {} -> {} ({})\n\
+             \x20 Time:       {:.2}s ({:.2} MB/s)\n",
+            stats.file_count,
+            stats.input_size_bytes,
+            stats.output_size_bytes,
+            change(
+                reduction(
+                    stats.input_size_bytes as usize,
+                    stats.output_size_bytes as usize
+                ),
+                "smaller",
+                "larger"
+            ),
+            self.input_tokens,
+            self.output_tokens,
+            change(stats.token_reduction_percent as f64, "fewer", "more"),
+            stats.processing_time_ms as f64 / 1000.0,
+            stats.throughput_bytes_per_sec / (1024.0 * 1024.0),
+        );
+
+        out.push_str("Slowest files:\n");
+        for file in self.slowest(LISTED_FILES) {
+            out.push_str(&format!(
+                "  {:>9.2}ms  {}\n",
+                file.processing_time_ms, file.path
+            ));
+        }
+        out.push_str("Worst-compressing files:\n");
+        for file in self.worst_compressing(LISTED_FILES) {
+            out.push_str(&format!(
+                "  {:>9.1}%   {}\n",
+                file.token_reduction(),
+                file.path
+            ));
+        }
+
+        out.push_str(&format!("Performance targets: {}\n", self.check.summary()));
+        for passed in &self.check.passed {
+            out.push_str(&format!("  ✓ {}\n", passed));
+        }
+        for failed in &self.check.failed {
+            out.push_str(&format!("  ✗ {}\n", failed));
+        }
+
+        if let Some(savings) = &self.savings {
+            let status = if savings.is_success() {
+                "met"
+            } else {
+                "not met"
+            };
+            out.push_str(&format!("Savings thresholds: {}\n", status));
+            for passed in &savings.passed {
+                out.push_str(&format!("  ✓ {}\n", passed));
+            }
+            for failed in &savings.failed {
+                out.push_str(&format!("  ✗ {}\n", failed));
+            }
+        }
+        out
+    }
+}
+
+/// Describe a reduction, e.g. `12.0% fewer` or, when it grew, `12.0% more`
+fn change(reduction: f64, smaller: &str, larger: &str) -> String {
+    if reduction < 0.0 {
+        format!("{:.1}% {}", -reduction, larger)
+    } else {
+        format!("{:.1}% {}", reduction, smaller)
+    }
+}
+
+/// Percentage saved going from `before` to `after`, negative when it grew
+fn reduction(before: usize, after: usize) -> f64 {
+    if before == 0 {
+        return 0.0;
+    }
+    (before as f64 - after as f64) / before as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, input_tokens: usize, output_tokens: usize, ms: f64) -> FileStatistics {
+        FileStatistics {
+            path: path.to_string(),
+            input_bytes: input_tokens as u64 * 4,
+            output_bytes: output_tokens as u64 * 4,
+            input_tokens,
+            output_tokens,
+            processing_time_ms: ms,
+        }
+    }
+
+    #[test]
+    fn test_summary_totals_and_rankings() {
+        let mut run = RunSummary::start();
+        run.record(file("a.json", 100, 50, 1.0), 1024);
+        run.record(file("b.json", 100, 90, 5.0), 1024);
+        run.record(file("c.json", 200, 60, 2.0), 1024);
+        let summary = run.finish(
+            &PerformanceTargets {
+                min_token_reduction: 90.0,
+                ..Default::default()
+            },
+            &SavingsThresholds::default(),
+        );
+
+        assert_eq!(summary.statistics.file_count, 3);
+        assert_eq!(summary.input_tokens, 400);
+        assert_eq!(summary.output_tokens, 200);
+        assert_eq!(summary.statistics.token_reduction_percent, 50.0);
+
+        let slowest: Vec<_> = summary.slowest(2).iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(slowest, ["b.json", "c.json"]);
+        let worst: Vec<_> = summary
+            .worst_compressing(1)
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(worst, ["b.json"]);
+
+        assert!(!summary.check.is_success());
+        let text = summary.to_text();
+        assert!(text.contains("Tokens:     400 -> 200 (50.0% fewer)"));
+        assert!(text.contains("✗ Token reduction: 50.0% (target: 90.0%)"));
+        assert!(summary.savings.is_none());
+        assert!(!text.contains("Savings thresholds"));
+    }
+
+    #[test]
+    fn test_savings_thresholds_per_file_and_run() {
+        let thresholds = SavingsThresholds {
+            min_reduction: Some(60.0),
+            max_output_tokens: Some(95),
+        };
+        let good = file("a.json", 200, 60, 1.0);
+        let poor = file("b.json", 100, 90, 1.0);
+        assert!(good.check_savings(&thresholds).is_success());
+        assert_eq!(
+            poor.check_savings(&thresholds).failed,
+            ["Token reduction: 10.0% (minimum: 60.0%)"]
+        );
+
+        let mut run = RunSummary::start();
+        run.record(good, 1024);
+        run.record(poor, 1024);
+        let summary = run.finish(&PerformanceTargets::default(), &thresholds);
+
+        // 300 -> 150 tokens; the output token limit is not applied to the total
+        let savings = summary.savings.as_ref().unwrap();
+        assert_eq!(savings.failed, ["Token reduction: 50.0% (minimum: 60.0%)"]);
+        assert!(savings.passed.is_empty());
+        assert!(summary
+            .to_text()
+            .contains("Savings thresholds: not met\n  ✗ Token reduction: 50.0%"));
+    }
+}
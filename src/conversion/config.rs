@@ -125,6 +125,8 @@ pub struct ConversionConfig {
     pub include_schema: bool,
     /// Maximum nesting depth
     pub max_depth: Option<usize>,
+    /// Build a source map linking output lines to input JSON Pointers
+    pub source_map: bool,
 }
 
 impl Default for ConversionConfig {
@@ -141,6 +143,7 @@ impl Default for ConversionConfig {
             validate_output: true,
             include_schema: true,
             max_depth: Some(1000), // Reasonable limit to prevent stack overflow
+            source_map: false,
         }
     }
 }
@@ -183,6 +186,54 @@ impl ConversionConfig {
         }
     }
 
+    /// Create configuration that spends as few LLM tokens as possible
+    pub fn llm_min() -> Self {
+        Self {
+            indent_size: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Create configuration for output people review by eye
+    pub fn readable() -> Self {
+        Self {
+            indent_size: 4,
+            ..Default::default()
+        }
+    }
+
+    /// Settings as `toonconv.toml` keys and values
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        let delimiter = match self.delimiter {
+            DelimiterType::Comma => "comma",
+            DelimiterType::Tab => "tab",
+            DelimiterType::Pipe => "pipe",
+        };
+        let quote_strings = match self.quote_strings {
+            QuoteStrategy::Smart => "smart",
+            QuoteStrategy::Always => "always",
+            QuoteStrategy::Never => "never",
+        };
+        vec![
+            ("indent", self.indent_size.to_string()),
+            ("delimiter", delimiter.to_string()),
+            ("quote-strings", quote_strings.to_string()),
+            ("plain", (!self.pretty).to_string()),
+            (
+                "memory-limit",
+                format!("{}MB", self.memory_limit / (1024 * 1024)),
+            ),
+            ("timeout", self.timeout.as_secs().to_string()),
+            ("simd", self.enable_simd.to_string()),
+            ("validate-output", self.validate_output.to_string()),
+            (
+                "max-depth",
+                self.max_depth
+                    .map_or_else(|| "none".to_string(), |depth| depth.to_string()),
+            ),
+        ]
+    }
+
     /// Set indentation size
     pub fn with_indent_size(mut self, size: u8) -> Result<Self, String> {
         if size > 8 {
@@ -246,6 +297,12 @@ impl ConversionConfig {
         self
     }
 
+    /// Enable/disable source map generation
+    pub fn with_source_map(mut self, enabled: bool) -> Self {
+        self.source_map = enabled;
+        self
+    }
+
     /// Validate configuration consistency
     pub fn validate(&self) -> Result<(), String> {
         // Check indent size bounds
@@ -263,6 +320,11 @@ impl ConversionConfig {
             return Err("Timeout must be greater than 0".to_string());
         }
 
+        // Source maps are built from line-based output
+        if self.source_map && !self.pretty {
+            return Err("Source maps require pretty output".to_string());
+        }
+
         // Check max depth bounds
         if let Some(depth) = self.max_depth {
             if depth == 0 {
@@ -312,19 +374,76 @@ pub enum PerformanceProfile {
     Memory,
     /// Balanced approach
     Balanced,
+    /// Fewest tokens for LLM prompts
+    LlmMin,
+    /// Easiest to read for human review
+    Readable,
     /// Custom configuration
     Custom(ConversionConfig),
 }
 
 impl PerformanceProfile {
+    /// Named presets, in the order they are listed to users
+    pub fn presets() -> [PerformanceProfile; 5] {
+        [
+            PerformanceProfile::Speed,
+            PerformanceProfile::Memory,
+            PerformanceProfile::Balanced,
+            PerformanceProfile::LlmMin,
+            PerformanceProfile::Readable,
+        ]
+    }
+
     pub fn to_config(&self) -> ConversionConfig {
         match self {
             PerformanceProfile::Speed => ConversionConfig::large_files(),
             PerformanceProfile::Memory => ConversionConfig::small_files(),
             PerformanceProfile::Balanced => ConversionConfig::default(),
+            PerformanceProfile::LlmMin => ConversionConfig::llm_min(),
+            PerformanceProfile::Readable => ConversionConfig::readable(),
             PerformanceProfile::Custom(config) => config.clone(),
         }
     }
+
+    /// Name used by `--profile`
+    pub fn name(&self) -> &'static str {
+        match self {
+            PerformanceProfile::Speed => "speed",
+            PerformanceProfile::Memory => "memory",
+            PerformanceProfile::Balanced => "balanced",
+            PerformanceProfile::LlmMin => "llm-min",
+            PerformanceProfile::Readable => "readable",
+            PerformanceProfile::Custom(_) => "custom",
+        }
+    }
+
+    /// One-line summary of what the profile is for
+    pub fn description(&self) -> &'static str {
+        match self {
+            PerformanceProfile::Speed => "Large inputs: SIMD parsing, no output validation",
+            PerformanceProfile::Memory => "Small inputs with a tight memory limit and timeout",
+            PerformanceProfile::Balanced => "Default settings",
+            PerformanceProfile::LlmMin => "Fewest tokens: one-space indent",
+            PerformanceProfile::Readable => "Human review: four-space indent",
+            PerformanceProfile::Custom(_) => "Custom settings",
+        }
+    }
+}
+
+impl std::str::FromStr for PerformanceProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::presets()
+            .into_iter()
+            .find(|profile| profile.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid profile '{}'. Use 'speed', 'memory', 'balanced', 'llm-min', or 'readable'",
+                    s
+                )
+            })
+    }
 }
 
 #[cfg(test)]
@@ -382,4 +501,23 @@ mod tests {
         assert!(memory.to_config().memory_limit < 100 * 1024 * 1024);
         assert!(balanced.to_config().validate_output);
     }
+
+    #[test]
+    fn test_profile_names_round_trip() {
+        for profile in PerformanceProfile::presets() {
+            let parsed = PerformanceProfile::from_str(profile.name()).unwrap();
+            assert_eq!(parsed.name(), profile.name());
+            assert!(profile.to_config().validate().is_ok());
+        }
+        assert!(PerformanceProfile::from_str("fastest").is_err());
+
+        let llm_min = PerformanceProfile::LlmMin.to_config();
+        assert_eq!(llm_min.indent_size, 1);
+        assert!(llm_min.settings().contains(&("indent", "1".to_string())));
+        // The encoder writes no `#` markers, so profiles must not claim to set them
+        assert!(llm_min
+            .settings()
+            .iter()
+            .all(|(key, _)| *key != "length-marker"));
+    }
 }
@@ -3,11 +3,14 @@
 use crate::conversion::config::ConversionConfig;
 use crate::conversion::limits;
 use crate::conversion::ConversionResult;
+use crate::document::cst::ToonCst;
+use crate::document::source_map::SourceMap;
 use crate::error::{ConversionError, ConversionErrorKind};
 use crate::formatter::format_to_toon;
 use crate::parser::validation::validate_json_structure;
 use crate::parser::JsonSource;
 use crate::validation::{CircularRefDetector, ToonValidator};
+use serde::Serialize;
 use serde_json::Value;
 use std::time::Instant;
 
@@ -16,12 +19,25 @@ use std::time::Instant;
 pub struct ToonData {
     pub content: String,
     pub metadata: ConversionMetadata,
+    /// Lines and cells of `content` mapped to input JSON Pointers, when
+    /// requested with [`ConversionConfig::source_map`]
+    pub source_map: Option<SourceMap>,
 }
 
 impl ToonData {
     /// Create a new TOON data result
     pub fn new(content: String, metadata: ConversionMetadata) -> Self {
-        Self { content, metadata }
+        Self {
+            content,
+            metadata,
+            source_map: None,
+        }
+    }
+
+    /// Attach a source map
+    pub fn with_source_map(mut self, source_map: SourceMap) -> Self {
+        self.source_map = Some(source_map);
+        self
     }
 
     /// Get the formatted TOON output
@@ -41,7 +57,7 @@ impl ToonData {
 }
 
 /// Metadata about the conversion process
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ConversionMetadata {
     pub input_size: u64,
     pub output_size: u64,
@@ -52,14 +68,14 @@ pub struct ConversionMetadata {
 }
 
 /// Schema information for arrays
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SchemaInfo {
     pub array_count: usize,
     pub uniform_arrays: Vec<ArraySchema>,
 }
 
 /// Schema for a single array
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ArraySchema {
     pub element_count: usize,
     pub field_count: Option<usize>,
@@ -116,7 +132,14 @@ impl ConversionEngine {
             schema_info: self.extract_schema_info(json_data),
         };
 
-        Ok(ToonData::new(toon_content, metadata))
+        let toon_data = if self.config.source_map {
+            let source_map = self.build_source_map(json_data, &toon_content)?;
+            ToonData::new(toon_content, metadata).with_source_map(source_map)
+        } else {
+            ToonData::new(toon_content, metadata)
+        };
+
+        Ok(toon_data)
     }
 
     /// Convert JSON from a source to TOON
@@ -180,30 +203,63 @@ impl ConversionEngine {
         Ok(())
     }
 
+    /// Map the lines of TOON output back to input JSON Pointers
+    ///
+    /// The map is read from the output itself, so every pointer is checked
+    /// against the input to make sure the output nests the way the input does.
+    fn build_source_map(
+        &self,
+        json_data: &Value,
+        toon_content: &str,
+    ) -> ConversionResult<SourceMap> {
+        let failed = |message: String| {
+            ConversionError::conversion(ConversionErrorKind::ConversionFailed {
+                message: format!("Cannot build a source map for the output: {}", message),
+            })
+        };
+        let source_map = ToonCst::parse_with(toon_content, self.config.delimiter)
+            .map_err(|e| failed(e.to_string()))?
+            .source_map();
+
+        for mapping in &source_map.lines {
+            let pointers = std::iter::once(&mapping.pointer)
+                .chain(mapping.cells.iter().map(|cell| &cell.pointer));
+            for pointer in pointers {
+                if json_data.pointer(pointer).is_none() {
+                    return Err(failed(format!(
+                        "line {} does not correspond to an input value",
+                        mapping.line
+                    )));
+                }
+            }
+        }
+        Ok(source_map)
+    }
+
     /// Convert JSON value to TOON string
     fn convert_to_toon(&self, json_data: &Value) -> ConversionResult<String> {
         // Use the TOON formatter
         format_to_toon(json_data, &self.config)
     }
 
-    /// Estimate input size in bytes
+    /// Input size in bytes, measured as compact JSON without buffering it
     fn estimate_input_size(&self, json_data: &Value) -> u64 {
-        // This is a rough estimate - in a real implementation,
-        // you'd want to track the actual size from the source
-        match json_data {
-            Value::String(s) => s.len() as u64,
-            Value::Number(n) => n.to_string().len() as u64,
-            Value::Bool(_) => 4,
-            Value::Null => 4,
-            Value::Array(a) => {
-                // Estimate based on array elements
-                (a.len() * 10) as u64 // Rough estimate
+        struct ByteCounter(u64);
+
+        impl std::io::Write for ByteCounter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0 += buf.len() as u64;
+                Ok(buf.len())
             }
-            Value::Object(o) => {
-                // Estimate based on object properties
-                (o.len() * 20) as u64 // Rough estimate
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
             }
         }
+
+        let mut counter = ByteCounter(0);
+        serde_json::to_writer(&mut counter, json_data).expect("JSON values serialize");
+        counter.0
     }
 
     /// Calculate token reduction percentage
@@ -212,15 +268,15 @@ impl ConversionEngine {
             return 0.0;
         }
 
-        let reduction = ((input_size as f32 - output_size as f32) / input_size as f32) * 100.0;
-        reduction.max(0.0) // Don't show negative reduction
+        // Negative when the TOON is larger than the input
+        ((input_size as f32 - output_size as f32) / input_size as f32) * 100.0
     }
 
     /// Estimate peak memory usage
     fn estimate_memory_usage(&self) -> usize {
         // Rough estimate - in a real implementation, track actual usage
         // This should be much more sophisticated
-        self.config.memory_limit / 4 // Assume peak usage is 25% of limit
+        self.config.memory_limit / 4 / 1024 // Assume peak usage is 25% of limit, in KB
     }
 
     /// Extract schema information from JSON
@@ -398,6 +454,26 @@ mod tests {
         assert!(result.metadata.output_size > 0);
     }
 
+    #[test]
+    fn test_source_map_is_optional() {
+        let json = serde_json::json!({
+            "users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]
+        });
+
+        let plain = ConversionEngine::new(ConversionConfig::default())
+            .convert(&json)
+            .unwrap();
+        assert!(plain.source_map.is_none());
+
+        let mapped = ConversionEngine::new(ConversionConfig::default().with_source_map(true))
+            .convert(&json)
+            .unwrap();
+        let source_map = mapped.source_map.unwrap();
+        assert_eq!(source_map.pointer_at(1, None), Some("/users"));
+        assert_eq!(source_map.pointer_at(3, Some(5)), Some("/users/1/name"));
+        assert_eq!(source_map.line_of("/users/0/id"), Some(2));
+    }
+
     #[test]
     fn test_string_conversion() {
         let config = ConversionConfig::default();
@@ -426,7 +502,12 @@ mod tests {
         let result = engine.convert(&json).unwrap();
 
         // TOON should typically be smaller for structured data
-        assert!(result.metadata.token_reduction >= 0.0);
+        let metadata = &result.metadata;
+        assert_eq!(
+            metadata.input_size,
+            serde_json::to_string(&json).unwrap().len() as u64
+        );
+        assert!(metadata.token_reduction > 0.0);
         // processing_time_ms is u64, always >= 0
     }
 
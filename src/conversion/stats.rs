@@ -10,7 +10,7 @@ pub struct ConversionStatistics {
     pub input_size_bytes: u64,
     /// Output TOON size in bytes
     pub output_size_bytes: u64,
-    /// Token reduction percentage
+    /// Token reduction percentage, negative when the output is larger
     pub token_reduction_percent: f32,
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
@@ -74,7 +74,7 @@ impl ConversionStatistics {
         Self {
             input_size_bytes: input_size,
             output_size_bytes: output_size,
-            token_reduction_percent: token_reduction_percent.max(0.0),
+            token_reduction_percent,
             processing_time_ms,
             memory_peak_bytes: memory_peak,
             file_count: 1,
@@ -101,8 +101,7 @@ impl ConversionStatistics {
                 * 100.0
         } else {
             0.0
-        }
-        .max(0.0);
+        };
 
         self.avg_time_per_operation_ms = if self.operation_count > 0 {
             self.processing_time_ms as f32 / self.operation_count as f32
@@ -123,8 +122,8 @@ impl ConversionStatistics {
     pub fn efficiency_score(&self) -> f32 {
         let mut score = 0.0;
 
-        // Token reduction contributes up to 40 points
-        score += (self.token_reduction_percent / 100.0).min(0.4) * 100.0;
+        // Token reduction contributes up to 40 points, none when the output grew
+        score += (self.token_reduction_percent / 100.0).clamp(0.0, 0.4) * 100.0;
 
         // Speed contributes up to 30 points (faster = better)
         if self.avg_time_per_operation_ms > 0.0 {
@@ -182,6 +181,17 @@ impl ConversionStatistics {
             ));
         }
 
+        // Check throughput
+        let throughput_mbps = self.throughput_bytes_per_sec / (1024.0 * 1024.0);
+        if throughput_mbps >= targets.min_throughput_mbps {
+            passed.push(format!("Throughput: {:.1}MB/s", throughput_mbps));
+        } else {
+            failed.push(format!(
+                "Throughput: {:.1}MB/s (target: {:.1}MB/s)",
+                throughput_mbps, targets.min_throughput_mbps
+            ));
+        }
+
         PerformanceCheck { passed, failed }
     }
 
@@ -208,7 +218,7 @@ impl ConversionStatistics {
 }
 
 /// Performance targets for comparison
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PerformanceTargets {
     pub max_avg_time_ms: f32,
     pub max_memory_bytes: usize,
@@ -228,7 +238,7 @@ impl Default for PerformanceTargets {
 }
 
 /// Result of performance target checking
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PerformanceCheck {
     pub passed: Vec<String>,
     pub failed: Vec<String>,
@@ -252,6 +262,53 @@ impl PerformanceCheck {
     }
 }
 
+/// Token savings that a conversion must reach, unlike [`PerformanceTargets`]
+/// which are only reported
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavingsThresholds {
+    /// Minimum token reduction in percent
+    pub min_reduction: Option<f32>,
+    /// Maximum number of output tokens
+    pub max_output_tokens: Option<usize>,
+}
+
+impl SavingsThresholds {
+    /// Check if any threshold is set
+    pub fn is_enabled(&self) -> bool {
+        self.min_reduction.is_some() || self.max_output_tokens.is_some()
+    }
+
+    /// Check a token reduction and output size against the thresholds
+    pub fn check(&self, token_reduction: f32, output_tokens: usize) -> PerformanceCheck {
+        let mut passed = vec![];
+        let mut failed = vec![];
+
+        if let Some(min_reduction) = self.min_reduction {
+            if token_reduction >= min_reduction {
+                passed.push(format!("Token reduction: {:.1}%", token_reduction));
+            } else {
+                failed.push(format!(
+                    "Token reduction: {:.1}% (minimum: {:.1}%)",
+                    token_reduction, min_reduction
+                ));
+            }
+        }
+
+        if let Some(max_output_tokens) = self.max_output_tokens {
+            if output_tokens <= max_output_tokens {
+                passed.push(format!("Output tokens: {}", output_tokens));
+            } else {
+                failed.push(format!(
+                    "Output tokens: {} (maximum: {})",
+                    output_tokens, max_output_tokens
+                ));
+            }
+        }
+
+        PerformanceCheck { passed, failed }
+    }
+}
+
 /// Performance tracker for conversion operations
 pub struct PerformanceTracker {
     start_time: Instant,
@@ -289,6 +346,26 @@ impl PerformanceTracker {
         self.stats
     }
 
+    /// Add the statistics of one operation in a multi-file run
+    pub fn record(&mut self, operation: &ConversionStatistics) {
+        self.stats.combine(operation);
+    }
+
+    /// Complete a run of recorded operations. Processing time and throughput
+    /// cover the whole run since [`PerformanceTracker::start`], including
+    /// reading and writing files; the average per operation is kept.
+    pub fn finish_run(mut self) -> ConversionStatistics {
+        let elapsed = self.start_time.elapsed();
+        self.stats.processing_time_ms = elapsed.as_millis() as u64;
+        self.stats.throughput_bytes_per_sec = if elapsed.as_secs_f64() > 0.0 {
+            (self.stats.input_size_bytes as f64 / elapsed.as_secs_f64()) as f32
+        } else {
+            0.0
+        };
+        self.stats.collected_at = chrono::Utc::now();
+        self.stats
+    }
+
     /// Get current memory usage (simplified - in real implementation would be more sophisticated)
     fn get_current_memory() -> usize {
         // This is a placeholder - in a real implementation,
@@ -429,6 +506,13 @@ mod tests {
         assert_eq!(stats1.file_count, 2);
         assert_eq!(stats1.operation_count, 2);
         assert_eq!(stats1.processing_time_ms, 300);
+
+        // Growth is reported as a negative reduction, as for a single file
+        let grown = ConversionStatistics::for_conversion(100, 125, Duration::ZERO, 0);
+        assert_eq!(grown.token_reduction_percent, -25.0);
+        stats1.combine(&grown);
+        assert_eq!(stats1.output_size_bytes, 1925);
+        assert!(stats1.token_reduction_percent > 0.0);
     }
 
     #[test]
@@ -439,6 +523,9 @@ mod tests {
         let score = stats.efficiency_score();
         assert!(score > 0.0);
         assert!(score <= 100.0);
+
+        let grown = ConversionStatistics::for_conversion(100, 200, Duration::ZERO, 0);
+        assert_eq!(grown.efficiency_score(), 0.0);
     }
 
     #[test]
@@ -455,6 +542,27 @@ mod tests {
         assert!(!check.passed.is_empty());
     }
 
+    #[test]
+    fn test_savings_thresholds() {
+        assert!(!SavingsThresholds::default().is_enabled());
+        assert!(SavingsThresholds::default().check(-50.0, 1000).is_success());
+
+        let thresholds = SavingsThresholds {
+            min_reduction: Some(20.0),
+            max_output_tokens: Some(100),
+        };
+        assert!(thresholds.check(25.0, 100).is_success());
+
+        let check = thresholds.check(10.0, 150);
+        assert_eq!(
+            check.failed,
+            [
+                "Token reduction: 10.0% (minimum: 20.0%)",
+                "Output tokens: 150 (maximum: 100)"
+            ]
+        );
+    }
+
     #[test]
     fn test_benchmark_results() {
         let times = vec![100, 110, 95, 105, 120];
@@ -485,4 +593,31 @@ mod tests {
         assert_eq!(stats.input_size_bytes, 1000);
         assert_eq!(stats.output_size_bytes, 600);
     }
+
+    #[test]
+    fn test_performance_tracker_run() {
+        let mut tracker = PerformanceTracker::start();
+        for _ in 0..3 {
+            tracker.record(&ConversionStatistics::for_conversion(
+                1000,
+                400,
+                Duration::from_millis(2),
+                1024,
+            ));
+        }
+        thread::sleep(Duration::from_millis(10));
+
+        let stats = tracker.finish_run();
+        assert_eq!(stats.file_count, 3);
+        assert_eq!(stats.input_size_bytes, 3000);
+        assert_eq!(stats.avg_time_per_operation_ms, 2.0);
+        assert!(stats.processing_time_ms >= 10);
+        assert!(stats.throughput_bytes_per_sec < 300_000.0);
+
+        let check = stats.meets_targets(&PerformanceTargets {
+            min_throughput_mbps: 1000.0,
+            ..Default::default()
+        });
+        assert!(check.failed.iter().any(|f| f.starts_with("Throughput")));
+    }
 }
@@ -0,0 +1,1092 @@
+//! TOON decoding module
+//!
+//! Parses TOON text back into JSON values. Strict mode rejects any deviation
+//! from the declared structure. Lenient mode is meant for model-generated
+//! TOON and recovers from the usual mistakes, reporting each one as a
+//! [`DecodeWarning`]:
+//!
+//! - `[N]` headers that disagree with the actual number of rows, items or
+//!   values (the actual count wins)
+//! - Indentation that is not the configured width, mixes widths or uses tabs
+//!   (indentation is re-inferred from the lines themselves)
+//! - Tabular rows with too few cells (padded with `null`) or too many
+//!   (trimmed to the header's fields)
+//!
+//! Decoding builds a [`ToonDocument`] first, so the layout of the input can be
+//! kept for re-serialization; [`DecodedToon::value`] is derived from it.
+
+pub mod reader;
+
+use crate::conversion::DelimiterType;
+use crate::document::{ArrayLayout, ToonArray, ToonDocument, ToonField, ToonNode, ToonObject};
+use crate::error::{ParseError, ParseResult};
+use serde::Serialize;
+use serde_json::{Number, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Options controlling TOON decoding
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    /// Recover from deviations instead of failing
+    pub lenient: bool,
+    /// Spaces per indentation level
+    pub indent_size: usize,
+    /// Delimiter for arrays whose header does not declare one
+    pub delimiter: DelimiterType,
+    /// Read unquoted dotted keys (`a.b: 1`) as folded paths of nested objects
+    pub expand_paths: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            lenient: false,
+            indent_size: 2,
+            delimiter: DelimiterType::Comma,
+            expand_paths: false,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Strict decoding options
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Lenient decoding options for model-generated TOON
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::default()
+        }
+    }
+
+    /// Set spaces per indentation level
+    pub fn with_indent_size(mut self, size: usize) -> Self {
+        self.indent_size = size.max(1);
+        self
+    }
+
+    /// Set the default array delimiter
+    pub fn with_delimiter(mut self, delimiter: DelimiterType) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Expand folded dotted keys into nested objects
+    pub fn with_expand_paths(mut self, enabled: bool) -> Self {
+        self.expand_paths = enabled;
+        self
+    }
+}
+
+/// Kind of deviation recovered from in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DecodeWarningKind {
+    /// An `[N]` header disagrees with the number of rows, items or values found
+    LengthMismatch { declared: usize, actual: usize },
+    /// A line is not indented where its block expects it
+    Indentation { expected: usize, found: usize },
+    /// A tab was used for indentation
+    TabIndentation,
+    /// A tabular row had fewer cells than fields; missing cells became `null`
+    MissingCells { expected: usize, found: usize },
+    /// A tabular row had more cells than fields; extra cells were dropped
+    ExtraCells { expected: usize, found: usize },
+}
+
+impl fmt::Display for DecodeWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeWarningKind::LengthMismatch { declared, actual } => write!(
+                f,
+                "Array header declares {} entries but {} were found",
+                declared, actual
+            ),
+            DecodeWarningKind::Indentation { expected, found } => write!(
+                f,
+                "Expected indentation of {} spaces but found {}",
+                expected, found
+            ),
+            DecodeWarningKind::TabIndentation => write!(f, "Tab used for indentation"),
+            DecodeWarningKind::MissingCells { expected, found } => write!(
+                f,
+                "Row has {} cells but the header declares {} fields",
+                found, expected
+            ),
+            DecodeWarningKind::ExtraCells { expected, found } => write!(
+                f,
+                "Row has {} cells but the header declares {} fields",
+                found, expected
+            ),
+        }
+    }
+}
+
+/// A deviation recovered from while decoding leniently
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodeWarning {
+    /// Line the deviation was found on (1-based)
+    pub line: usize,
+    /// What was wrong and how it was recovered
+    #[serde(flatten)]
+    pub kind: DecodeWarningKind,
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}", self.kind, self.line)
+    }
+}
+
+/// Result of decoding a TOON document
+#[derive(Debug, Clone)]
+pub struct DecodedToon {
+    /// Decoded value
+    pub value: Value,
+    /// Decoded document with the layout of the input
+    pub document: ToonDocument,
+    /// Deviations recovered from (always empty in strict mode)
+    pub warnings: Vec<DecodeWarning>,
+    /// Line each decoded value starts on, keyed by JSON Pointer
+    pub locations: BTreeMap<String, usize>,
+}
+
+impl DecodedToon {
+    /// Line the value at a JSON Pointer was decoded from, falling back to the
+    /// nearest enclosing value
+    pub fn line_of(&self, pointer: &str) -> Option<usize> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(line) = self.locations.get(pointer) {
+                return Some(*line);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+}
+
+/// One non-blank input line
+#[derive(Debug, Clone)]
+struct Line {
+    /// 1-based line number
+    number: usize,
+    /// Indentation width in spaces
+    indent: usize,
+    /// Content after the indentation, without trailing whitespace
+    content: String,
+}
+
+impl Line {
+    /// Measure the indentation of a non-blank line, counting a tab as
+    /// `tab_width` spaces; also reports whether a tab was seen
+    fn measure(number: usize, raw: &str, tab_width: usize) -> (Self, bool) {
+        let mut indent = 0;
+        let mut tabs = false;
+        let mut content_start = raw.len();
+        for (offset, ch) in raw.char_indices() {
+            match ch {
+                ' ' => indent += 1,
+                '\t' => {
+                    tabs = true;
+                    indent += tab_width;
+                }
+                _ => {
+                    content_start = offset;
+                    break;
+                }
+            }
+        }
+
+        let line = Line {
+            number,
+            indent,
+            content: raw[content_start..].to_string(),
+        };
+        (line, tabs)
+    }
+}
+
+/// Parsed `[N]{fields}` array header
+#[derive(Debug)]
+pub(crate) struct ArrayHeader {
+    pub(crate) length: usize,
+    pub(crate) delimiter: char,
+    /// The delimiter was declared in the header rather than defaulted
+    pub(crate) explicit_delimiter: bool,
+    /// The header used the `#` length marker
+    pub(crate) length_marker: bool,
+    pub(crate) fields: Option<Vec<String>>,
+}
+
+/// TOON decoder
+pub struct ToonDecoder {
+    options: DecodeOptions,
+    lines: Vec<Line>,
+    pos: usize,
+    unit: usize,
+    warnings: Vec<DecodeWarning>,
+    path: Vec<String>,
+    locations: BTreeMap<String, usize>,
+}
+
+impl ToonDecoder {
+    /// Create a new decoder with options
+    pub fn new(options: DecodeOptions) -> Self {
+        let unit = options.indent_size;
+        Self {
+            options,
+            lines: Vec::new(),
+            pos: 0,
+            unit,
+            warnings: Vec::new(),
+            path: Vec::new(),
+            locations: BTreeMap::new(),
+        }
+    }
+
+    /// Decode a TOON document
+    pub fn decode(&mut self, input: &str) -> ParseResult<DecodedToon> {
+        self.pos = 0;
+        self.warnings.clear();
+        self.path.clear();
+        self.locations.clear();
+        self.unit = self.options.indent_size;
+        self.lines = self.split_lines(input)?;
+
+        if self.options.lenient {
+            self.infer_indent_unit();
+        }
+
+        let root = self.parse_root()?;
+
+        if let Some(line) = self.lines.get(self.pos) {
+            return Err(ParseError::new(
+                "Unexpected content after the end of the document".to_string(),
+                Some((line.number, line.indent + 1)),
+            ));
+        }
+
+        let document = ToonDocument {
+            root,
+            indent_size: self.unit,
+            delimiter: self.options.delimiter,
+        };
+        Ok(DecodedToon {
+            value: document.to_value(),
+            document,
+            warnings: std::mem::take(&mut self.warnings),
+            locations: std::mem::take(&mut self.locations),
+        })
+    }
+
+    /// Decode a TOON document keeping its layout
+    pub fn decode_document(&mut self, input: &str) -> ParseResult<ToonDocument> {
+        self.decode(input).map(|decoded| decoded.document)
+    }
+
+    /// Split input into non-blank lines with measured indentation
+    fn split_lines(&mut self, input: &str) -> ParseResult<Vec<Line>> {
+        let mut lines = Vec::new();
+
+        for (index, raw) in input.lines().enumerate() {
+            let number = index + 1;
+            let raw = raw.trim_end();
+            if raw.is_empty() {
+                continue;
+            }
+
+            let (line, tabs) = Line::measure(number, raw, self.options.indent_size);
+            if tabs {
+                self.deviation(number, 1, DecodeWarningKind::TabIndentation)?;
+            }
+            lines.push(line);
+        }
+
+        Ok(lines)
+    }
+
+    /// Use the smallest indentation found as the indentation unit
+    fn infer_indent_unit(&mut self) {
+        let smallest = self
+            .lines
+            .iter()
+            .filter(|line| line.indent > 0)
+            .min_by_key(|line| line.indent)
+            .map(|line| (line.number, line.indent));
+
+        if let Some((number, indent)) = smallest {
+            if indent != self.options.indent_size {
+                self.warnings.push(DecodeWarning {
+                    line: number,
+                    kind: DecodeWarningKind::Indentation {
+                        expected: self.options.indent_size,
+                        found: indent,
+                    },
+                });
+                self.unit = indent;
+            }
+        }
+    }
+
+    /// Record a deviation in lenient mode, or fail in strict mode
+    fn deviation(
+        &mut self,
+        line: usize,
+        column: usize,
+        kind: DecodeWarningKind,
+    ) -> ParseResult<()> {
+        if self.options.lenient {
+            self.warnings.push(DecodeWarning { line, kind });
+            Ok(())
+        } else {
+            Err(ParseError::new(kind.to_string(), Some((line, column))))
+        }
+    }
+
+    fn error(line: &Line, message: &str) -> ParseError {
+        ParseError::new(message.to_string(), Some((line.number, line.indent + 1)))
+            .with_preview(line.content.clone())
+    }
+
+    /// Descend into a key or index, recording the line its value starts on
+    fn enter(&mut self, segment: String, line: usize) {
+        self.path
+            .push(segment.replace('~', "~0").replace('/', "~1"));
+        let pointer: String = self
+            .path
+            .iter()
+            .map(|segment| format!("/{}", segment))
+            .collect();
+        self.locations.entry(pointer).or_insert(line);
+    }
+
+    fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    /// Check if the next line belongs to the block under `parent` indentation
+    fn next_child(&self, parent: Option<usize>) -> Option<&Line> {
+        self.lines
+            .get(self.pos)
+            .filter(|line| !matches!(parent, Some(p) if line.indent <= p))
+    }
+
+    /// Check a block line's indentation against the block, fixing the block
+    /// indentation from its first line
+    fn check_block_indent(
+        &mut self,
+        block_indent: &mut Option<usize>,
+        expected: usize,
+    ) -> ParseResult<()> {
+        let (number, indent) = {
+            let line = &self.lines[self.pos];
+            (line.number, line.indent)
+        };
+
+        let expected = block_indent.unwrap_or(expected);
+        if indent != expected {
+            self.deviation(
+                number,
+                indent + 1,
+                DecodeWarningKind::Indentation {
+                    expected,
+                    found: indent,
+                },
+            )?;
+        }
+        if block_indent.is_none() {
+            *block_indent = Some(indent);
+        }
+        Ok(())
+    }
+
+    fn parse_root(&mut self) -> ParseResult<ToonNode> {
+        let Some(first) = self.lines.first().cloned() else {
+            // An empty document is an empty object
+            return Ok(ToonNode::Object(ToonObject::default()));
+        };
+        self.locations.insert(String::new(), first.number);
+
+        if first.content.starts_with('[') {
+            if first.indent != 0 {
+                self.deviation(
+                    first.number,
+                    first.indent + 1,
+                    DecodeWarningKind::Indentation {
+                        expected: 0,
+                        found: first.indent,
+                    },
+                )?;
+            }
+            self.pos += 1;
+            let (header, rest) = parse_header(&first.content, self.options.delimiter)
+                .ok_or_else(|| Self::error(&first, "Invalid array header"))?;
+            return self.parse_array_body(&first, header, rest);
+        }
+
+        if self.lines.len() == 1 && split_key(&first.content).is_none() {
+            self.pos += 1;
+            return parse_primitive(&first.content)
+                .map(ToonNode::Primitive)
+                .map_err(|message| Self::error(&first, &message));
+        }
+
+        self.parse_object_block(None, 0)
+    }
+
+    /// Parse the fields of an object made of the lines under `parent`
+    fn parse_object_block(
+        &mut self,
+        parent: Option<usize>,
+        expected: usize,
+    ) -> ParseResult<ToonNode> {
+        let mut object = ToonObject::default();
+        let mut block_indent = None;
+
+        while self.next_child(parent).is_some() {
+            self.check_block_indent(&mut block_indent, expected)?;
+            let line = self.lines[self.pos].clone();
+            self.pos += 1;
+
+            object.fields.push(self.parse_field(&line)?);
+        }
+
+        Ok(ToonNode::Object(object))
+    }
+
+    /// Parse a `key: value`, `key:` or `key[N]...:` line and its nested lines
+    fn parse_field(&mut self, line: &Line) -> ParseResult<ToonField> {
+        let (key, after) =
+            split_key(&line.content).ok_or_else(|| Self::error(line, "Expected 'key: value'"))?;
+        let key = key.map_err(|message| Self::error(line, &message))?;
+        let folded =
+            self.options.expand_paths && !line.content.starts_with('"') && is_folded_path(&key);
+
+        let segments: Vec<String> = if folded {
+            key.split('.').map(str::to_string).collect()
+        } else {
+            vec![key.clone()]
+        };
+        for segment in &segments {
+            self.enter(segment.clone(), line.number);
+        }
+        let value = self.parse_field_value(line, after)?;
+        for _ in &segments {
+            self.leave();
+        }
+
+        Ok(ToonField { key, value, folded })
+    }
+
+    fn parse_field_value(&mut self, line: &Line, after: &str) -> ParseResult<ToonNode> {
+        if after.starts_with('[') {
+            let (header, rest) = parse_header(after, self.options.delimiter)
+                .ok_or_else(|| Self::error(line, "Invalid array header"))?;
+            return self.parse_array_body(line, header, rest);
+        }
+
+        // split_key guarantees the key is followed by '[' or ':'
+        let rest = after[1..].trim_start();
+        if rest.is_empty() {
+            self.parse_object_block(Some(line.indent), line.indent + self.unit)
+        } else {
+            parse_primitive(rest)
+                .map(ToonNode::Primitive)
+                .map_err(|message| Self::error(line, &message))
+        }
+    }
+
+    /// Parse an array given its header line, header and the text after the colon
+    fn parse_array_body(
+        &mut self,
+        line: &Line,
+        header: ArrayHeader,
+        rest: &str,
+    ) -> ParseResult<ToonNode> {
+        let layout;
+        let mut hyphen_fields = false;
+        let items = if let Some(fields) = &header.fields {
+            layout = ArrayLayout::Tabular(fields.clone());
+            if !rest.is_empty() {
+                return Err(Self::error(
+                    line,
+                    "Unexpected content after tabular array header",
+                ));
+            }
+            self.parse_rows(line, fields, header.delimiter)?
+        } else if !rest.is_empty() {
+            layout = ArrayLayout::Inline;
+            let cells = split_cells(rest, header.delimiter)
+                .map_err(|message| Self::error(line, &message))?;
+            for index in 0..cells.len() {
+                self.enter(index.to_string(), line.number);
+                self.leave();
+            }
+            cells
+                .iter()
+                .map(|cell| parse_primitive(cell).map(ToonNode::Primitive))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|message| Self::error(line, &message))?
+        } else {
+            layout = ArrayLayout::List;
+            let (items, hyphen) = self.parse_list_items(line)?;
+            hyphen_fields = hyphen;
+            items
+        };
+
+        if items.len() != header.length {
+            self.deviation(
+                line.number,
+                line.indent + 1,
+                DecodeWarningKind::LengthMismatch {
+                    declared: header.length,
+                    actual: items.len(),
+                },
+            )?;
+        }
+
+        Ok(ToonNode::Array(ToonArray {
+            items,
+            layout,
+            delimiter: header
+                .explicit_delimiter
+                .then(|| delimiter_type(header.delimiter)),
+            length_marker: header.length_marker,
+            hyphen_fields,
+        }))
+    }
+
+    /// Parse the rows of a tabular array
+    fn parse_rows(
+        &mut self,
+        header_line: &Line,
+        fields: &[String],
+        delimiter: char,
+    ) -> ParseResult<Vec<ToonNode>> {
+        let mut rows = Vec::new();
+        let mut block_indent = None;
+
+        while self.next_child(Some(header_line.indent)).is_some() {
+            self.check_block_indent(&mut block_indent, header_line.indent + self.unit)?;
+            let line = self.lines[self.pos].clone();
+            self.pos += 1;
+
+            let mut cells = split_cells(&line.content, delimiter)
+                .map_err(|message| Self::error(&line, &message))?;
+
+            if cells.len() < fields.len() {
+                self.deviation(
+                    line.number,
+                    line.indent + 1,
+                    DecodeWarningKind::MissingCells {
+                        expected: fields.len(),
+                        found: cells.len(),
+                    },
+                )?;
+            } else if cells.len() > fields.len() {
+                self.deviation(
+                    line.number,
+                    line.indent + 1,
+                    DecodeWarningKind::ExtraCells {
+                        expected: fields.len(),
+                        found: cells.len(),
+                    },
+                )?;
+                cells.truncate(fields.len());
+            }
+
+            self.enter(rows.len().to_string(), line.number);
+            let mut row = ToonObject::default();
+            for (index, field) in fields.iter().enumerate() {
+                self.enter(field.clone(), line.number);
+                self.leave();
+                let value = match cells.get(index) {
+                    Some(cell) => {
+                        parse_primitive(cell).map_err(|message| Self::error(&line, &message))?
+                    }
+                    None => Value::Null,
+                };
+                row.fields.push(ToonField {
+                    key: field.clone(),
+                    value: ToonNode::Primitive(value),
+                    folded: false,
+                });
+            }
+            self.leave();
+            rows.push(ToonNode::Object(row));
+        }
+
+        Ok(rows)
+    }
+
+    /// Parse the `- item` lines of a list array, and whether an object item
+    /// put its first field on the hyphen line
+    fn parse_list_items(&mut self, header_line: &Line) -> ParseResult<(Vec<ToonNode>, bool)> {
+        let mut items = Vec::new();
+        let mut hyphen_fields = false;
+        let mut block_indent = None;
+
+        while self.next_child(Some(header_line.indent)).is_some() {
+            self.check_block_indent(&mut block_indent, header_line.indent + self.unit)?;
+            let line = self.lines[self.pos].clone();
+            self.pos += 1;
+
+            self.enter(items.len().to_string(), line.number);
+            let item = if line.content == "-" {
+                // Object whose fields all follow on deeper lines
+                self.parse_object_block(Some(line.indent), line.indent + self.unit)?
+            } else if let Some(content) = line.content.strip_prefix("- ") {
+                let content = content.trim_start();
+                let (item, hyphen) = self.parse_list_item(&line, content)?;
+                hyphen_fields |= hyphen;
+                item
+            } else {
+                return Err(Self::error(
+                    &line,
+                    "Expected a list item starting with '- '",
+                ));
+            };
+            self.leave();
+            items.push(item);
+        }
+
+        Ok((items, hyphen_fields))
+    }
+
+    /// Parse the content after `- `, and whether it is an object whose first
+    /// field sits on the hyphen line
+    fn parse_list_item(&mut self, line: &Line, content: &str) -> ParseResult<(ToonNode, bool)> {
+        if content.starts_with('[') {
+            if let Some((header, rest)) = parse_header(content, self.options.delimiter) {
+                return Ok((self.parse_array_body(line, header, rest)?, false));
+            }
+        }
+
+        if split_key(content).is_some() {
+            // Object whose first field sits on the hyphen line. Re-queue that
+            // field as a line of its own, aligned with the fields that follow.
+            let field_indent = line.indent + self.unit;
+            self.pos -= 1;
+            self.lines[self.pos] = Line {
+                number: line.number,
+                indent: field_indent,
+                content: content.to_string(),
+            };
+            return Ok((
+                self.parse_object_block(Some(line.indent), field_indent)?,
+                true,
+            ));
+        }
+
+        parse_primitive(content)
+            .map(|value| (ToonNode::Primitive(value), false))
+            .map_err(|message| Self::error(line, &message))
+    }
+}
+
+/// Split a line into its key and the text starting at the following `[` or `:`
+///
+/// Returns `None` when the line is not a field (a bare value). The key itself
+/// is an error when it is a malformed quoted key.
+pub(crate) fn split_key(content: &str) -> Option<(Result<String, String>, &str)> {
+    if content.starts_with('"') {
+        let end = closing_quote(content)?;
+        let after = &content[end + 1..];
+        if after.starts_with(':') || after.starts_with('[') {
+            return Some((unescape(&content[1..end]), after));
+        }
+        return None;
+    }
+
+    let end = content.find([':', '['])?;
+    let key = content[..end].trim_end();
+    if key.is_empty() {
+        return None;
+    }
+    let after = &content[end..];
+    if after.starts_with('[') && parse_header_with(after, ',').is_none() {
+        return None;
+    }
+    Some((Ok(key.to_string()), after))
+}
+
+/// Parse `[N]`, `[#N]`, `[N|]` or `[N<tab>]`, an optional `{fields}` list and
+/// the colon, returning the header and the text after the colon
+fn parse_header(text: &str, default_delimiter: DelimiterType) -> Option<(ArrayHeader, &str)> {
+    parse_header_with(
+        text,
+        default_delimiter.as_str().chars().next().unwrap_or(','),
+    )
+}
+
+pub(crate) fn parse_header_with(
+    text: &str,
+    default_delimiter: char,
+) -> Option<(ArrayHeader, &str)> {
+    let inner_end = text.find(']')?;
+    let inner = text.get(1..inner_end)?;
+    let length_marker = inner.starts_with('#');
+    let inner = inner.strip_prefix('#').unwrap_or(inner);
+
+    let (digits, delimiter, explicit_delimiter) = match inner.chars().last()? {
+        c @ ('|' | '\t' | ',') => (&inner[..inner.len() - 1], c, true),
+        _ => (inner, default_delimiter, false),
+    };
+    let length = digits.parse::<usize>().ok()?;
+
+    let mut after = &text[inner_end + 1..];
+    let fields = if after.starts_with('{') {
+        let fields_end = closing_brace(after)?;
+        let fields = split_cells(&after[1..fields_end], delimiter)
+            .ok()?
+            .into_iter()
+            .map(|field| {
+                if field.starts_with('"') {
+                    unescape(field.get(1..field.len().saturating_sub(1))?).ok()
+                } else {
+                    Some(field)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        after = &after[fields_end + 1..];
+        Some(fields)
+    } else {
+        None
+    };
+
+    let rest = after.strip_prefix(':')?.trim_start_matches(' ');
+    Some((
+        ArrayHeader {
+            length,
+            delimiter,
+            explicit_delimiter,
+            length_marker,
+            fields,
+        },
+        rest,
+    ))
+}
+
+/// Delimiter type for a header delimiter character
+pub(crate) fn delimiter_type(delimiter: char) -> DelimiterType {
+    match delimiter {
+        '|' => DelimiterType::Pipe,
+        '\t' => DelimiterType::Tab,
+        _ => DelimiterType::Comma,
+    }
+}
+
+/// Check if a key is a foldable path: identifier segments joined by dots
+pub(crate) fn is_folded_path(key: &str) -> bool {
+    key.contains('.')
+        && key.split('.').all(|segment| {
+            let mut chars = segment.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// Byte offset of the quote closing the string that opens `text`
+pub(crate) fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (offset, ch) in text.char_indices().skip(1) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Byte offset of the `}` closing a field list, skipping quoted field names
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (offset, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '}' if !in_quotes => return Some(offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split delimited values, keeping quoted delimiters, and trim spaces
+fn split_cells(text: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for ch in text.chars() {
+        if in_quotes {
+            current.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_quotes = false;
+            }
+        } else if ch == delimiter {
+            cells.push(current.trim_matches(' ').to_string());
+            current.clear();
+        } else {
+            if ch == '"' {
+                in_quotes = true;
+            }
+            current.push(ch);
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quoted string".to_string());
+    }
+    cells.push(current.trim_matches(' ').to_string());
+    Ok(cells)
+}
+
+/// Decode the escapes of a quoted string body
+fn unescape(body: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some(other) => return Err(format!("Invalid escape sequence '\\{}'", other)),
+            None => return Err("Unterminated escape sequence".to_string()),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parse a primitive token: quoted string, keyword, number or bare string
+pub(crate) fn parse_primitive(token: &str) -> Result<Value, String> {
+    let token = token.trim_matches(' ');
+
+    if token.starts_with('"') {
+        let end = closing_quote(token).ok_or("Unterminated quoted string")?;
+        if end != token.len() - 1 {
+            return Err("Unexpected characters after quoted string".to_string());
+        }
+        return unescape(&token[1..end]).map(Value::String);
+    }
+
+    Ok(match token {
+        "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        // JSON number syntax, so forms like `05` or `1.` stay strings
+        _ => match token.parse::<Number>() {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(token.to_string()),
+        },
+    })
+}
+
+/// Decode TOON text with the given options
+pub fn decode_toon(input: &str, options: &DecodeOptions) -> ParseResult<DecodedToon> {
+    ToonDecoder::new(options.clone()).decode(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use serde_json::json;
+
+    fn strict(input: &str) -> Value {
+        decode_toon(input, &DecodeOptions::strict()).unwrap().value
+    }
+
+    fn lenient(input: &str) -> DecodedToon {
+        decode_toon(input, &DecodeOptions::lenient()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_round_trips_encoder_output() {
+        let values = [
+            json!({"name": "Alice", "age": 30, "active": true, "score": -1.5, "none": null}),
+            json!({"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]}),
+            json!({"tags": ["a", "b c", "1", ""], "empty": []}),
+            json!({"outer": {"inner": {"deep": "x: y"}}}),
+            json!([{"id": 1}, {"id": 2}]),
+            json!({"items": [1, "two", [3, 4]]}),
+        ];
+
+        for value in values {
+            let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+            assert_eq!(
+                strict(&toon.content),
+                value,
+                "round trip of:\n{}",
+                toon.content
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_spec_forms() {
+        let input = "\
+items[2]:
+  - id: 1
+    tags[2]: a,b
+  - id: 2
+    tags[0]:
+pipe[3|]: a|b,c|d
+rows[#2]{\"first name\",n}:
+  \"Ann, Jr\",1
+  Bo,02
+";
+        assert_eq!(
+            strict(input),
+            json!({
+                "items": [{"id": 1, "tags": ["a", "b"]}, {"id": 2, "tags": []}],
+                "pipe": ["a", "b,c", "d"],
+                "rows": [{"first name": "Ann, Jr", "n": 1}, {"first name": "Bo", "n": "02"}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_root_primitive_and_empty() {
+        assert_eq!(strict("hello world"), json!("hello world"));
+        assert_eq!(strict("42"), json!(42));
+        assert_eq!(strict(""), json!({}));
+    }
+
+    #[test]
+    fn test_non_json_numbers_stay_strings() {
+        assert_eq!(
+            strict("a[4]: 05,1.,+1,-2.5e3"),
+            json!({"a": ["05", "1.", "+1", -2500.0]})
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_deviations() {
+        let err = decode_toon("users[3]{id}:\n  1\n  2\n", &DecodeOptions::strict()).unwrap_err();
+        assert_eq!(err.location, Some((1, 1)));
+        assert!(err.message.contains("declares 3 entries but 2"));
+
+        let err = decode_toon("a:\n   b: 1\n", &DecodeOptions::strict()).unwrap_err();
+        assert_eq!(err.location, Some((2, 4)));
+    }
+
+    #[test]
+    fn test_lenient_trusts_actual_row_count() {
+        let decoded = lenient("users[5]{id,name}:\n  1,Ann\n  2,Bo\n");
+        assert_eq!(
+            decoded.value,
+            json!({"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]})
+        );
+        assert_eq!(
+            decoded.warnings,
+            [DecodeWarning {
+                line: 1,
+                kind: DecodeWarningKind::LengthMismatch {
+                    declared: 5,
+                    actual: 2
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lenient_pads_and_trims_cells() {
+        let decoded = lenient("t[2]{a,b,c}:\n  1,2\n  3,4,5,6\n");
+        assert_eq!(
+            decoded.value,
+            json!({"t": [{"a": 1, "b": 2, "c": null}, {"a": 3, "b": 4, "c": 5}]})
+        );
+        assert_eq!(
+            decoded
+                .warnings
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "Row has 2 cells but the header declares 3 fields at line 2",
+                "Row has 4 cells but the header declares 3 fields at line 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_reinfers_indentation() {
+        let decoded =
+            lenient("config:\n    name: app\n    limits:\n        cpu: 2\n   debug: true\n");
+        assert_eq!(
+            decoded.value,
+            json!({"config": {"name": "app", "limits": {"cpu": 2}, "debug": true}})
+        );
+        assert_eq!(
+            decoded.warnings[0].kind,
+            DecodeWarningKind::Indentation {
+                expected: 2,
+                found: 3
+            }
+        );
+        assert!(decoded.warnings.len() >= 2);
+    }
+
+    #[test]
+    fn test_lenient_accepts_tabs() {
+        let decoded = lenient("a:\n\tb: 1\n");
+        assert_eq!(decoded.value, json!({"a": {"b": 1}}));
+        assert_eq!(decoded.warnings[0].kind, DecodeWarningKind::TabIndentation);
+    }
+
+    #[test]
+    fn test_warning_serializes_with_kind() {
+        let warning = DecodeWarning {
+            line: 4,
+            kind: DecodeWarningKind::ExtraCells {
+                expected: 2,
+                found: 3,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&warning).unwrap(),
+            json!({"line": 4, "kind": "extra_cells", "expected": 2, "found": 3})
+        );
+    }
+
+    #[test]
+    fn test_locations_map_pointers_to_lines() {
+        let decoded =
+            lenient("name: app\nusers[2]{id,a/b}:\n  1,x\n  2,y\nitems[1]:\n  - k: v\n    n: 1\n");
+        assert_eq!(decoded.locations[""], 1);
+        assert_eq!(decoded.locations["/users"], 2);
+        assert_eq!(decoded.locations["/users/1/a~1b"], 4);
+        assert_eq!(decoded.locations["/items/0/n"], 7);
+        assert_eq!(decoded.line_of("/users/1/missing"), Some(4));
+    }
+
+    #[test]
+    fn test_errors_report_line() {
+        let err = decode_toon("a: 1\nb \"x\"\n", &DecodeOptions::lenient()).unwrap_err();
+        assert_eq!(err.location, Some((2, 1)));
+        let err = decode_toon("a: \"open\n", &DecodeOptions::strict()).unwrap_err();
+        assert_eq!(err.location, Some((1, 1)));
+    }
+}
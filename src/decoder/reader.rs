@@ -0,0 +1,885 @@
+//! Streaming TOON pull parser
+//!
+//! [`ToonReader`] reads TOON line by line and yields [`ToonEvent`]s, so large
+//! documents such as tabular exports can be processed without building the
+//! whole `Value`. Only the current line and the chain of open containers are
+//! kept in memory.
+//!
+//! Table rows can be pulled one at a time with [`ToonReader::rows`], either as
+//! `Vec<Value>` in header order or deserialized into a struct:
+//!
+//! ```no_run
+//! # use toonconv::{DecodeOptions, ToonEvent, ToonReader};
+//! # #[derive(serde::Deserialize)] struct User { id: u64, name: String }
+//! # fn main() -> Result<(), toonconv::ParseError> {
+//! let file = std::io::BufReader::new(std::fs::File::open("users.toon").unwrap());
+//! let mut reader = ToonReader::new(file, DecodeOptions::strict());
+//! while let Some(event) = reader.next_event()? {
+//!     if let ToonEvent::StartTable { .. } = event {
+//!         for user in reader.rows().deserialize::<User>() {
+//!             let user = user?;
+//!             println!("{} {}", user.id, user.name);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The reader accepts the same syntax as [`ToonDecoder`](super::ToonDecoder)
+//! and honours lenient [`DecodeOptions`], collecting [`DecodeWarning`]s. In
+//! lenient mode the indentation unit is inferred from the first indented line,
+//! since the document is not available up front.
+
+use super::{
+    parse_header, parse_primitive, split_cells, split_key, DecodeOptions, DecodeWarning,
+    DecodeWarningKind, Line,
+};
+use crate::error::{ParseError, ParseResult};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+/// Event produced by [`ToonReader`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToonEvent {
+    /// Start of an object (the root, a nested object or a list item)
+    StartObject,
+    /// End of the current object
+    EndObject,
+    /// Key of the next value in the current object
+    Key(String),
+    /// Start of a tabular array, with its declared length and fields
+    StartTable { len: usize, fields: Vec<String> },
+    /// One table row, with a value per field in header order
+    Row(Vec<Value>),
+    /// End of the current table
+    EndTable,
+    /// Start of an inline or list array, with its declared length
+    StartArray { len: usize },
+    /// End of the current array
+    EndArray,
+    /// A primitive value
+    Value(Value),
+}
+
+/// Kind of open container
+#[derive(Debug)]
+enum FrameKind {
+    Object,
+    Table {
+        fields: Vec<String>,
+        delimiter: char,
+    },
+    List,
+}
+
+/// Open container and the indentation of the lines that belong to it
+#[derive(Debug)]
+struct Frame {
+    kind: FrameKind,
+    /// Lines belong to the frame while indented deeper than this
+    parent_indent: Option<usize>,
+    /// Expected indentation of the first line
+    expected_indent: usize,
+    /// Indentation of the first line, which its siblings must match
+    block_indent: Option<usize>,
+    /// Declared `[N]` length for arrays
+    declared: usize,
+    /// Rows or items read so far
+    count: usize,
+    /// Line of the array header
+    header_line: usize,
+}
+
+impl Frame {
+    fn object(parent_indent: Option<usize>, expected_indent: usize) -> Self {
+        Self {
+            kind: FrameKind::Object,
+            parent_indent,
+            expected_indent,
+            block_indent: None,
+            declared: 0,
+            count: 0,
+            header_line: 0,
+        }
+    }
+}
+
+/// Streaming TOON reader
+pub struct ToonReader<R> {
+    reader: R,
+    options: DecodeOptions,
+    unit: usize,
+    unit_inferred: bool,
+    line_number: usize,
+    current_line: usize,
+    buffer: String,
+    lookahead: VecDeque<Line>,
+    eof: bool,
+    stack: Vec<Frame>,
+    pending: VecDeque<ToonEvent>,
+    started: bool,
+    finished: bool,
+    table_fields: Vec<String>,
+    warnings: Vec<DecodeWarning>,
+}
+
+impl<R: BufRead> ToonReader<R> {
+    /// Create a reader over buffered TOON input
+    pub fn new(reader: R, options: DecodeOptions) -> Self {
+        let unit = options.indent_size;
+        Self {
+            reader,
+            options,
+            unit,
+            unit_inferred: false,
+            line_number: 0,
+            current_line: 0,
+            buffer: String::new(),
+            lookahead: VecDeque::new(),
+            eof: false,
+            stack: Vec::new(),
+            pending: VecDeque::new(),
+            started: false,
+            finished: false,
+            table_fields: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Deviations recovered from so far (always empty in strict mode)
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
+    /// Line of the most recently read TOON line (1-based)
+    pub fn line(&self) -> usize {
+        self.current_line
+    }
+
+    /// Pull the next event, or `None` at the end of the document
+    ///
+    /// After an error the reader is finished and yields no further events.
+    pub fn next_event(&mut self) -> ParseResult<Option<ToonEvent>> {
+        match self.advance() {
+            Err(e) => {
+                self.finished = true;
+                self.pending.clear();
+                self.stack.clear();
+                Err(e)
+            }
+            ok => ok,
+        }
+    }
+
+    /// Iterate over the rows of the table just started
+    ///
+    /// Call after a [`ToonEvent::StartTable`]; iteration consumes the rows and
+    /// the matching [`ToonEvent::EndTable`].
+    pub fn rows(&mut self) -> Rows<'_, R> {
+        let fields = self.table_fields.clone();
+        Rows {
+            reader: self,
+            fields,
+        }
+    }
+
+    fn advance(&mut self) -> ParseResult<Option<ToonEvent>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+        if self.finished {
+            return Ok(None);
+        }
+        if !self.started {
+            self.started = true;
+            self.start_root()?;
+            return Ok(self.pending.pop_front());
+        }
+
+        self.fill(1)?;
+        let Some(frame) = self.stack.last() else {
+            if let Some(line) = self.lookahead.front() {
+                return Err(ParseError::new(
+                    "Unexpected content after the end of the document".to_string(),
+                    Some((line.number, line.indent + 1)),
+                ));
+            }
+            self.finished = true;
+            return Ok(None);
+        };
+
+        let belongs = self
+            .lookahead
+            .front()
+            .is_some_and(|line| !matches!(frame.parent_indent, Some(p) if line.indent <= p));
+        if !belongs {
+            let frame = self.stack.pop().expect("frame checked above");
+            self.end_frame(frame)?;
+            return Ok(self.pending.pop_front());
+        }
+
+        self.check_indent()?;
+        let line = self.lookahead.pop_front().expect("line checked above");
+        self.current_line = line.number;
+
+        match self.stack.last().map(|frame| &frame.kind) {
+            Some(FrameKind::Object) => self.read_field(&line)?,
+            Some(FrameKind::Table { .. }) => self.read_row(&line)?,
+            Some(FrameKind::List) => self.read_item(&line)?,
+            None => unreachable!("frame checked above"),
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Buffer up to `count` non-blank lines
+    fn fill(&mut self, count: usize) -> ParseResult<()> {
+        while self.lookahead.len() < count && !self.eof {
+            self.buffer.clear();
+            let read = self.reader.read_line(&mut self.buffer).map_err(|e| {
+                ParseError::new(
+                    format!("Failed to read TOON input: {}", e),
+                    Some((self.line_number + 1, 1)),
+                )
+            })?;
+            if read == 0 {
+                self.eof = true;
+                break;
+            }
+            self.line_number += 1;
+
+            let raw = self.buffer.trim_end();
+            if raw.is_empty() {
+                continue;
+            }
+            let (line, tabs) = Line::measure(self.line_number, raw, self.options.indent_size);
+            if tabs {
+                self.deviation(line.number, 1, DecodeWarningKind::TabIndentation)?;
+            }
+            if self.options.lenient && !self.unit_inferred && line.indent > 0 {
+                self.unit_inferred = true;
+                if line.indent != self.options.indent_size {
+                    self.warnings.push(DecodeWarning {
+                        line: line.number,
+                        kind: DecodeWarningKind::Indentation {
+                            expected: self.options.indent_size,
+                            found: line.indent,
+                        },
+                    });
+                    self.unit = line.indent;
+                }
+            }
+            self.lookahead.push_back(line);
+        }
+        Ok(())
+    }
+
+    /// Record a deviation in lenient mode, or fail in strict mode
+    fn deviation(
+        &mut self,
+        line: usize,
+        column: usize,
+        kind: DecodeWarningKind,
+    ) -> ParseResult<()> {
+        if self.options.lenient {
+            self.warnings.push(DecodeWarning { line, kind });
+            Ok(())
+        } else {
+            Err(ParseError::new(kind.to_string(), Some((line, column))))
+        }
+    }
+
+    fn error(line: &Line, message: &str) -> ParseError {
+        ParseError::new(message.to_string(), Some((line.number, line.indent + 1)))
+            .with_preview(line.content.clone())
+    }
+
+    /// Check the next line's indentation against the innermost frame
+    fn check_indent(&mut self) -> ParseResult<()> {
+        let (number, indent) = match self.lookahead.front() {
+            Some(line) => (line.number, line.indent),
+            None => return Ok(()),
+        };
+        let frame = self.stack.last_mut().expect("frame checked by caller");
+        let expected = frame.block_indent.unwrap_or(frame.expected_indent);
+        frame.block_indent.get_or_insert(indent);
+
+        if indent != expected {
+            self.deviation(
+                number,
+                indent + 1,
+                DecodeWarningKind::Indentation {
+                    expected,
+                    found: indent,
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    fn start_root(&mut self) -> ParseResult<()> {
+        self.fill(2)?;
+        let Some(first) = self.lookahead.front().cloned() else {
+            // An empty document is an empty object
+            self.pending.push_back(ToonEvent::StartObject);
+            self.pending.push_back(ToonEvent::EndObject);
+            return Ok(());
+        };
+
+        if first.content.starts_with('[') {
+            if first.indent != 0 {
+                self.deviation(
+                    first.number,
+                    first.indent + 1,
+                    DecodeWarningKind::Indentation {
+                        expected: 0,
+                        found: first.indent,
+                    },
+                )?;
+            }
+            self.lookahead.pop_front();
+            self.current_line = first.number;
+            return self.start_array(&first, &first.content);
+        }
+
+        if self.lookahead.len() == 1 && split_key(&first.content).is_none() {
+            self.lookahead.pop_front();
+            self.current_line = first.number;
+            let value =
+                parse_primitive(&first.content).map_err(|message| Self::error(&first, &message))?;
+            self.pending.push_back(ToonEvent::Value(value));
+            return Ok(());
+        }
+
+        self.stack.push(Frame::object(None, 0));
+        self.pending.push_back(ToonEvent::StartObject);
+        Ok(())
+    }
+
+    /// Start an array from its header text (starting at `[`)
+    fn start_array(&mut self, line: &Line, header_text: &str) -> ParseResult<()> {
+        let (header, rest) = parse_header(header_text, self.options.delimiter)
+            .ok_or_else(|| Self::error(line, "Invalid array header"))?;
+
+        if let Some(fields) = header.fields {
+            if !rest.is_empty() {
+                return Err(Self::error(
+                    line,
+                    "Unexpected content after tabular array header",
+                ));
+            }
+            self.table_fields = fields.clone();
+            self.pending.push_back(ToonEvent::StartTable {
+                len: header.length,
+                fields: fields.clone(),
+            });
+            self.stack.push(Frame {
+                kind: FrameKind::Table {
+                    fields,
+                    delimiter: header.delimiter,
+                },
+                parent_indent: Some(line.indent),
+                expected_indent: line.indent + self.unit,
+                block_indent: None,
+                declared: header.length,
+                count: 0,
+                header_line: line.number,
+            });
+        } else if !rest.is_empty() {
+            let cells = split_cells(rest, header.delimiter)
+                .map_err(|message| Self::error(line, &message))?;
+            if cells.len() != header.length {
+                self.deviation(
+                    line.number,
+                    line.indent + 1,
+                    DecodeWarningKind::LengthMismatch {
+                        declared: header.length,
+                        actual: cells.len(),
+                    },
+                )?;
+            }
+            self.pending
+                .push_back(ToonEvent::StartArray { len: header.length });
+            for cell in &cells {
+                let value = parse_primitive(cell).map_err(|message| Self::error(line, &message))?;
+                self.pending.push_back(ToonEvent::Value(value));
+            }
+            self.pending.push_back(ToonEvent::EndArray);
+        } else {
+            self.pending
+                .push_back(ToonEvent::StartArray { len: header.length });
+            self.stack.push(Frame {
+                kind: FrameKind::List,
+                parent_indent: Some(line.indent),
+                expected_indent: line.indent + self.unit,
+                block_indent: None,
+                declared: header.length,
+                count: 0,
+                header_line: line.number,
+            });
+        }
+        Ok(())
+    }
+
+    /// Read a `key: value`, `key:` or `key[N]...:` line
+    fn read_field(&mut self, line: &Line) -> ParseResult<()> {
+        let (key, after) =
+            split_key(&line.content).ok_or_else(|| Self::error(line, "Expected 'key: value'"))?;
+        let key = key.map_err(|message| Self::error(line, &message))?;
+        self.pending.push_back(ToonEvent::Key(key));
+
+        if after.starts_with('[') {
+            return self.start_array(line, after);
+        }
+
+        // split_key guarantees the key is followed by '[' or ':'
+        let rest = after[1..].trim_start();
+        if rest.is_empty() {
+            self.stack
+                .push(Frame::object(Some(line.indent), line.indent + self.unit));
+            self.pending.push_back(ToonEvent::StartObject);
+        } else {
+            let value = parse_primitive(rest).map_err(|message| Self::error(line, &message))?;
+            self.pending.push_back(ToonEvent::Value(value));
+        }
+        Ok(())
+    }
+
+    /// Read one tabular row
+    fn read_row(&mut self, line: &Line) -> ParseResult<()> {
+        let Some(Frame {
+            kind: FrameKind::Table { fields, delimiter },
+            ..
+        }) = self.stack.last()
+        else {
+            unreachable!("read_row is only called inside a table");
+        };
+        let expected = fields.len();
+        let mut cells = split_cells(&line.content, *delimiter)
+            .map_err(|message| Self::error(line, &message))?;
+
+        if cells.len() < expected {
+            self.deviation(
+                line.number,
+                line.indent + 1,
+                DecodeWarningKind::MissingCells {
+                    expected,
+                    found: cells.len(),
+                },
+            )?;
+        } else if cells.len() > expected {
+            self.deviation(
+                line.number,
+                line.indent + 1,
+                DecodeWarningKind::ExtraCells {
+                    expected,
+                    found: cells.len(),
+                },
+            )?;
+            cells.truncate(expected);
+        }
+
+        let mut values = cells
+            .iter()
+            .map(|cell| parse_primitive(cell))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|message| Self::error(line, &message))?;
+        values.resize(expected, Value::Null);
+
+        if let Some(frame) = self.stack.last_mut() {
+            frame.count += 1;
+        }
+        self.pending.push_back(ToonEvent::Row(values));
+        Ok(())
+    }
+
+    /// Read one `- item` line of a list array
+    fn read_item(&mut self, line: &Line) -> ParseResult<()> {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.count += 1;
+        }
+
+        if line.content == "-" {
+            // Object whose fields all follow on deeper lines
+            self.stack
+                .push(Frame::object(Some(line.indent), line.indent + self.unit));
+            self.pending.push_back(ToonEvent::StartObject);
+            return Ok(());
+        }
+
+        let Some(content) = line.content.strip_prefix("- ") else {
+            return Err(Self::error(line, "Expected a list item starting with '- '"));
+        };
+        let content = content.trim_start();
+
+        if content.starts_with('[') && parse_header(content, self.options.delimiter).is_some() {
+            return self.start_array(line, content);
+        }
+
+        if split_key(content).is_some() {
+            // Object whose first field sits on the hyphen line. Re-queue that
+            // field as a line of its own, aligned with the fields that follow.
+            let field_indent = line.indent + self.unit;
+            self.lookahead.push_front(Line {
+                number: line.number,
+                indent: field_indent,
+                content: content.to_string(),
+            });
+            self.stack
+                .push(Frame::object(Some(line.indent), field_indent));
+            self.pending.push_back(ToonEvent::StartObject);
+            return Ok(());
+        }
+
+        let value = parse_primitive(content).map_err(|message| Self::error(line, &message))?;
+        self.pending.push_back(ToonEvent::Value(value));
+        Ok(())
+    }
+
+    /// Emit the end event of a closed frame, checking array lengths
+    fn end_frame(&mut self, frame: Frame) -> ParseResult<()> {
+        let event = match frame.kind {
+            FrameKind::Object => ToonEvent::EndObject,
+            FrameKind::Table { .. } => ToonEvent::EndTable,
+            FrameKind::List => ToonEvent::EndArray,
+        };
+
+        if event != ToonEvent::EndObject && frame.count != frame.declared {
+            self.deviation(
+                frame.header_line,
+                1,
+                DecodeWarningKind::LengthMismatch {
+                    declared: frame.declared,
+                    actual: frame.count,
+                },
+            )?;
+        }
+
+        self.pending.push_back(event);
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for ToonReader<R> {
+    type Item = ParseResult<ToonEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Rows of the current table, pulled one at a time
+pub struct Rows<'a, R> {
+    reader: &'a mut ToonReader<R>,
+    fields: Vec<String>,
+}
+
+impl<'a, R: BufRead> Rows<'a, R> {
+    /// Field names of the table, in header order
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Deserialize each row into `T`, keyed by the header's field names
+    pub fn deserialize<T: DeserializeOwned>(self) -> DeserializeRows<'a, R, T> {
+        DeserializeRows {
+            rows: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Rows<'_, R> {
+    type Item = ParseResult<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.next_event() {
+            Ok(Some(ToonEvent::Row(values))) => Some(Ok(values)),
+            Ok(Some(ToonEvent::EndTable)) | Ok(None) => None,
+            Ok(Some(other)) => Some(Err(ParseError::new(
+                format!("Expected a table row, found {:?}", other),
+                Some((self.reader.line(), 1)),
+            ))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Table rows deserialized into `T`
+pub struct DeserializeRows<'a, R, T> {
+    rows: Rows<'a, R>,
+    marker: PhantomData<T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for DeserializeRows<'_, R, T> {
+    type Item = ParseResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let values = match self.rows.next()? {
+            Ok(values) => values,
+            Err(e) => return Some(Err(e)),
+        };
+        let row: Map<String, Value> = self.rows.fields.iter().cloned().zip(values).collect();
+
+        Some(serde_json::from_value(Value::Object(row)).map_err(|e| {
+            ParseError::new(
+                format!("Cannot deserialize table row: {}", e),
+                Some((self.rows.reader.line(), 1)),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::decode_toon;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn reader(input: &str) -> ToonReader<Cursor<&str>> {
+        ToonReader::new(Cursor::new(input), DecodeOptions::strict())
+    }
+
+    type Events = std::iter::Peekable<std::vec::IntoIter<ToonEvent>>;
+
+    /// Rebuild a value from events, to compare with the whole-document decoder
+    fn build(events: &mut Events) -> Value {
+        match events.next().unwrap() {
+            ToonEvent::Value(value) => value,
+            ToonEvent::StartObject => {
+                let mut object = Map::new();
+                loop {
+                    match events.next().unwrap() {
+                        ToonEvent::EndObject => return Value::Object(object),
+                        ToonEvent::Key(key) => {
+                            object.insert(key, build(events));
+                        }
+                        other => panic!("unexpected {:?}", other),
+                    }
+                }
+            }
+            ToonEvent::StartArray { .. } => {
+                let mut items = Vec::new();
+                while events.peek() != Some(&ToonEvent::EndArray) {
+                    items.push(build(events));
+                }
+                events.next();
+                Value::Array(items)
+            }
+            ToonEvent::StartTable { fields, .. } => {
+                let mut rows = Vec::new();
+                for event in events.by_ref() {
+                    match event {
+                        ToonEvent::Row(values) => {
+                            rows.push(Value::Object(fields.iter().cloned().zip(values).collect()))
+                        }
+                        ToonEvent::EndTable => break,
+                        other => panic!("unexpected {:?}", other),
+                    }
+                }
+                Value::Array(rows)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_events_for_nested_document() {
+        let events: Vec<_> =
+            reader("name: app\nusers[2]{id,name}:\n  1,Ann\n  2,Bo\nmeta:\n  tags[1]: x\n")
+                .collect::<ParseResult<_>>()
+                .unwrap();
+        assert_eq!(
+            events,
+            [
+                ToonEvent::StartObject,
+                ToonEvent::Key("name".into()),
+                ToonEvent::Value(json!("app")),
+                ToonEvent::Key("users".into()),
+                ToonEvent::StartTable {
+                    len: 2,
+                    fields: vec!["id".into(), "name".into()]
+                },
+                ToonEvent::Row(vec![json!(1), json!("Ann")]),
+                ToonEvent::Row(vec![json!(2), json!("Bo")]),
+                ToonEvent::EndTable,
+                ToonEvent::Key("meta".into()),
+                ToonEvent::StartObject,
+                ToonEvent::Key("tags".into()),
+                ToonEvent::StartArray { len: 1 },
+                ToonEvent::Value(json!("x")),
+                ToonEvent::EndArray,
+                ToonEvent::EndObject,
+                ToonEvent::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_match_decoder() {
+        let documents = [
+            "a: 1\nb:\n  c[2]: x,y\n  d: true",
+            "[2]:\n  - id: 1\n    tags[1]: a\n  - [2]: 1,2",
+            "items[2]:\n  -\n    x: 1\n  - plain\nend: null",
+            "[2]{a,b}:\n  1,2\n  3,4",
+            "\"quoted key\": \"v: w\"",
+            "hello",
+            "",
+        ];
+        for document in documents {
+            let mut events = reader(document)
+                .collect::<ParseResult<Vec<_>>>()
+                .unwrap()
+                .into_iter()
+                .peekable();
+            let value = build(&mut events);
+            assert!(
+                events.next().is_none(),
+                "trailing events for {:?}",
+                document
+            );
+            assert_eq!(
+                value,
+                decode_toon(document, &DecodeOptions::strict())
+                    .unwrap()
+                    .value,
+                "document {:?}",
+                document
+            );
+        }
+    }
+
+    #[test]
+    fn test_rows_iterate_one_at_a_time() {
+        let input = format!(
+            "rows[1000]{{n,sq}}:\n{}after: done\n",
+            (0..1000)
+                .map(|n| format!("  {},{}\n", n, n * n))
+                .collect::<String>()
+        );
+        let mut reader = reader(&input);
+        assert_eq!(reader.next_event().unwrap(), Some(ToonEvent::StartObject));
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Some(ToonEvent::Key("rows".into()))
+        );
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(ToonEvent::StartTable { len: 1000, .. })
+        ));
+
+        let mut count = 0;
+        for row in reader.rows() {
+            let row = row.unwrap();
+            assert_eq!(row, vec![json!(count), json!(count * count)]);
+            count += 1;
+        }
+        assert_eq!(count, 1000);
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Some(ToonEvent::Key("after".into()))
+        );
+    }
+
+    #[test]
+    fn test_rows_deserialize_into_structs() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: u32,
+            name: String,
+            email: Option<String>,
+        }
+
+        let mut reader = reader("[2]{id,name,email}:\n  1,Ann,null\n  2,Bo,bo@example.com\n");
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(ToonEvent::StartTable { .. })
+        ));
+        let users: Vec<User> = reader
+            .rows()
+            .deserialize()
+            .collect::<ParseResult<_>>()
+            .unwrap();
+        assert_eq!(
+            users,
+            [
+                User {
+                    id: 1,
+                    name: "Ann".into(),
+                    email: None
+                },
+                User {
+                    id: 2,
+                    name: "Bo".into(),
+                    email: Some("bo@example.com".into())
+                },
+            ]
+        );
+        assert_eq!(reader.next_event().unwrap(), None);
+    }
+
+    #[test]
+    fn test_deserialize_error_reports_line() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Row {
+            id: u32,
+        }
+
+        let mut reader = reader("[2]{id}:\n  1\n  x\n");
+        reader.next_event().unwrap();
+        let err = reader
+            .rows()
+            .deserialize::<Row>()
+            .collect::<ParseResult<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!(err.location, Some((3, 1)));
+    }
+
+    #[test]
+    fn test_strict_length_mismatch_fails_at_table_end() {
+        let err = reader("t[3]{a}:\n  1\n  2\n")
+            .collect::<ParseResult<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!(err.location, Some((1, 1)));
+    }
+
+    #[test]
+    fn test_lenient_reader_collects_warnings() {
+        let mut reader = ToonReader::new(
+            Cursor::new("t[3]{a,b}:\n    1\n    2,3,4\n"),
+            DecodeOptions::lenient(),
+        );
+        let events: Vec<_> = reader.by_ref().collect::<ParseResult<_>>().unwrap();
+        assert!(events.contains(&ToonEvent::Row(vec![json!(1), Value::Null])));
+        assert!(events.contains(&ToonEvent::Row(vec![json!(2), json!(3)])));
+
+        let kinds: Vec<_> = reader.warnings().iter().map(|w| w.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                DecodeWarningKind::Indentation {
+                    expected: 2,
+                    found: 4
+                },
+                DecodeWarningKind::MissingCells {
+                    expected: 2,
+                    found: 1
+                },
+                DecodeWarningKind::ExtraCells {
+                    expected: 2,
+                    found: 3
+                },
+                DecodeWarningKind::LengthMismatch {
+                    declared: 3,
+                    actual: 2
+                },
+            ]
+        );
+    }
+}
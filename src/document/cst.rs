@@ -0,0 +1,1274 @@
+//! Format-preserving TOON editing
+//!
+//! [`ToonCst`] is a lossless concrete syntax tree over the lines of a TOON
+//! file: printing an unedited tree gives back the input byte for byte,
+//! including blank lines, indentation, quoting style, delimiters and line
+//! endings. Edits address values by JSON Pointer and rewrite only the lines
+//! that hold them:
+//!
+//! - Setting a primitive replaces its token in place, keeping quotes if the
+//!   old value was quoted
+//! - Inserting or removing array items touches the items' lines and the
+//!   `[N]` count in the header
+//! - Values that change shape (a primitive becoming an object, a table row
+//!   gaining a nested field) re-render the smallest enclosing field, list item
+//!   or array, keeping the recorded layout where it still fits
+//!
+//! ```
+//! # use toonconv::document::cst::ToonCst;
+//! # use serde_json::json;
+//! let mut cst = ToonCst::parse("name:   \"Ann\"\ntags[2|]: a|b\n")?;
+//! cst.set("/name", &json!("Bo"))?;
+//! cst.insert("/tags/-", &json!("c"))?;
+//! assert_eq!(cst.to_string(), "name:   \"Bo\"\ntags[3|]: a|b|c\n");
+//! # Ok::<(), toonconv::ConversionError>(())
+//! ```
+
+use super::source_map::{CellMapping, LineMapping, SourceMap};
+use super::{
+    pointer_segments, ArrayLayout, ToonArray, ToonDocument, ToonField, ToonNode, ToonObject,
+};
+use crate::conversion::{ConversionConfig, ConversionResult, DelimiterType, QuoteStrategy};
+use crate::decoder::{delimiter_type, parse_header_with, parse_primitive, split_key, ArrayHeader};
+use crate::error::{ConversionError, FormattingError, ParseError, ParseResult};
+use crate::formatter::ToonFormatter;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Lossless syntax tree of a TOON document
+#[derive(Debug, Clone)]
+pub struct ToonCst {
+    lines: Vec<String>,
+    line_ending: &'static str,
+    trailing_newline: bool,
+    delimiter: DelimiterType,
+    unit: usize,
+    root: Node,
+}
+
+/// A value and the lines it occupies
+#[derive(Debug, Clone)]
+struct Node {
+    slot: Slot,
+    /// Lines of the entry holding the value (a field, list item or row)
+    lines: Range<usize>,
+    kind: Kind,
+}
+
+/// Where a value sits in its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    Root,
+    /// Object field; `prefix` is the byte offset where the key text ends.
+    /// `hyphen` marks the first field of a list-item object, which shares the
+    /// item's hyphen line.
+    Field {
+        line: usize,
+        prefix: usize,
+        hyphen: bool,
+    },
+    ListItem {
+        line: usize,
+    },
+    Row {
+        line: usize,
+    },
+    /// Value in an inline array or tabular row
+    Cell,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Scalar {
+        line: usize,
+        span: Range<usize>,
+    },
+    Object {
+        /// Indentation of the fields, when known
+        indent: Option<usize>,
+        fields: Vec<(String, Node)>,
+    },
+    Array(ArrayNode),
+}
+
+#[derive(Debug, Clone)]
+struct ArrayNode {
+    header: usize,
+    /// Span of the length digits in the header
+    count: Range<usize>,
+    /// Byte offset just after the header's colon
+    colon_end: usize,
+    delimiter: char,
+    explicit_delimiter: bool,
+    length_marker: bool,
+    /// An object list item puts its first field on the hyphen line
+    hyphen_fields: bool,
+    layout: ArrayLayout,
+    /// Indentation of the rows or list items, when known
+    item_indent: Option<usize>,
+    items: Vec<Node>,
+}
+
+/// Change to the lines of the document
+#[derive(Debug)]
+enum Edit {
+    Splice {
+        line: usize,
+        range: Range<usize>,
+        text: String,
+    },
+    Replace {
+        lines: Range<usize>,
+        with: Vec<String>,
+    },
+}
+
+impl Edit {
+    /// Position used to apply edits from the end of the document backwards
+    fn position(&self) -> (usize, usize) {
+        match self {
+            Edit::Splice { line, range, .. } => (*line, range.start),
+            Edit::Replace { lines, .. } => (lines.start, usize::MAX),
+        }
+    }
+}
+
+impl ToonCst {
+    /// Parse TOON text with comma as the default array delimiter
+    pub fn parse(input: &str) -> ParseResult<Self> {
+        Self::parse_with(input, DelimiterType::Comma)
+    }
+
+    /// Parse TOON text with a default array delimiter
+    pub fn parse_with(input: &str, delimiter: DelimiterType) -> ParseResult<Self> {
+        let line_ending = if input.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = input.ends_with('\n');
+        let body = input.strip_suffix('\n').unwrap_or(input);
+        let lines: Vec<String> = if input.is_empty() {
+            Vec::new()
+        } else {
+            body.split('\n')
+                .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+                .collect()
+        };
+        Self::from_lines(lines, line_ending, trailing_newline, delimiter)
+    }
+
+    fn from_lines(
+        lines: Vec<String>,
+        line_ending: &'static str,
+        trailing_newline: bool,
+        delimiter: DelimiterType,
+    ) -> ParseResult<Self> {
+        let unit = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| indent_of(line))
+            .filter(|indent| *indent > 0)
+            .min()
+            .unwrap_or(2);
+        let root = Parser {
+            lines: &lines,
+            pos: 0,
+            last: 0,
+            unit,
+            delimiter: delimiter.as_str().chars().next().unwrap_or(','),
+        }
+        .parse()?;
+
+        Ok(Self {
+            lines,
+            line_ending,
+            trailing_newline,
+            delimiter,
+            unit,
+            root,
+        })
+    }
+
+    /// Source map linking each line and primitive value to its JSON Pointer
+    pub fn source_map(&self) -> SourceMap {
+        let mut lines = BTreeMap::new();
+        self.map_node(&self.root, "", &mut lines);
+        SourceMap {
+            lines: lines.into_values().collect(),
+            ..SourceMap::new()
+        }
+    }
+
+    fn map_node(&self, node: &Node, pointer: &str, lines: &mut BTreeMap<usize, LineMapping>) {
+        let entry = match node.slot {
+            // A root object's lines belong to its fields
+            Slot::Root => Some(node.lines.start)
+                .filter(|_| !matches!(node.kind, Kind::Object { .. }) && !node.lines.is_empty()),
+            Slot::Field { line, .. } | Slot::ListItem { line } | Slot::Row { line } => Some(line),
+            Slot::Cell => None,
+        };
+        if let Some(line) = entry {
+            mapping(lines, line, pointer);
+        }
+
+        match &node.kind {
+            Kind::Scalar { line, span } => {
+                let text = &self.lines[*line];
+                let start = text[..span.start].chars().count() + 1;
+                let end = start + text[span.clone()].chars().count();
+                mapping(lines, *line, pointer).cells.push(CellMapping {
+                    start,
+                    end,
+                    pointer: pointer.to_string(),
+                });
+            }
+            Kind::Object { fields, .. } => {
+                for (key, child) in fields {
+                    let child_pointer =
+                        format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                    self.map_node(child, &child_pointer, lines);
+                }
+            }
+            Kind::Array(array) => {
+                for (index, item) in array.items.iter().enumerate() {
+                    self.map_node(item, &format!("{}/{}", pointer, index), lines);
+                }
+            }
+        }
+    }
+
+    /// Document model of the tree
+    pub fn to_document(&self) -> ToonDocument {
+        ToonDocument {
+            root: self.node(&self.root),
+            indent_size: self.unit,
+            delimiter: self.delimiter,
+        }
+    }
+
+    /// JSON value of the tree
+    pub fn to_value(&self) -> Value {
+        self.to_document().to_value()
+    }
+
+    /// Check if a JSON Pointer addresses a value
+    pub fn contains(&self, pointer: &str) -> bool {
+        pointer_segments(pointer)
+            .and_then(|segments| self.resolve(&segments))
+            .is_some()
+    }
+
+    /// Replace the value at a JSON Pointer, or add it when the pointer names
+    /// a missing key of an existing object
+    pub fn set(&mut self, pointer: &str, value: &Value) -> ConversionResult<()> {
+        let segments = parse_pointer(pointer)?;
+        let edits = match self.resolve(&segments) {
+            Some(chain) => self.set_plan(&chain, value)?,
+            None => match segments.split_last() {
+                Some((key, parent)) if self.resolve(parent).is_some_and(|c| is_object(&c)) => {
+                    let chain = self.resolve(parent).unwrap_or_default();
+                    self.insert_plan(&chain, key, value)?
+                }
+                _ => return Err(missing(pointer)),
+            },
+        };
+        self.apply(edits)
+    }
+
+    /// Insert a value: a new key into an object, or an item into an array at
+    /// an index (`-` appends)
+    pub fn insert(&mut self, pointer: &str, value: &Value) -> ConversionResult<()> {
+        let segments = parse_pointer(pointer)?;
+        let (key, parent) = segments
+            .split_last()
+            .ok_or_else(|| structure("Cannot insert at the document root"))?;
+        let chain = self.resolve(parent).ok_or_else(|| missing(pointer))?;
+        let edits = self.insert_plan(&chain, key, value)?;
+        self.apply(edits)
+    }
+
+    /// Remove the value at a JSON Pointer
+    pub fn remove(&mut self, pointer: &str) -> ConversionResult<()> {
+        let segments = parse_pointer(pointer)?;
+        let chain = self.resolve(&segments).ok_or_else(|| missing(pointer))?;
+        let edits = self.remove_plan(&chain)?;
+        self.apply(edits)
+    }
+
+    /// Nodes from the root to the value addressed by pointer segments
+    fn resolve(&self, segments: &[String]) -> Option<Vec<&Node>> {
+        let mut chain = vec![&self.root];
+        for segment in segments {
+            let child = chain.last()?.child(segment)?;
+            chain.push(child);
+        }
+        Some(chain)
+    }
+
+    fn set_plan(&self, chain: &[&Node], value: &Value) -> ConversionResult<Vec<Edit>> {
+        let node = chain[chain.len() - 1];
+        if let Kind::Scalar { line, span } = &node.kind {
+            if !value.is_array() && !value.is_object() {
+                let quoted = self.lines[*line][span.clone()].starts_with('"');
+                let delimiter = self.cell_delimiter(chain);
+                return Ok(vec![Edit::Splice {
+                    line: *line,
+                    range: span.clone(),
+                    text: self.scalar(value, delimiter, quoted)?,
+                }]);
+            }
+        }
+        self.replace_plan(chain, ToonNode::from_value(value))
+    }
+
+    /// Edits re-rendering the value at the end of `chain` as `new`
+    fn replace_plan(&self, chain: &[&Node], new: ToonNode) -> ConversionResult<Vec<Edit>> {
+        let node = chain[chain.len() - 1];
+        match node.slot {
+            Slot::Root => Ok(vec![Edit::Replace {
+                lines: 0..self.lines.len(),
+                with: self.render(new)?,
+            }]),
+            Slot::Field {
+                line,
+                prefix,
+                hyphen: false,
+            } => {
+                let rendered = self.render(single_field("k", new))?;
+                let indent = &self.lines[line][..indent_of(&self.lines[line])];
+                let mut with = vec![format!(
+                    "{}{}",
+                    &self.lines[line][..prefix],
+                    &rendered[0][1..]
+                )];
+                with.extend(
+                    rendered[1..]
+                        .iter()
+                        .map(|text| format!("{}{}", indent, text)),
+                );
+                Ok(vec![Edit::Replace {
+                    lines: node.lines.clone(),
+                    with,
+                }])
+            }
+            Slot::ListItem { line } => Ok(vec![Edit::Replace {
+                lines: node.lines.clone(),
+                with: self.render_item(
+                    indent_of(&self.lines[line]),
+                    new,
+                    chain[chain.len() - 2].array().hyphen_fields,
+                )?,
+            }]),
+            Slot::Row { line } => {
+                let array = chain[chain.len() - 2].array();
+                match self.row(array, &new)? {
+                    Some(row) => Ok(vec![Edit::Replace {
+                        lines: line..line + 1,
+                        with: vec![format!(
+                            "{}{}",
+                            " ".repeat(indent_of(&self.lines[line])),
+                            row
+                        )],
+                    }]),
+                    None => self.rebuild_parent(chain, |children, index| children[index] = new),
+                }
+            }
+            // First fields of list-item objects and cells are not standalone
+            // lines; re-render the parent
+            Slot::Field { hyphen: true, .. } | Slot::Cell => {
+                self.rebuild_parent(chain, |children, index| children[index] = new)
+            }
+        }
+    }
+
+    /// Edits re-rendering the parent of the value at the end of `chain` after
+    /// changing its children (given with the value's index)
+    fn rebuild_parent(
+        &self,
+        chain: &[&Node],
+        change: impl FnOnce(&mut Vec<ToonNode>, usize),
+    ) -> ConversionResult<Vec<Edit>> {
+        let node = chain[chain.len() - 1];
+        let parent_chain = &chain[..chain.len() - 1];
+        let parent = parent_chain[parent_chain.len() - 1];
+        let index = parent
+            .children()
+            .position(|child| std::ptr::eq(child, node))
+            .expect("chain nodes are children of their parent");
+
+        let rebuilt = match self.node(parent) {
+            ToonNode::Object(mut object) => {
+                let mut values: Vec<ToonNode> =
+                    object.fields.iter().map(|f| f.value.clone()).collect();
+                change(&mut values, index);
+                if values.len() < object.fields.len() {
+                    object.fields.remove(index);
+                } else {
+                    for (field, value) in object.fields.iter_mut().zip(values) {
+                        field.value = value;
+                    }
+                }
+                ToonNode::Object(object)
+            }
+            ToonNode::Array(mut array) => {
+                change(&mut array.items, index);
+                ToonNode::Array(array)
+            }
+            ToonNode::Primitive(_) => unreachable!("primitives have no children"),
+        };
+        self.replace_plan(parent_chain, rebuilt)
+    }
+
+    fn insert_plan(
+        &self,
+        chain: &[&Node],
+        key: &str,
+        value: &Value,
+    ) -> ConversionResult<Vec<Edit>> {
+        let parent = chain[chain.len() - 1];
+        let new = ToonNode::from_value(value);
+
+        if let Kind::Object { indent, fields } = &parent.kind {
+            if fields.iter().any(|(name, _)| name == key) {
+                return Err(structure(&format!("Key '{}' already exists", key)));
+            }
+            if matches!(parent.slot, Slot::Row { .. }) {
+                let mut object = match self.node(parent) {
+                    ToonNode::Object(object) => object,
+                    _ => unreachable!("rows are objects"),
+                };
+                object.fields.push(plain_field(key, new));
+                return self.replace_plan(chain, ToonNode::Object(object));
+            }
+            let indent = indent.unwrap_or_else(|| self.child_indent(parent));
+            let with = self
+                .render(single_field(key, new))?
+                .into_iter()
+                .map(|text| format!("{}{}", " ".repeat(indent), text))
+                .collect();
+            return Ok(vec![Edit::Replace {
+                lines: parent.lines.end..parent.lines.end,
+                with,
+            }]);
+        }
+
+        let Kind::Array(array) = &parent.kind else {
+            return Err(structure("Cannot insert into a primitive value"));
+        };
+        let len = array.items.len();
+        let index = if key == "-" {
+            len
+        } else {
+            key.parse::<usize>()
+                .ok()
+                .filter(|index| *index <= len)
+                .ok_or_else(|| structure(&format!("Invalid array index '{}'", key)))?
+        };
+        let mut edits = vec![self.count_edit(array, len + 1)];
+        let item_indent = array
+            .item_indent
+            .unwrap_or_else(|| indent_of(&self.lines[array.header]) + self.unit);
+        let at = match array.items.get(index) {
+            Some(item) => item.lines.start,
+            None => parent.lines.end,
+        };
+
+        match (&array.layout, self.row(array, &new)?) {
+            (ArrayLayout::Inline, _) if new.is_primitive() => {
+                let ToonNode::Primitive(value) = &new else {
+                    unreachable!()
+                };
+                let text = self.scalar(value, array.delimiter, false)?;
+                let (offset, text) = match (array.items.get(index), array.items.last()) {
+                    (Some(item), _) => (item.span().start, format!("{}{}", text, array.delimiter)),
+                    (None, Some(last)) => (last.span().end, format!("{}{}", array.delimiter, text)),
+                    (None, None) => (array.colon_end, format!(" {}", text)),
+                };
+                edits.push(Edit::Splice {
+                    line: array.header,
+                    range: offset..offset,
+                    text,
+                });
+            }
+            (ArrayLayout::Tabular(_), Some(row)) => {
+                edits.push(Edit::Replace {
+                    lines: at..at,
+                    with: vec![format!("{}{}", " ".repeat(item_indent), row)],
+                });
+            }
+            (ArrayLayout::List, _) => edits.push(Edit::Replace {
+                lines: at..at,
+                with: self.render_item(item_indent, new, array.hyphen_fields)?,
+            }),
+            _ => return self.rebuild_parent_items(chain, |items| items.insert(index, new)),
+        }
+        Ok(edits)
+    }
+
+    fn remove_plan(&self, chain: &[&Node]) -> ConversionResult<Vec<Edit>> {
+        let node = chain[chain.len() - 1];
+        if chain.len() < 2 {
+            return Err(structure("Cannot remove the document root"));
+        }
+        let parent = chain[chain.len() - 2];
+
+        match (node.slot, &parent.kind) {
+            (Slot::Field { hyphen: false, .. }, _) => Ok(vec![Edit::Replace {
+                lines: node.lines.clone(),
+                with: Vec::new(),
+            }]),
+            (Slot::Row { .. } | Slot::ListItem { .. }, Kind::Array(array)) => Ok(vec![
+                self.count_edit(array, array.items.len() - 1),
+                Edit::Replace {
+                    lines: node.lines.clone(),
+                    with: Vec::new(),
+                },
+            ]),
+            (Slot::Cell, Kind::Array(array)) => {
+                let index = parent
+                    .children()
+                    .position(|child| std::ptr::eq(child, node))
+                    .expect("chain nodes are children of their parent");
+                let span = node.span();
+                let range = if let Some(next) = array.items.get(index + 1) {
+                    span.start..next.span().start
+                } else if index > 0 {
+                    array.items[index - 1].span().end..span.end
+                } else {
+                    array.colon_end..span.end
+                };
+                Ok(vec![
+                    self.count_edit(array, array.items.len() - 1),
+                    Edit::Splice {
+                        line: array.header,
+                        range,
+                        text: String::new(),
+                    },
+                ])
+            }
+            _ => self.rebuild_parent(chain, |children, index| {
+                children.remove(index);
+            }),
+        }
+    }
+
+    /// Re-render the array at the end of `chain` after changing its items
+    fn rebuild_parent_items(
+        &self,
+        chain: &[&Node],
+        change: impl FnOnce(&mut Vec<ToonNode>),
+    ) -> ConversionResult<Vec<Edit>> {
+        let ToonNode::Array(mut array) = self.node(chain[chain.len() - 1]) else {
+            unreachable!("only arrays have items");
+        };
+        change(&mut array.items);
+        self.replace_plan(chain, ToonNode::Array(array))
+    }
+
+    fn count_edit(&self, array: &ArrayNode, count: usize) -> Edit {
+        Edit::Splice {
+            line: array.header,
+            range: array.count.clone(),
+            text: count.to_string(),
+        }
+    }
+
+    /// Apply edits from the end of the document backwards and re-parse
+    fn apply(&mut self, mut edits: Vec<Edit>) -> ConversionResult<()> {
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.position()));
+        let mut lines = self.lines.clone();
+        for edit in edits {
+            match edit {
+                Edit::Splice { line, range, text } => lines[line].replace_range(range, &text),
+                Edit::Replace { lines: range, with } => {
+                    lines.splice(range, with);
+                }
+            }
+        }
+
+        *self = Self::from_lines(
+            lines,
+            self.line_ending,
+            self.trailing_newline,
+            self.delimiter,
+        )
+        .map_err(|e| structure(&format!("Edit produced invalid TOON: {}", e)))?;
+        Ok(())
+    }
+
+    /// Delimiter that applies to the cell at the end of `chain`
+    fn cell_delimiter(&self, chain: &[&Node]) -> char {
+        chain
+            .iter()
+            .rev()
+            .skip(1)
+            .take(2)
+            .find_map(|node| match &node.kind {
+                Kind::Array(array) => Some(array.delimiter),
+                _ => None,
+            })
+            .filter(|_| chain[chain.len() - 1].slot == Slot::Cell)
+            .unwrap_or(self.default_delimiter())
+    }
+
+    fn default_delimiter(&self) -> char {
+        self.delimiter.as_str().chars().next().unwrap_or(',')
+    }
+
+    /// Indentation for the first field of an object that has none yet
+    fn child_indent(&self, node: &Node) -> usize {
+        match node.slot {
+            Slot::Root => 0,
+            _ => indent_of(&self.lines[node.lines.start]) + self.unit,
+        }
+    }
+
+    /// Format a primitive for a context with the given delimiter
+    fn scalar(&self, value: &Value, delimiter: char, quoted: bool) -> ConversionResult<String> {
+        let mut config = ConversionConfig {
+            delimiter: delimiter_type(delimiter),
+            ..ConversionConfig::default()
+        };
+        if quoted && value.is_string() {
+            config.quote_strings = QuoteStrategy::Always;
+        }
+        Ok(ToonFormatter::new(config).format_primitive(value)?)
+    }
+
+    /// A tabular row for `node`, if it fits the array's fields
+    fn row(&self, array: &ArrayNode, node: &ToonNode) -> ConversionResult<Option<String>> {
+        let ArrayLayout::Tabular(fields) = &array.layout else {
+            return Ok(None);
+        };
+        let ToonNode::Object(object) = node else {
+            return Ok(None);
+        };
+        if !array.layout.fits(std::slice::from_ref(node)) {
+            return Ok(None);
+        }
+        let cells = fields
+            .iter()
+            .map(|field| match object.get(field) {
+                Some(ToonNode::Primitive(value)) => self.scalar(value, array.delimiter, false),
+                _ => unreachable!("fitting rows hold every field as a primitive"),
+            })
+            .collect::<ConversionResult<Vec<_>>>()?;
+        Ok(Some(cells.join(&array.delimiter.to_string())))
+    }
+
+    /// Render a node as document lines at depth 0
+    fn render(&self, root: ToonNode) -> ConversionResult<Vec<String>> {
+        let document = ToonDocument {
+            root,
+            indent_size: self.unit,
+            delimiter: self.delimiter,
+        };
+        Ok(document.to_toon()?.lines().map(str::to_string).collect())
+    }
+
+    /// Render a node as a `- item` at an indentation, in the style of the
+    /// list's other object items
+    fn render_item(
+        &self,
+        indent: usize,
+        item: ToonNode,
+        hyphen_fields: bool,
+    ) -> ConversionResult<Vec<String>> {
+        let list = ToonNode::Array(ToonArray {
+            items: vec![item],
+            layout: ArrayLayout::List,
+            delimiter: None,
+            length_marker: false,
+            hyphen_fields,
+        });
+        Ok(self
+            .render(list)?
+            .iter()
+            .skip(1)
+            .map(|text| format!("{}{}", " ".repeat(indent), &text[self.unit..]))
+            .collect())
+    }
+
+    /// Document model of a node
+    fn node(&self, node: &Node) -> ToonNode {
+        match &node.kind {
+            Kind::Scalar { line, span } => {
+                let token = &self.lines[*line][span.clone()];
+                ToonNode::Primitive(
+                    parse_primitive(token).unwrap_or_else(|_| Value::String(token.to_string())),
+                )
+            }
+            Kind::Object { fields, .. } => ToonNode::Object(ToonObject {
+                fields: fields
+                    .iter()
+                    .map(|(key, value)| plain_field(key, self.node(value)))
+                    .collect(),
+            }),
+            Kind::Array(array) => ToonNode::Array(ToonArray {
+                items: array.items.iter().map(|item| self.node(item)).collect(),
+                layout: array.layout.clone(),
+                delimiter: array
+                    .explicit_delimiter
+                    .then(|| delimiter_type(array.delimiter)),
+                length_marker: array.length_marker,
+                hyphen_fields: array.hyphen_fields,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ToonCst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lines.join(self.line_ending))?;
+        if self.trailing_newline {
+            f.write_str(self.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+impl Node {
+    fn child(&self, segment: &str) -> Option<&Node> {
+        match &self.kind {
+            Kind::Object { fields, .. } => fields
+                .iter()
+                .find(|(key, _)| key == segment)
+                .map(|(_, node)| node),
+            Kind::Array(array) => array.items.get(segment.parse::<usize>().ok()?),
+            Kind::Scalar { .. } => None,
+        }
+    }
+
+    fn children(&self) -> Box<dyn Iterator<Item = &Node> + '_> {
+        match &self.kind {
+            Kind::Object { fields, .. } => Box::new(fields.iter().map(|(_, node)| node)),
+            Kind::Array(array) => Box::new(array.items.iter()),
+            Kind::Scalar { .. } => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn array(&self) -> &ArrayNode {
+        match &self.kind {
+            Kind::Array(array) => array,
+            _ => unreachable!("rows belong to arrays"),
+        }
+    }
+
+    fn span(&self) -> Range<usize> {
+        match &self.kind {
+            Kind::Scalar { span, .. } => span.clone(),
+            _ => unreachable!("cells are scalars"),
+        }
+    }
+}
+
+/// Recursive-descent parser over raw lines
+///
+/// Nesting follows indentation: a line belongs to the block above it while
+/// it is indented deeper than the block's own line.
+struct Parser<'a> {
+    lines: &'a [String],
+    pos: usize,
+    /// Last line consumed
+    last: usize,
+    unit: usize,
+    delimiter: char,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> ParseResult<Node> {
+        let Some(first) = self.child_line(None) else {
+            return Ok(Node {
+                slot: Slot::Root,
+                lines: 0..0,
+                kind: Kind::Object {
+                    indent: None,
+                    fields: Vec::new(),
+                },
+            });
+        };
+        let start = indent_of(&self.lines[first]);
+        let text = self.text(first, start);
+
+        let root = if text.starts_with('[') {
+            self.take(first);
+            let (header, rest) = self.header(first, start)?;
+            self.array(first, start, start, header, rest, Slot::Root)?
+        } else if self.lines[first + 1..].iter().all(|l| l.trim().is_empty())
+            && split_key(text).is_none()
+        {
+            self.take(first);
+            self.scalar(first, start, Slot::Root)?
+        } else {
+            self.object(None, first, Slot::Root)?
+        };
+
+        if let Some(line) = self.child_line(None) {
+            return Err(self.error(line, "Unexpected content after the end of the document"));
+        }
+        Ok(root)
+    }
+
+    /// Next non-blank line, if it is indented deeper than `parent`
+    fn child_line(&mut self, parent: Option<usize>) -> Option<usize> {
+        while self.pos < self.lines.len() && self.lines[self.pos].trim().is_empty() {
+            self.pos += 1;
+        }
+        let line = self.lines.get(self.pos)?;
+        (!matches!(parent, Some(p) if indent_of(line) <= p)).then_some(self.pos)
+    }
+
+    fn take(&mut self, line: usize) {
+        self.pos = line + 1;
+        self.last = line;
+    }
+
+    /// Content of a line from a byte offset, without trailing whitespace
+    fn text(&self, line: usize, from: usize) -> &'a str {
+        let lines: &'a [String] = self.lines;
+        lines[line][from..].trim_end()
+    }
+
+    fn end(&self, line: usize) -> usize {
+        self.lines[line].trim_end().len()
+    }
+
+    fn error(&self, line: usize, message: &str) -> ParseError {
+        let indent = indent_of(&self.lines[line]);
+        ParseError::new(message.to_string(), Some((line + 1, indent + 1)))
+            .with_preview(self.lines[line][indent..].trim_end().to_string())
+    }
+
+    /// Fields of an object whose lines are deeper than `parent`
+    fn object(&mut self, parent: Option<usize>, start: usize, slot: Slot) -> ParseResult<Node> {
+        let mut fields = Vec::new();
+        let mut indent = None;
+        while let Some(line) = self.child_line(parent) {
+            self.take(line);
+            let column = indent_of(&self.lines[line]);
+            indent.get_or_insert(column);
+            fields.push(self.field(line, column, column, false)?);
+        }
+        let end = if fields.is_empty() {
+            start + 1
+        } else {
+            self.last + 1
+        };
+        Ok(Node {
+            slot,
+            lines: start..end,
+            kind: Kind::Object { indent, fields },
+        })
+    }
+
+    /// A field whose key starts at byte `start` of `line`; deeper lines than
+    /// `block` belong to it
+    fn field(
+        &mut self,
+        line: usize,
+        start: usize,
+        block: usize,
+        hyphen: bool,
+    ) -> ParseResult<(String, Node)> {
+        let text = self.text(line, start);
+        let (key, after) =
+            split_key(text).ok_or_else(|| self.error(line, "Expected 'key: value'"))?;
+        let key = key.map_err(|message| self.error(line, &message))?;
+        let prefix = start + text.len() - after.len();
+        let slot = Slot::Field {
+            line,
+            prefix,
+            hyphen,
+        };
+
+        let node = if after.starts_with('[') {
+            let (header, rest) = self.header(line, prefix)?;
+            self.array(line, prefix, block, header, rest, slot)?
+        } else if after[1..].trim().is_empty() {
+            self.object(Some(block), line, slot)?
+        } else {
+            let value_start = self.end(line) - after[1..].trim_start().len();
+            self.scalar(line, value_start, slot)?
+        };
+        Ok((key, node))
+    }
+
+    fn header(&self, line: usize, start: usize) -> ParseResult<(ArrayHeader, usize)> {
+        let text = self.text(line, start);
+        let (header, rest) = parse_header_with(text, self.delimiter)
+            .ok_or_else(|| self.error(line, "Invalid array header"))?;
+        Ok((header, self.end(line) - rest.len()))
+    }
+
+    /// An array whose header starts at byte `start` of `line`; rows and list
+    /// items are the following lines deeper than `block`
+    fn array(
+        &mut self,
+        line: usize,
+        start: usize,
+        block: usize,
+        header: ArrayHeader,
+        rest: usize,
+        slot: Slot,
+    ) -> ParseResult<Node> {
+        let count_start = start + 1 + usize::from(header.length_marker);
+        let digits = self.lines[line][count_start..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count();
+        let colon_end = self.lines[line][..rest].trim_end_matches(' ').len();
+        let mut item_indent = None;
+        let mut hyphen_fields = false;
+        let mut items = Vec::new();
+
+        let layout = if let Some(fields) = header.fields {
+            if rest != self.end(line) {
+                return Err(self.error(line, "Unexpected content after tabular array header"));
+            }
+            while let Some(row) = self.child_line(Some(block)) {
+                self.take(row);
+                let column = indent_of(&self.lines[row]);
+                item_indent.get_or_insert(column);
+                let spans = self.cells(row, column, header.delimiter)?;
+                if spans.len() != fields.len() {
+                    return Err(self.error(
+                        row,
+                        &format!(
+                            "Row has {} cells but the header declares {} fields",
+                            spans.len(),
+                            fields.len()
+                        ),
+                    ));
+                }
+                let cells = fields
+                    .iter()
+                    .cloned()
+                    .zip(spans)
+                    .map(|(field, span)| {
+                        let cell = self.cell(row, span)?;
+                        Ok((field, cell))
+                    })
+                    .collect::<ParseResult<Vec<_>>>()?;
+                items.push(Node {
+                    slot: Slot::Row { line: row },
+                    lines: row..row + 1,
+                    kind: Kind::Object {
+                        indent: None,
+                        fields: cells,
+                    },
+                });
+            }
+            ArrayLayout::Tabular(fields)
+        } else if rest != self.end(line) {
+            for span in self.cells(line, rest, header.delimiter)? {
+                items.push(self.cell(line, span)?);
+            }
+            ArrayLayout::Inline
+        } else {
+            while let Some(item) = self.child_line(Some(block)) {
+                self.take(item);
+                let indent = indent_of(&self.lines[item]);
+                item_indent.get_or_insert(indent);
+                let node = self.list_item(item)?;
+                hyphen_fields |=
+                    matches!(node.kind, Kind::Object { .. }) && self.text(item, indent) != "-";
+                items.push(node);
+            }
+            ArrayLayout::List
+        };
+
+        Ok(Node {
+            slot,
+            lines: line..self.last.max(line) + 1,
+            kind: Kind::Array(ArrayNode {
+                header: line,
+                count: count_start..count_start + digits,
+                colon_end,
+                delimiter: header.delimiter,
+                explicit_delimiter: header.explicit_delimiter,
+                length_marker: header.length_marker,
+                hyphen_fields,
+                layout,
+                item_indent,
+                items,
+            }),
+        })
+    }
+
+    fn list_item(&mut self, line: usize) -> ParseResult<Node> {
+        let indent = indent_of(&self.lines[line]);
+        let text = self.text(line, indent);
+        let slot = Slot::ListItem { line };
+
+        if text == "-" {
+            return self.object(Some(indent), line, slot);
+        }
+        let Some(content) = text.strip_prefix("- ") else {
+            return Err(self.error(line, "Expected a list item starting with '- '"));
+        };
+        let start = self.end(line) - content.trim_start().len();
+        let content = content.trim_start();
+
+        if content.starts_with('[') {
+            if let Ok((header, rest)) = self.header(line, start) {
+                return self.array(line, start, indent, header, rest, slot);
+            }
+        }
+
+        if split_key(content).is_some() {
+            // The first field shares the hyphen line; the rest align with it
+            let field_indent = indent + self.unit;
+            let mut fields = vec![self.field(line, start, field_indent, true)?];
+            while let Some(next) = self.child_line(Some(indent)) {
+                self.take(next);
+                let column = indent_of(&self.lines[next]);
+                fields.push(self.field(next, column, column, false)?);
+            }
+            return Ok(Node {
+                slot,
+                lines: line..self.last + 1,
+                kind: Kind::Object {
+                    indent: Some(field_indent),
+                    fields,
+                },
+            });
+        }
+
+        self.scalar(line, start, slot)
+    }
+
+    fn scalar(&self, line: usize, start: usize, slot: Slot) -> ParseResult<Node> {
+        let span = start..self.end(line);
+        parse_primitive(&self.lines[line][span.clone()])
+            .map_err(|message| self.error(line, &message))?;
+        Ok(Node {
+            slot,
+            lines: line..line + 1,
+            kind: Kind::Scalar { line, span },
+        })
+    }
+
+    fn cell(&self, line: usize, span: Range<usize>) -> ParseResult<Node> {
+        parse_primitive(&self.lines[line][span.clone()])
+            .map_err(|message| self.error(line, &message))?;
+        Ok(Node {
+            slot: Slot::Cell,
+            lines: line..line + 1,
+            kind: Kind::Scalar { line, span },
+        })
+    }
+
+    /// Spans of the delimited cells from byte `start` of a line, without
+    /// surrounding spaces
+    fn cells(&self, line: usize, start: usize, delimiter: char) -> ParseResult<Vec<Range<usize>>> {
+        let text = &self.lines[line][..self.end(line)];
+        let mut spans = Vec::new();
+        let mut cell_start = start;
+        let mut in_quotes = false;
+        let mut escaped = false;
+
+        for (offset, ch) in text[start..].char_indices() {
+            let offset = start + offset;
+            if in_quotes {
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == '"' {
+                    in_quotes = false;
+                }
+            } else if ch == '"' {
+                in_quotes = true;
+            } else if ch == delimiter {
+                spans.push(trim_span(text, cell_start..offset));
+                cell_start = offset + ch.len_utf8();
+            }
+        }
+
+        if in_quotes {
+            return Err(self.error(line, "Unterminated quoted string"));
+        }
+        spans.push(trim_span(text, cell_start..text.len()));
+        Ok(spans)
+    }
+}
+
+/// Narrow a span to exclude surrounding spaces
+fn trim_span(text: &str, span: Range<usize>) -> Range<usize> {
+    let slice = &text[span.clone()];
+    let start = span.start + (slice.len() - slice.trim_start_matches(' ').len());
+    let end = span.end - (slice.len() - slice.trim_end_matches(' ').len());
+    start..end.max(start)
+}
+
+/// Width of a line's leading spaces and tabs
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Mapping of a 0-based line, created with `pointer` if the line has none
+fn mapping<'m>(
+    lines: &'m mut BTreeMap<usize, LineMapping>,
+    line: usize,
+    pointer: &str,
+) -> &'m mut LineMapping {
+    lines.entry(line).or_insert_with(|| LineMapping {
+        line: line + 1,
+        pointer: pointer.to_string(),
+        cells: Vec::new(),
+    })
+}
+
+fn is_object(chain: &[&Node]) -> bool {
+    matches!(
+        chain.last().map(|node| &node.kind),
+        Some(Kind::Object { .. })
+    )
+}
+
+fn plain_field(key: &str, value: ToonNode) -> ToonField {
+    ToonField {
+        key: key.to_string(),
+        value,
+        folded: false,
+    }
+}
+
+fn single_field(key: &str, value: ToonNode) -> ToonNode {
+    ToonNode::Object(ToonObject {
+        fields: vec![plain_field(key, value)],
+    })
+}
+
+fn parse_pointer(pointer: &str) -> ConversionResult<Vec<String>> {
+    pointer_segments(pointer)
+        .ok_or_else(|| structure(&format!("Invalid JSON Pointer '{}'", pointer)))
+}
+
+fn structure(message: &str) -> ConversionError {
+    FormattingError::invalid_structure(message.to_string()).into()
+}
+
+fn missing(pointer: &str) -> ConversionError {
+    structure(&format!("No value at '{}'", pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edit(input: &str, change: impl FnOnce(&mut ToonCst)) -> String {
+        let mut cst = ToonCst::parse(input).unwrap();
+        change(&mut cst);
+        cst.to_string()
+    }
+
+    #[test]
+    fn test_round_trip_is_lossless() {
+        let input = "name:    \"Ann\"\r\n\r\nusers[#2|]{id|name}:\r\n    1 | Ann\r\n    2|\"Bo\"\r\nitems[2]:\r\n  - id: 1\r\n    tags[0]:\r\n  -   x\r\n";
+        let cst = ToonCst::parse(input).unwrap();
+        assert_eq!(cst.to_string(), input);
+        assert_eq!(
+            cst.to_value(),
+            json!({
+                "name": "Ann",
+                "users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}],
+                "items": [{"id": 1, "tags": []}, "x"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_scalar_touches_only_its_token() {
+        let input = "a:   1\nb: \"x\"\nrows[2|]{id|v}:\n  1 | a\n  2 | b\n";
+        let output = edit(input, |cst| {
+            cst.set("/a", &json!(2)).unwrap();
+            cst.set("/b", &json!("y")).unwrap();
+            cst.set("/rows/1/v", &json!("c|d")).unwrap();
+        });
+        assert_eq!(
+            output,
+            "a:   2\nb: \"y\"\nrows[2|]{id|v}:\n  1 | a\n  2 | \"c|d\"\n"
+        );
+    }
+
+    #[test]
+    fn test_set_complex_value_rerenders_field() {
+        let input = "keep:  1\nuser: x\nafter: 2\n";
+        let output = edit(input, |cst| {
+            cst.set("/user", &json!({"name": "Ann", "tags": ["a", "b"]}))
+                .unwrap();
+        });
+        assert_eq!(
+            output,
+            "keep:  1\nuser:\n  name: Ann\n  tags[2]: a,b\nafter: 2\n"
+        );
+    }
+
+    #[test]
+    fn test_set_missing_key_appends_field() {
+        let output = edit("server:\n    host: a\nother: 1\n", |cst| {
+            cst.set("/server/port", &json!(80)).unwrap();
+        });
+        assert_eq!(output, "server:\n    host: a\n    port: 80\nother: 1\n");
+    }
+
+    #[test]
+    fn test_insert_and_remove_array_items_update_count() {
+        let input = "tags[3]: a, b, c\nrows[#1]{id}:\n  1\nlist[1]:\n  - x\n";
+        let output = edit(input, |cst| {
+            cst.remove("/tags/1").unwrap();
+            cst.insert("/tags/0", &json!("z")).unwrap();
+            cst.insert("/rows/-", &json!({"id": 2})).unwrap();
+            cst.insert("/list/0", &json!({"k": 1, "v": 2})).unwrap();
+        });
+        assert_eq!(
+            output,
+            "tags[3]: z,a, c\nrows[#2]{id}:\n  1\n  2\nlist[2]:\n  -\n    k: 1\n    v: 2\n  - x\n"
+        );
+    }
+
+    #[test]
+    fn test_inserted_items_follow_list_style() {
+        let input = "list[1]:\n  - k: 1\n    v: 2\n";
+        let output = edit(input, |cst| {
+            cst.insert("/list/-", &json!({"k": 3, "v": 4})).unwrap();
+            cst.set("/list/0", &json!({"k": 5})).unwrap();
+        });
+        assert_eq!(output, "list[2]:\n  - k: 5\n  - k: 3\n    v: 4\n");
+    }
+
+    #[test]
+    fn test_remove_fields_rows_and_items() {
+        let input =
+            "a: 1\nb:\n  c: 2\nrows[2]{id}:\n  1\n  2\nlist[2]:\n  - k: 1\n    v: 2\n  - y\n";
+        let output = edit(input, |cst| {
+            cst.remove("/b").unwrap();
+            cst.remove("/rows/0").unwrap();
+            cst.remove("/list/0/k").unwrap();
+            cst.remove("/list/1").unwrap();
+        });
+        assert_eq!(output, "a: 1\nrows[1]{id}:\n  2\nlist[1]:\n  - v: 2\n");
+    }
+
+    #[test]
+    fn test_row_that_no_longer_fits_rerenders_array() {
+        let output = edit("rows[2|]{id|v}:\n  1|a\n  2|b\nz: 1\n", |cst| {
+            cst.set("/rows/0/v", &json!([1, 2])).unwrap();
+        });
+        assert_eq!(
+            output,
+            "rows[2|]:\n  -\n    id: 1\n    v[2]: 1,2\n  -\n    id: 2\n    v: b\nz: 1\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut cst = ToonCst::parse("a: 1\nlist[1]: x\n").unwrap();
+        assert!(cst.set("/missing/deep", &json!(1)).is_err());
+        assert!(cst.insert("/a", &json!(1)).is_err());
+        assert!(cst.insert("/list/5", &json!(1)).is_err());
+        assert!(cst.remove("").is_err());
+        assert!(ToonCst::parse("rows[1]{a,b}:\n  1\n").is_err());
+    }
+}
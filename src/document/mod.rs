@@ -0,0 +1,455 @@
+//! TOON document model
+//!
+//! [`ToonDocument`] is an owned tree of TOON values that keeps the layout
+//! choices `serde_json::Value` cannot express: whether an array is written
+//! inline, as a table or as a list, the delimiter declared in its header, its
+//! `#` length marker, whether object list items start on the hyphen line, and
+//! keys folded into dotted paths (`a.b.c: 1`).
+//!
+//! The decoder produces documents ([`ToonDecoder::decode_document`]) and the
+//! encoder renders every value through one ([`ToonFormatter::format`] builds
+//! it with [`ToonDocument::from_value`]), so a document can be edited
+//! programmatically and written back with its original layout. Arrays whose
+//! recorded layout no longer fits their items after an edit (for example a
+//! table row gaining a nested field) fall back to the layout the encoder
+//! would choose.
+//!
+//! [`ToonDecoder::decode_document`]: crate::decoder::ToonDecoder::decode_document
+//! [`ToonFormatter::format`]: crate::formatter::ToonFormatter::format
+
+pub mod cst;
+pub mod source_map;
+
+use crate::conversion::{ConversionConfig, ConversionResult, DelimiterType};
+use crate::formatter::ToonFormatter;
+use serde_json::{Map, Value};
+
+/// A TOON document: a root node and its document-wide layout settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToonDocument {
+    /// Root value
+    pub root: ToonNode,
+    /// Spaces per indentation level
+    pub indent_size: usize,
+    /// Delimiter for arrays whose header does not declare one
+    pub delimiter: DelimiterType,
+}
+
+/// A value in a TOON document
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToonNode {
+    /// Null, boolean, number or string
+    Primitive(Value),
+    /// Object with ordered fields
+    Object(ToonObject),
+    /// Array with its layout
+    Array(ToonArray),
+}
+
+/// Object with fields in document order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ToonObject {
+    pub fields: Vec<ToonField>,
+}
+
+/// One object field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToonField {
+    /// Key as written; a dotted path when `folded`
+    pub key: String,
+    pub value: ToonNode,
+    /// The key is a folded path standing for nested single-key objects
+    pub folded: bool,
+}
+
+/// Array with its layout choices
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToonArray {
+    pub items: Vec<ToonNode>,
+    pub layout: ArrayLayout,
+    /// Delimiter declared in the header (`[N|]`, `[N\t]`); `None` uses the
+    /// document delimiter
+    pub delimiter: Option<DelimiterType>,
+    /// Header uses the `[#N]` length marker
+    pub length_marker: bool,
+    /// Object items put their first field on the hyphen line (`- id: 1`)
+    /// instead of under a bare `-`
+    pub hyphen_fields: bool,
+}
+
+/// How an array is written
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayLayout {
+    /// `key[N]: a,b,c`
+    Inline,
+    /// `key[N]{f1,f2}:` followed by one row per item
+    Tabular(Vec<String>),
+    /// `key[N]:` followed by `- item` lines
+    List,
+}
+
+impl ToonDocument {
+    /// Create a document around a root node with default settings
+    pub fn new(root: ToonNode) -> Self {
+        Self {
+            root,
+            indent_size: 2,
+            delimiter: DelimiterType::Comma,
+        }
+    }
+
+    /// Build a document from JSON, choosing layouts as the encoder does
+    pub fn from_value(value: &Value) -> Self {
+        Self::new(ToonNode::from_value(value))
+    }
+
+    /// Convert to JSON, expanding folded keys
+    pub fn to_value(&self) -> Value {
+        self.root.to_value()
+    }
+
+    /// Look up a node by JSON Pointer
+    pub fn pointer(&self, pointer: &str) -> Option<&ToonNode> {
+        self.root.pointer(pointer)
+    }
+
+    /// Look up a node by JSON Pointer for editing
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut ToonNode> {
+        self.root.pointer_mut(pointer)
+    }
+
+    /// Write the document as TOON with its own indentation and delimiter
+    pub fn to_toon(&self) -> ConversionResult<String> {
+        let config = ConversionConfig {
+            indent_size: self.indent_size as u8,
+            delimiter: self.delimiter,
+            ..ConversionConfig::default()
+        };
+        ToonFormatter::new(config).format_document(self)
+    }
+}
+
+impl ToonNode {
+    /// Build a node from JSON, choosing layouts as the encoder does: uniform
+    /// objects of primitives become tables, primitives inline, anything else
+    /// a list
+    pub fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Object(map) => ToonNode::Object(ToonObject {
+                fields: map
+                    .iter()
+                    .map(|(key, value)| ToonField {
+                        key: key.clone(),
+                        value: ToonNode::from_value(value),
+                        folded: false,
+                    })
+                    .collect(),
+            }),
+            Value::Array(items) => {
+                let items: Vec<_> = items.iter().map(ToonNode::from_value).collect();
+                let layout = ArrayLayout::preferred(&items);
+                ToonNode::Array(ToonArray {
+                    items,
+                    layout,
+                    delimiter: None,
+                    length_marker: false,
+                    hyphen_fields: false,
+                })
+            }
+            primitive => ToonNode::Primitive(primitive.clone()),
+        }
+    }
+
+    /// Convert to JSON, expanding folded keys
+    pub fn to_value(&self) -> Value {
+        match self {
+            ToonNode::Primitive(value) => value.clone(),
+            ToonNode::Array(array) => {
+                Value::Array(array.items.iter().map(ToonNode::to_value).collect())
+            }
+            ToonNode::Object(object) => {
+                let mut map = Map::new();
+                for field in &object.fields {
+                    let value = field.value.to_value();
+                    if field.folded {
+                        insert_path(&mut map, &field.key.split('.').collect::<Vec<_>>(), value);
+                    } else {
+                        map.insert(field.key.clone(), value);
+                    }
+                }
+                Value::Object(map)
+            }
+        }
+    }
+
+    /// Check if the node is a primitive
+    pub fn is_primitive(&self) -> bool {
+        matches!(self, ToonNode::Primitive(_))
+    }
+
+    /// Look up a node by JSON Pointer
+    ///
+    /// Folded keys are matched segment by segment; a pointer that stops inside
+    /// a folded path finds nothing.
+    pub fn pointer(&self, pointer: &str) -> Option<&ToonNode> {
+        let segments = pointer_segments(pointer)?;
+        let mut node = self;
+        let mut rest = &segments[..];
+        while !rest.is_empty() {
+            let (next, consumed) = node.child(rest)?;
+            node = next;
+            rest = &rest[consumed..];
+        }
+        Some(node)
+    }
+
+    /// Look up a node by JSON Pointer for editing
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut ToonNode> {
+        let segments = pointer_segments(pointer)?;
+        let mut node = self;
+        let mut rest = &segments[..];
+        while !rest.is_empty() {
+            let (next, consumed) = node.child_mut(rest)?;
+            node = next;
+            rest = &rest[consumed..];
+        }
+        Some(node)
+    }
+
+    /// Child matching the leading segments, and how many segments it used
+    fn child(&self, segments: &[String]) -> Option<(&ToonNode, usize)> {
+        match self {
+            ToonNode::Object(object) => object
+                .fields
+                .iter()
+                .find_map(|field| field.matches(segments).map(|n| (&field.value, n))),
+            ToonNode::Array(array) => segments[0]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.items.get(index))
+                .map(|item| (item, 1)),
+            ToonNode::Primitive(_) => None,
+        }
+    }
+
+    fn child_mut(&mut self, segments: &[String]) -> Option<(&mut ToonNode, usize)> {
+        match self {
+            ToonNode::Object(object) => object
+                .fields
+                .iter_mut()
+                .find_map(|field| field.matches(segments).map(|n| (&mut field.value, n))),
+            ToonNode::Array(array) => segments[0]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.items.get_mut(index))
+                .map(|item| (item, 1)),
+            ToonNode::Primitive(_) => None,
+        }
+    }
+}
+
+impl ToonObject {
+    /// Value of a (non-folded) key
+    pub fn get(&self, key: &str) -> Option<&ToonNode> {
+        self.fields
+            .iter()
+            .find(|field| field.key == key)
+            .map(|field| &field.value)
+    }
+
+    /// Value of a (non-folded) key for editing
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ToonNode> {
+        self.fields
+            .iter_mut()
+            .find(|field| field.key == key)
+            .map(|field| &mut field.value)
+    }
+
+    /// Set a key, keeping its position if it exists or appending it otherwise
+    pub fn insert(&mut self, key: &str, value: ToonNode) -> Option<ToonNode> {
+        match self.get_mut(key) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.fields.push(ToonField {
+                    key: key.to_string(),
+                    value,
+                    folded: false,
+                });
+                None
+            }
+        }
+    }
+
+    /// Remove a key, returning its value
+    pub fn remove(&mut self, key: &str) -> Option<ToonNode> {
+        let index = self.fields.iter().position(|field| field.key == key)?;
+        Some(self.fields.remove(index).value)
+    }
+}
+
+impl ToonField {
+    /// Number of pointer segments this field's key covers, if it matches
+    fn matches(&self, segments: &[String]) -> Option<usize> {
+        if !self.folded {
+            return (self.key == segments[0]).then_some(1);
+        }
+        let parts: Vec<&str> = self.key.split('.').collect();
+        (parts.len() <= segments.len() && parts.iter().zip(segments).all(|(a, b)| a == b))
+            .then_some(parts.len())
+    }
+}
+
+impl ToonArray {
+    /// Layout to write the array with: the recorded one while it still fits
+    /// the items, otherwise the encoder's choice
+    pub fn effective_layout(&self) -> ArrayLayout {
+        if self.layout.fits(&self.items) {
+            self.layout.clone()
+        } else {
+            ArrayLayout::preferred(&self.items)
+        }
+    }
+}
+
+impl ArrayLayout {
+    /// Layout the encoder chooses for items
+    pub fn preferred(items: &[ToonNode]) -> Self {
+        if items.iter().all(ToonNode::is_primitive) {
+            return ArrayLayout::Inline;
+        }
+        if let Some(ToonNode::Object(first)) = items.first() {
+            let fields: Vec<String> = first.fields.iter().map(|f| f.key.clone()).collect();
+            let tabular = ArrayLayout::Tabular(fields);
+            if !first.fields.is_empty() && tabular.fits(items) {
+                return tabular;
+            }
+        }
+        ArrayLayout::List
+    }
+
+    /// Check if the layout can represent the items
+    pub fn fits(&self, items: &[ToonNode]) -> bool {
+        match self {
+            ArrayLayout::Inline => items.iter().all(ToonNode::is_primitive),
+            ArrayLayout::List => true,
+            ArrayLayout::Tabular(fields) => items.iter().all(|item| match item {
+                ToonNode::Object(object) => {
+                    object.fields.len() == fields.len()
+                        && object.fields.iter().all(|field| {
+                            !field.folded
+                                && field.value.is_primitive()
+                                && fields.contains(&field.key)
+                        })
+                }
+                _ => false,
+            }),
+        }
+    }
+}
+
+/// Insert a value at a dotted path, merging with objects already there
+fn insert_path(map: &mut Map<String, Value>, path: &[&str], value: Value) {
+    let (first, rest) = path.split_first().expect("folded keys have segments");
+    if rest.is_empty() {
+        map.insert(first.to_string(), value);
+        return;
+    }
+    let entry = map
+        .entry(first.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !entry.is_object() {
+        *entry = Value::Object(Map::new());
+    }
+    if let Value::Object(inner) = entry {
+        insert_path(inner, rest, value);
+    }
+}
+
+/// Split a JSON Pointer into unescaped segments
+fn pointer_segments(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_value_chooses_encoder_layouts() {
+        let document = ToonDocument::from_value(&json!({
+            "tags": ["a", "b"],
+            "users": [{"id": 1}, {"id": 2}],
+            "mixed": [1, {"a": [1]}]
+        }));
+        let layout = |pointer: &str| match document.pointer(pointer) {
+            Some(ToonNode::Array(array)) => array.effective_layout(),
+            other => panic!("not an array: {:?}", other),
+        };
+        assert_eq!(layout("/tags"), ArrayLayout::Inline);
+        assert_eq!(layout("/users"), ArrayLayout::Tabular(vec!["id".into()]));
+        assert_eq!(layout("/mixed"), ArrayLayout::List);
+    }
+
+    #[test]
+    fn test_folded_keys_expand() {
+        let document = ToonDocument::new(ToonNode::Object(ToonObject {
+            fields: vec![
+                ToonField {
+                    key: "server.host".into(),
+                    value: ToonNode::Primitive(json!("localhost")),
+                    folded: true,
+                },
+                ToonField {
+                    key: "server.port".into(),
+                    value: ToonNode::Primitive(json!(80)),
+                    folded: true,
+                },
+            ],
+        }));
+        assert_eq!(
+            document.to_value(),
+            json!({"server": {"host": "localhost", "port": 80}})
+        );
+        assert_eq!(
+            document.pointer("/server/port"),
+            Some(&ToonNode::Primitive(json!(80)))
+        );
+        assert_eq!(document.pointer("/server"), None);
+    }
+
+    #[test]
+    fn test_edits_through_pointer() {
+        let mut document = ToonDocument::from_value(&json!({"a": {"b": 1}, "list": [1, 2]}));
+        *document.pointer_mut("/list/1").unwrap() = ToonNode::Primitive(json!(3));
+        if let Some(ToonNode::Object(object)) = document.pointer_mut("/a") {
+            object.insert("c", ToonNode::Primitive(json!(true)));
+            object.remove("b");
+        }
+        assert_eq!(
+            document.to_value(),
+            json!({"a": {"c": true}, "list": [1, 3]})
+        );
+    }
+
+    #[test]
+    fn test_tabular_layout_falls_back_when_items_change() {
+        let mut array = match ToonNode::from_value(&json!([{"id": 1}, {"id": 2}])) {
+            ToonNode::Array(array) => array,
+            _ => unreachable!(),
+        };
+        assert!(matches!(array.effective_layout(), ArrayLayout::Tabular(_)));
+        array
+            .items
+            .push(ToonNode::from_value(&json!({"id": 3, "tags": [1]})));
+        assert_eq!(array.effective_layout(), ArrayLayout::List);
+    }
+}
@@ -0,0 +1,143 @@
+//! Source maps between TOON output and JSON input
+//!
+//! A [`SourceMap`] links each line of encoded TOON, and each primitive value
+//! (cell) on it, to the JSON Pointer of the input value it was written from.
+//! It answers "which value is on line 42" when a model cites a line of TOON
+//! context, and "which line holds `/users/3/email`" when validation fails.
+//!
+//! Lines and columns are 1-based; columns count characters and cell ranges
+//! end exclusively. A line's pointer names the outermost value that starts on
+//! it (a field, list item or table row); blank lines are not mapped.
+
+use serde::{Deserialize, Serialize};
+
+/// Current source map format version
+pub const SOURCE_MAP_VERSION: u32 = 1;
+
+/// Mapping from TOON lines and cells to JSON Pointers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub version: u32,
+    pub lines: Vec<LineMapping>,
+}
+
+/// JSON Pointer of one TOON line and its cells
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineMapping {
+    pub line: usize,
+    pub pointer: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<CellMapping>,
+}
+
+/// JSON Pointer of one primitive value on a line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellMapping {
+    /// First column of the value
+    pub start: usize,
+    /// Column after the value
+    pub end: usize,
+    pub pointer: String,
+}
+
+impl SourceMap {
+    /// Create an empty source map
+    pub fn new() -> Self {
+        Self {
+            version: SOURCE_MAP_VERSION,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Pointer of the value at a line, narrowed to the cell covering
+    /// `column` when one is given
+    pub fn pointer_at(&self, line: usize, column: Option<usize>) -> Option<&str> {
+        let mapping = self.lines.iter().find(|mapping| mapping.line == line)?;
+        let cell = column.and_then(|column| {
+            mapping
+                .cells
+                .iter()
+                .find(|cell| cell.start <= column && column < cell.end)
+        });
+        Some(cell.map_or(mapping.pointer.as_str(), |cell| cell.pointer.as_str()))
+    }
+
+    /// Line a JSON Pointer was written to, falling back to the nearest
+    /// enclosing value
+    pub fn line_of(&self, pointer: &str) -> Option<usize> {
+        let mut pointer = pointer;
+        loop {
+            let found = self.lines.iter().find(|mapping| {
+                mapping.pointer == pointer || mapping.cells.iter().any(|c| c.pointer == pointer)
+            });
+            if let Some(mapping) = found {
+                return Some(mapping.line);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("source maps serialize to JSON")
+    }
+}
+
+impl Default for SourceMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::cst::ToonCst;
+
+    fn map(input: &str) -> SourceMap {
+        ToonCst::parse(input).unwrap().source_map()
+    }
+
+    #[test]
+    fn test_lines_and_cells_map_to_pointers() {
+        let source_map = map("name: Ann\n\nusers[2]{id,name}:\n  1,Ann\n  2,Bo\ntags[2]: a,b\n");
+
+        assert_eq!(source_map.pointer_at(1, None), Some("/name"));
+        assert_eq!(source_map.pointer_at(2, None), None);
+        assert_eq!(source_map.pointer_at(3, None), Some("/users"));
+        assert_eq!(source_map.pointer_at(5, None), Some("/users/1"));
+        assert_eq!(source_map.pointer_at(5, Some(5)), Some("/users/1/name"));
+        assert_eq!(source_map.pointer_at(6, Some(10)), Some("/tags/0"));
+        assert_eq!(source_map.pointer_at(6, Some(12)), Some("/tags/1"));
+        assert_eq!(source_map.line_of("/users/1/name"), Some(5));
+        assert_eq!(source_map.line_of("/tags/9"), Some(6));
+    }
+
+    #[test]
+    fn test_list_items_and_nested_objects() {
+        let source_map = map("items[2]:\n  - id: 1\n    meta:\n      ok: true\n  - x\n");
+
+        assert_eq!(source_map.pointer_at(2, None), Some("/items/0"));
+        assert_eq!(source_map.pointer_at(2, Some(9)), Some("/items/0/id"));
+        assert_eq!(source_map.pointer_at(3, None), Some("/items/0/meta"));
+        assert_eq!(source_map.pointer_at(4, None), Some("/items/0/meta/ok"));
+        assert_eq!(source_map.pointer_at(5, None), Some("/items/1"));
+    }
+
+    #[test]
+    fn test_serializes_cells_as_columns() {
+        let source_map = map("a: \"é\"\n");
+        let json: serde_json::Value = serde_json::from_str(&source_map.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "lines": [{
+                    "line": 1,
+                    "pointer": "/a",
+                    "cells": [{"start": 4, "end": 7, "pointer": "/a"}]
+                }]
+            })
+        );
+    }
+}
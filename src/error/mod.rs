@@ -1,8 +1,9 @@
 //! Error types and handling infrastructure for JSON to TOON conversion
 
 use anyhow::Error;
+use serde::Serialize;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Core error types for the conversion process
 #[derive(Debug, thiserror::Error)]
@@ -42,6 +43,9 @@ pub enum ConversionErrorKind {
 
     #[error("Conversion failed: {message}")]
     ConversionFailed { message: String },
+
+    #[error("Savings threshold not met: {message}")]
+    ThresholdNotMet { message: String },
 }
 
 impl ConversionErrorKind {
@@ -60,6 +64,27 @@ impl ConversionErrorKind {
     pub fn configuration(message: String) -> Self {
         Self::Configuration { message }
     }
+
+    pub fn threshold_not_met(message: String) -> Self {
+        Self::ThresholdNotMet { message }
+    }
+
+    /// Stable snake_case name used in machine-readable reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::JsonParse { .. } => "json_parse",
+            Self::Formatting { .. } => "formatting",
+            Self::Io { .. } => "io",
+            Self::JsonTooLarge { .. } => "json_too_large",
+            Self::MemoryLimitExceeded { .. } => "memory_limit_exceeded",
+            Self::TimeoutExceeded { .. } => "timeout_exceeded",
+            Self::Configuration { .. } => "configuration",
+            Self::CircularReference => "circular_reference",
+            Self::UnsupportedEncoding { .. } => "unsupported_encoding",
+            Self::ConversionFailed { .. } => "conversion_failed",
+            Self::ThresholdNotMet { .. } => "threshold_not_met",
+        }
+    }
 }
 
 /// Main error type for conversion operations
@@ -105,6 +130,50 @@ impl ConversionError {
         Self::Other(error)
     }
 
+    /// Stable snake_case name of the error kind
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::ParseError(_) => "json_parse",
+            Self::FormattingError(_) => "formatting",
+            Self::Conversion { kind, .. } => kind.name(),
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// Check if a `--min-reduction`/`--max-output-tokens` threshold was missed
+    pub fn is_threshold_not_met(&self) -> bool {
+        matches!(
+            self,
+            Self::Conversion {
+                kind: ConversionErrorKind::ThresholdNotMet { .. },
+                ..
+            }
+        )
+    }
+
+    /// Line and column the error was found at, if known
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::ParseError(err) => err.location,
+            Self::Conversion {
+                kind: ConversionErrorKind::JsonParse { location, .. },
+                ..
+            } => *location,
+            _ => None,
+        }
+    }
+
+    /// File the error relates to, if known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Conversion {
+                kind: ConversionErrorKind::Io { path, .. },
+                ..
+            } => path.as_deref(),
+            _ => None,
+        }
+    }
+
     /// Create a user-friendly error message
     pub fn user_message(&self) -> String {
         match self {
@@ -152,6 +221,74 @@ impl ConversionError {
     }
 }
 
+/// Serializable form of an error for machine-readable reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub kind: &'static str,
+    pub message: String,
+    pub location: Option<ErrorLocation>,
+    pub path: Option<PathBuf>,
+}
+
+/// 1-based position of an error in its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ErrorLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ErrorReport {
+    /// Describe an error, looking through `anyhow` for the toonconv error
+    /// types that carry a kind, location or path
+    pub fn from_error(error: &Error) -> Self {
+        if let Some(err) = error.downcast_ref::<ConversionError>() {
+            return Self::from(err);
+        }
+        if let Some(err) = error.downcast_ref::<ParseError>() {
+            return Self::new("json_parse", error.to_string(), err.location, None);
+        }
+        if let Some(err) = error.downcast_ref::<serde_json::Error>() {
+            return Self::new(
+                "json_parse",
+                error.to_string(),
+                Some((err.line(), err.column())),
+                None,
+            );
+        }
+        let kind = if error.downcast_ref::<std::io::Error>().is_some() {
+            "io"
+        } else {
+            "other"
+        };
+        Self::new(kind, error.to_string(), None, None)
+    }
+
+    fn new(
+        kind: &'static str,
+        message: String,
+        location: Option<(usize, usize)>,
+        path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            kind,
+            message,
+            location: location.map(|(line, column)| ErrorLocation { line, column }),
+            path,
+        }
+    }
+}
+
+impl From<&ConversionError> for ErrorReport {
+    fn from(error: &ConversionError) -> Self {
+        Self::new(
+            error.kind_name(),
+            error.to_string(),
+            error.location(),
+            error.path().map(Path::to_path_buf),
+        )
+    }
+}
+
 /// JSON parsing errors
 #[derive(Debug, Clone)]
 pub struct ParseError {
@@ -299,4 +436,24 @@ mod tests {
             assert!(!error.user_message().is_empty());
         }
     }
+
+    #[test]
+    fn test_error_report_carries_kind_location_and_path() {
+        let parse = ConversionError::parse("Unexpected token".to_string(), Some((3, 7)));
+        let report = ErrorReport::from_error(&anyhow::Error::new(parse));
+        assert_eq!(report.kind, "json_parse");
+        assert_eq!(report.location, Some(ErrorLocation { line: 3, column: 7 }));
+
+        let io = ConversionError::conversion(ConversionErrorKind::io(
+            "Cannot read".to_string(),
+            Some(PathBuf::from("in.json")),
+        ));
+        let report = ErrorReport::from(&io);
+        assert_eq!(report.kind, "io");
+        assert_eq!(report.path.as_deref(), Some(Path::new("in.json")));
+
+        let report = ErrorReport::from_error(&anyhow::anyhow!("something else"));
+        assert_eq!(report.kind, "other");
+        assert_eq!(report.location, None);
+    }
 }
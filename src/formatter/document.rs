@@ -0,0 +1,328 @@
+//! Rendering of [`ToonDocument`]s
+//!
+//! This is the only writer of TOON structure. [`ToonFormatter::format`]
+//! renders JSON through a document built with the encoder's layouts, and
+//! decoded documents are written back with the layout they record: arrays
+//! keep their inline, tabular or list form, headers keep their declared
+//! delimiter and `#` length marker, object list items keep their first field
+//! on the hyphen line or under a bare `-`, and folded keys stay folded.
+//! Indentation and the default delimiter come from the document; quoting
+//! follows the formatter's configuration.
+
+use super::ToonFormatter;
+use crate::conversion::{ConversionConfig, DelimiterType};
+use crate::decoder::is_folded_path;
+use crate::document::{ArrayLayout, ToonArray, ToonDocument, ToonField, ToonNode, ToonObject};
+use crate::error::FormattingResult;
+use serde_json::Value;
+
+/// Line-based writer for a document
+pub(super) struct DocumentWriter {
+    config: ConversionConfig,
+    lines: Vec<String>,
+}
+
+impl DocumentWriter {
+    pub(super) fn new(config: &ConversionConfig, document: &ToonDocument) -> Self {
+        Self {
+            config: ConversionConfig {
+                indent_size: document.indent_size.min(u8::MAX as usize) as u8,
+                delimiter: document.delimiter,
+                ..config.clone()
+            },
+            lines: Vec::new(),
+        }
+    }
+
+    /// Render the document
+    pub(super) fn write(mut self, document: &ToonDocument) -> FormattingResult<String> {
+        match &document.root {
+            ToonNode::Object(object) => self.object(object, 0)?,
+            ToonNode::Array(array) => self.array("", array, 0)?,
+            ToonNode::Primitive(value) => {
+                let text = self.primitive(value, self.config.delimiter)?;
+                self.lines.push(text);
+            }
+        }
+        Ok(self.lines.join("\n"))
+    }
+
+    fn object(&mut self, object: &ToonObject, depth: usize) -> FormattingResult<()> {
+        for field in &object.fields {
+            self.field(field, depth)?;
+        }
+        Ok(())
+    }
+
+    fn field(&mut self, field: &ToonField, depth: usize) -> FormattingResult<()> {
+        let key = self.key(field)?;
+        match &field.value {
+            ToonNode::Primitive(value) => {
+                let text = self.primitive(value, self.config.delimiter)?;
+                self.line(depth, format!("{}: {}", key, text));
+            }
+            ToonNode::Object(object) => {
+                self.line(depth, format!("{}:", key));
+                self.object(object, depth + 1)?;
+            }
+            ToonNode::Array(array) => self.array(&key, array, depth)?,
+        }
+        Ok(())
+    }
+
+    /// Write an array: its header line at `depth` and its body one level
+    /// deeper
+    fn array(&mut self, key: &str, array: &ToonArray, depth: usize) -> FormattingResult<()> {
+        let delimiter = array.delimiter.unwrap_or(self.config.delimiter);
+        let layout = array.effective_layout();
+        let formatter = self.formatter(delimiter);
+        let mut header = format!(
+            "{}[{}{}{}]",
+            key,
+            if array.length_marker { "#" } else { "" },
+            array.items.len(),
+            match array.delimiter {
+                Some(declared) => declared.as_str(),
+                None => "",
+            }
+        );
+
+        match layout {
+            ArrayLayout::Inline => {
+                let cells = array
+                    .items
+                    .iter()
+                    .map(|item| match item {
+                        ToonNode::Primitive(value) => formatter.format_primitive(value),
+                        _ => unreachable!("inline layout only holds primitives"),
+                    })
+                    .collect::<FormattingResult<Vec<_>>>()?;
+                header.push(':');
+                if !cells.is_empty() {
+                    header.push(' ');
+                    header.push_str(&cells.join(delimiter.as_str()));
+                }
+                self.line(depth, header);
+            }
+            ArrayLayout::Tabular(fields) => {
+                // Field names are separated by the delimiter the header
+                // declares, and by commas when it declares none
+                let names = fields
+                    .iter()
+                    .map(|field| formatter.format_key(field))
+                    .collect::<FormattingResult<Vec<_>>>()?;
+                let separator = array.delimiter.map_or(",", |declared| declared.as_str());
+                header.push_str(&format!("{{{}}}:", names.join(separator)));
+                self.line(depth, header);
+
+                for item in &array.items {
+                    let ToonNode::Object(row) = item else {
+                        unreachable!("tabular layout only holds objects");
+                    };
+                    let cells = fields
+                        .iter()
+                        .map(|field| match row.get(field) {
+                            Some(ToonNode::Primitive(value)) => formatter.format_primitive(value),
+                            _ => unreachable!("tabular rows hold every field as a primitive"),
+                        })
+                        .collect::<FormattingResult<Vec<_>>>()?;
+                    self.line(depth + 1, cells.join(delimiter.as_str()));
+                }
+            }
+            ArrayLayout::List => {
+                header.push(':');
+                self.line(depth, header);
+                for item in &array.items {
+                    self.list_item(item, depth + 1, array.hyphen_fields)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a `- item` at `depth`
+    ///
+    /// A nested array's header shares the hyphen line with its body one level
+    /// deeper. An object's fields sit one level deeper, under a bare `-`, or
+    /// with `hyphen_fields` the first of them shares the hyphen line.
+    fn list_item(
+        &mut self,
+        item: &ToonNode,
+        depth: usize,
+        hyphen_fields: bool,
+    ) -> FormattingResult<()> {
+        let start = self.lines.len();
+        let first_depth = match item {
+            ToonNode::Primitive(value) => {
+                let text = self.primitive(value, self.config.delimiter)?;
+                self.line(depth, text);
+                depth
+            }
+            ToonNode::Array(array) => {
+                self.array("", array, depth)?;
+                depth
+            }
+            ToonNode::Object(object) if object.fields.is_empty() || !hyphen_fields => {
+                self.line(depth, "-".to_string());
+                return self.object(object, depth + 1);
+            }
+            ToonNode::Object(object) => {
+                self.object(object, depth + 1)?;
+                depth + 1
+            }
+        };
+
+        let unit = self.config.indent_size as usize;
+        let first = &mut self.lines[start];
+        *first = format!(
+            "{}- {}",
+            " ".repeat(depth * unit),
+            &first[first_depth * unit..]
+        );
+        Ok(())
+    }
+
+    /// Format a key; keys that are not folded but look like a folded path
+    /// are quoted so they read back as a single key
+    fn key(&self, field: &ToonField) -> FormattingResult<String> {
+        let formatter = self.formatter(self.config.delimiter);
+        if !field.folded && is_folded_path(&field.key) {
+            return formatter.quote_string(&field.key);
+        }
+        formatter.format_key(&field.key)
+    }
+
+    fn primitive(&self, value: &Value, delimiter: DelimiterType) -> FormattingResult<String> {
+        self.formatter(delimiter).format_primitive(value)
+    }
+
+    fn formatter(&self, delimiter: DelimiterType) -> ToonFormatter {
+        ToonFormatter::new(ConversionConfig {
+            delimiter,
+            ..self.config.clone()
+        })
+    }
+
+    fn line(&mut self, depth: usize, text: String) {
+        let indent = " ".repeat(depth * self.config.indent_size as usize);
+        self.lines.push(format!("{}{}", indent, text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use crate::decoder::{decode_toon, DecodeOptions, ToonDecoder};
+    use crate::document::{ToonDocument, ToonNode};
+    use serde_json::json;
+
+    fn round_trip(input: &str, options: &DecodeOptions) -> String {
+        let decoded = decode_toon(input, options).unwrap();
+        decoded.document.to_toon().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_keeps_layout() {
+        let input = "\
+users[#2|]{id|name}:
+  1|Ann
+  2|Bo
+tags[3]: a,b,c
+items[2]:
+  - id: 1
+    notes[1]: x
+  - 7
+empty[0]:
+nested:
+  deep: true";
+        assert_eq!(round_trip(input, &DecodeOptions::strict()), input);
+    }
+
+    #[test]
+    fn test_encoder_output_round_trips_through_document() {
+        let values = [
+            json!([{"a": 1, "b": [1, 2]}, {"a": 2}]),
+            json!({"items": [{"id": 1, "meta": {"k": "v"}}, "x", [1, 2]]}),
+            json!({"orders": [{"id": 1, "lines": [{"sku": "A", "tags": ["x"]}, {"sku": "B"}]}]}),
+            json!({"rows": [{"id": 1, "sub": [{"n": 1}, {"n": 2}]}], "empty": [{}], "e": {}}),
+            json!({"a.b": [{"c.d": 1, "x": [{"y": null}]}]}),
+        ];
+        for indent_size in [2, 4] {
+            let config = ConversionConfig {
+                indent_size,
+                ..ConversionConfig::default()
+            };
+            let options = DecodeOptions::strict().with_indent_size(indent_size as usize);
+            for value in &values {
+                let encoded = convert_json_to_toon(value, &config).unwrap().content;
+                let document = ToonDecoder::new(options.clone())
+                    .decode_document(&encoded)
+                    .unwrap();
+                assert_eq!(&document.to_value(), value);
+                assert_eq!(document.to_toon().unwrap(), encoded);
+                if indent_size == 2 {
+                    let built = ToonDocument::from_value(value).to_toon().unwrap();
+                    assert_eq!(built, encoded);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_folded_keys_and_indent() {
+        let input = "server.host: localhost\nserver.port: 80\nlimits:\n    max: 5";
+        let options = DecodeOptions::lenient()
+            .with_indent_size(4)
+            .with_expand_paths(true);
+        let decoded = decode_toon(input, &options).unwrap();
+        assert_eq!(
+            decoded.value,
+            json!({"server": {"host": "localhost", "port": 80}, "limits": {"max": 5}})
+        );
+        assert_eq!(decoded.document.to_toon().unwrap(), input);
+    }
+
+    #[test]
+    fn test_unfolded_dotted_key_is_quoted() {
+        let document = ToonDocument::from_value(&json!({"a.b": 1}));
+        assert_eq!(document.to_toon().unwrap(), "\"a.b\": 1");
+    }
+
+    #[test]
+    fn test_edited_table_keeps_header_style() {
+        let mut document = decode_toon("rows[#1|]{a|b}:\n  1|x\n", &DecodeOptions::strict())
+            .unwrap()
+            .document;
+        if let Some(ToonNode::Array(array)) = document.pointer_mut("/rows") {
+            array
+                .items
+                .push(ToonNode::from_value(&json!({"a": 2, "b": "y|z"})));
+        }
+        assert_eq!(
+            document.to_toon().unwrap(),
+            "rows[#2|]{a|b}:\n  1|x\n  2|\"y|z\""
+        );
+    }
+
+    #[test]
+    fn test_edited_table_falls_back_to_list() {
+        let mut document = decode_toon("rows[1]{a}:\n  1\n", &DecodeOptions::strict())
+            .unwrap()
+            .document;
+        if let Some(ToonNode::Array(array)) = document.pointer_mut("/rows") {
+            array
+                .items
+                .push(ToonNode::from_value(&json!({"a": 2, "b": [1, 2]})));
+        }
+        let output = document.to_toon().unwrap();
+        assert_eq!(
+            output,
+            "rows[2]:\n  -\n    a: 1\n  -\n    a: 2\n    b[2]: 1,2"
+        );
+        let reparsed = decode_toon(&output, &DecodeOptions::strict()).unwrap();
+        assert_eq!(
+            reparsed.value,
+            json!({"rows": [{"a": 1}, {"a": 2, "b": [1, 2]}]})
+        );
+    }
+}
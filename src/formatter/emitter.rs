@@ -0,0 +1,651 @@
+//! Incremental TOON emitter
+//!
+//! [`ToonEmitter`] writes TOON through explicit calls (`begin_object`, `key`,
+//! `begin_table`, `row`, `begin_list`, `end`) instead of formatting a
+//! `serde_json::Value`, for producers that stream data (such as rows from a
+//! database cursor) and never hold a whole document. Keys and values follow
+//! the same quoting rules as [`ToonFormatter`], and indentation follows the
+//! configured indent size.
+//!
+//! Array headers carry a length. When it is not known up front, pass `None`:
+//! the lines of that array are buffered until [`ToonEmitter::end`] and written
+//! behind a header with the actual count. Only the array with the unknown
+//! length is buffered, not the rest of the document.
+//!
+//! ```
+//! # use toonconv::formatter::emitter::ToonEmitter;
+//! # use toonconv::ConversionConfig;
+//! let mut emitter = ToonEmitter::new(Vec::new(), ConversionConfig::default());
+//! emitter.begin_object()?;
+//! emitter.key("users")?;
+//! emitter.begin_table(None, &["id", "name"])?;
+//! emitter.row(&[1.into(), "Ann".into()])?;
+//! emitter.row(&[2.into(), "Bo".into()])?;
+//! emitter.end()?;
+//! emitter.end()?;
+//! let output = String::from_utf8(emitter.finish()?).unwrap();
+//! assert_eq!(output, "users[2]{id,name}:\n  1,Ann\n  2,Bo");
+//! # Ok::<(), toonconv::ConversionError>(())
+//! ```
+
+use super::ToonFormatter;
+use crate::conversion::ConversionConfig;
+use crate::error::{ConversionError, ConversionErrorKind, ConversionResult, FormattingError};
+use serde_json::{Number, Value};
+use std::io::Write;
+
+/// Primitive value written by the emitter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(&'a str),
+}
+
+impl Scalar<'_> {
+    fn to_value(self) -> Option<Value> {
+        Some(match self {
+            Scalar::Null => Value::Null,
+            Scalar::Bool(b) => Value::Bool(b),
+            Scalar::Int(i) => Value::Number(i.into()),
+            Scalar::UInt(u) => Value::Number(u.into()),
+            Scalar::Float(f) => Value::Number(Number::from_f64(f)?),
+            Scalar::Str(s) => Value::String(s.to_string()),
+        })
+    }
+}
+
+impl<'a> From<&'a str> for Scalar<'a> {
+    fn from(value: &'a str) -> Self {
+        Scalar::Str(value)
+    }
+}
+
+impl<'a> From<&'a String> for Scalar<'a> {
+    fn from(value: &'a String) -> Self {
+        Scalar::Str(value)
+    }
+}
+
+impl From<bool> for Scalar<'_> {
+    fn from(value: bool) -> Self {
+        Scalar::Bool(value)
+    }
+}
+
+impl From<i32> for Scalar<'_> {
+    fn from(value: i32) -> Self {
+        Scalar::Int(value.into())
+    }
+}
+
+impl From<i64> for Scalar<'_> {
+    fn from(value: i64) -> Self {
+        Scalar::Int(value)
+    }
+}
+
+impl From<u32> for Scalar<'_> {
+    fn from(value: u32) -> Self {
+        Scalar::UInt(value.into())
+    }
+}
+
+impl From<u64> for Scalar<'_> {
+    fn from(value: u64) -> Self {
+        Scalar::UInt(value)
+    }
+}
+
+impl From<f64> for Scalar<'_> {
+    fn from(value: f64) -> Self {
+        Scalar::Float(value)
+    }
+}
+
+impl<'a, T: Into<Scalar<'a>>> From<Option<T>> for Scalar<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Scalar::Null, Into::into)
+    }
+}
+
+/// Kind of open container
+#[derive(Debug, PartialEq)]
+enum ScopeKind {
+    Object,
+    Table { fields: usize },
+    List,
+}
+
+/// Open container
+#[derive(Debug)]
+struct Scope {
+    kind: ScopeKind,
+    /// Indentation level of the container's lines (fields, rows or items)
+    depth: usize,
+    /// Declared length of an array
+    declared: Option<usize>,
+    /// Rows or items written so far
+    count: usize,
+    /// Header split around the length, for arrays of unknown length
+    header: Option<(String, String)>,
+    /// Lines written while the length is unknown
+    buffer: Option<String>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind, depth: usize) -> Self {
+        Self {
+            kind,
+            depth,
+            declared: None,
+            count: 0,
+            header: None,
+            buffer: None,
+        }
+    }
+}
+
+/// Incremental TOON writer
+pub struct ToonEmitter<W: Write> {
+    writer: W,
+    config: ConversionConfig,
+    formatter: ToonFormatter,
+    stack: Vec<Scope>,
+    pending_key: Option<String>,
+    wrote_line: bool,
+    root_done: bool,
+}
+
+impl<W: Write> ToonEmitter<W> {
+    /// Create an emitter writing to `writer`
+    pub fn new(writer: W, config: ConversionConfig) -> Self {
+        let formatter = ToonFormatter::new(config.clone());
+        Self {
+            writer,
+            config,
+            formatter,
+            stack: Vec::new(),
+            pending_key: None,
+            wrote_line: false,
+            root_done: false,
+        }
+    }
+
+    /// Start an object: the root, the value of the pending key, or a list item
+    ///
+    /// A list item object is a bare `-` with its fields one level deeper, as
+    /// [`ToonFormatter`] writes it.
+    pub fn begin_object(&mut self) -> ConversionResult<()> {
+        match self.position()? {
+            Position::Root => self.stack.push(Scope::new(ScopeKind::Object, 0)),
+            Position::Field { depth, key } => {
+                self.line(depth, &format!("{}:", key))?;
+                self.stack.push(Scope::new(ScopeKind::Object, depth + 1));
+            }
+            Position::Item { depth } => {
+                self.line(depth, "-")?;
+                self.stack.push(Scope::new(ScopeKind::Object, depth + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the key of the next value in the current object
+    pub fn key(&mut self, name: &str) -> ConversionResult<()> {
+        if !matches!(self.stack.last(), Some(scope) if scope.kind == ScopeKind::Object) {
+            return Err(misuse("key() is only valid inside an object"));
+        }
+        if self.pending_key.is_some() {
+            return Err(misuse("key() called twice without a value"));
+        }
+        self.pending_key = Some(self.formatter.format_key(name)?);
+        Ok(())
+    }
+
+    /// Write a primitive value: the root, the value of the pending key, or a
+    /// list item
+    pub fn value<'a>(&mut self, value: impl Into<Scalar<'a>>) -> ConversionResult<()> {
+        let formatted = self.format(value.into())?;
+        match self.position()? {
+            Position::Root => {
+                self.line(0, &formatted)?;
+                self.root_done = true;
+            }
+            Position::Field { depth, key } => {
+                self.line(depth, &format!("{}: {}", key, formatted))?
+            }
+            Position::Item { depth } => self.line(depth, &format!("- {}", formatted))?,
+        }
+        Ok(())
+    }
+
+    /// Write an array of primitives inline, as `key[N]: a,b,c`
+    pub fn inline_array(&mut self, values: &[Scalar<'_>]) -> ConversionResult<()> {
+        let cells = values
+            .iter()
+            .map(|value| self.format(*value))
+            .collect::<ConversionResult<Vec<_>>>()?;
+        let (depth, prefix) = self.header_position()?;
+        let mut line = format!("{}[{}]:", prefix, values.len());
+        if !cells.is_empty() {
+            line.push(' ');
+            line.push_str(&cells.join(self.config.delimiter.as_str()));
+        }
+        self.line(depth, &line)?;
+        self.close_root_value();
+        Ok(())
+    }
+
+    /// Start a tabular array with `len` rows (`None` if not known yet) and the
+    /// given fields
+    pub fn begin_table(&mut self, len: Option<usize>, fields: &[&str]) -> ConversionResult<()> {
+        let fields_text = fields
+            .iter()
+            .map(|field| self.formatter.format_key(field))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(",");
+        let (depth, prefix) = self.header_position()?;
+        self.begin_array(
+            ScopeKind::Table {
+                fields: fields.len(),
+            },
+            depth,
+            prefix,
+            format!("{{{}}}:", fields_text),
+            len,
+        )
+    }
+
+    /// Write one row of the current table
+    pub fn row(&mut self, cells: &[Scalar<'_>]) -> ConversionResult<()> {
+        let (depth, fields) = match self.stack.last() {
+            Some(Scope {
+                kind: ScopeKind::Table { fields },
+                depth,
+                ..
+            }) => (*depth, *fields),
+            _ => return Err(misuse("row() is only valid inside a table")),
+        };
+        if cells.len() != fields {
+            return Err(misuse(&format!(
+                "Row has {} cells but the table declares {} fields",
+                cells.len(),
+                fields
+            )));
+        }
+
+        let formatted = cells
+            .iter()
+            .map(|cell| self.format(*cell))
+            .collect::<ConversionResult<Vec<_>>>()?;
+        self.line(depth, &formatted.join(self.config.delimiter.as_str()))?;
+        if let Some(scope) = self.stack.last_mut() {
+            scope.count += 1;
+        }
+        Ok(())
+    }
+
+    /// Start a list array with `len` items (`None` if not known yet)
+    pub fn begin_list(&mut self, len: Option<usize>) -> ConversionResult<()> {
+        let (depth, prefix) = self.header_position()?;
+        self.begin_array(ScopeKind::List, depth, prefix, ":".to_string(), len)
+    }
+
+    /// Close the innermost object, table or list
+    pub fn end(&mut self) -> ConversionResult<()> {
+        if self.pending_key.is_some() {
+            return Err(misuse("end() called after key() without a value"));
+        }
+        let scope = self
+            .stack
+            .pop()
+            .ok_or_else(|| misuse("end() called with no open container"))?;
+
+        if let Some(declared) = scope.declared {
+            if declared != scope.count {
+                return Err(misuse(&format!(
+                    "Array declared {} entries but {} were written",
+                    declared, scope.count
+                )));
+            }
+        }
+
+        if let (Some((prefix, suffix)), Some(buffer)) = (scope.header, scope.buffer) {
+            self.write_line(&format!("{}[{}]{}", prefix, scope.count, suffix))?;
+            self.write_raw(&buffer)?;
+        }
+
+        self.close_root_value();
+        Ok(())
+    }
+
+    /// Finish the document and return the writer
+    pub fn finish(mut self) -> ConversionResult<W> {
+        if !self.stack.is_empty() || self.pending_key.is_some() {
+            return Err(misuse("finish() called with open containers"));
+        }
+        self.writer.flush().map_err(io_error)?;
+        Ok(self.writer)
+    }
+
+    fn format(&self, value: Scalar<'_>) -> ConversionResult<String> {
+        let value = value.to_value().ok_or_else(|| {
+            FormattingError::invalid_structure(
+                "Invalid number: infinity or NaN not supported in TOON".to_string(),
+            )
+        })?;
+        Ok(self.formatter.format_primitive(&value)?)
+    }
+
+    /// Where the next value goes, consuming the pending key
+    fn position(&mut self) -> ConversionResult<Position> {
+        let Some(scope) = self.stack.last_mut() else {
+            if self.root_done || self.wrote_line {
+                return Err(misuse("The document already has a root value"));
+            }
+            return Ok(Position::Root);
+        };
+
+        match scope.kind {
+            ScopeKind::Object => {
+                let key = self
+                    .pending_key
+                    .take()
+                    .ok_or_else(|| misuse("A value inside an object needs a key() first"))?;
+                Ok(Position::Field {
+                    depth: scope.depth,
+                    key,
+                })
+            }
+            ScopeKind::List => {
+                scope.count += 1;
+                Ok(Position::Item { depth: scope.depth })
+            }
+            ScopeKind::Table { .. } => Err(misuse("Use row() to write table rows")),
+        }
+    }
+
+    /// Depth and text before `[N]` of an array header
+    fn header_position(&mut self) -> ConversionResult<(usize, String)> {
+        Ok(match self.position()? {
+            Position::Root => (0, String::new()),
+            Position::Field { depth, key } => (depth, key),
+            Position::Item { depth } => (depth, "- ".to_string()),
+        })
+    }
+
+    fn begin_array(
+        &mut self,
+        kind: ScopeKind,
+        depth: usize,
+        prefix: String,
+        suffix: String,
+        len: Option<usize>,
+    ) -> ConversionResult<()> {
+        let mut scope = Scope::new(kind, depth + 1);
+        scope.declared = len;
+
+        match len {
+            Some(len) => self.line(depth, &format!("{}[{}]{}", prefix, len, suffix))?,
+            None => {
+                let prefix = self.indented(depth, &prefix);
+                scope.header = Some((prefix, suffix));
+                scope.buffer = Some(String::new());
+            }
+        }
+
+        self.stack.push(scope);
+        Ok(())
+    }
+
+    /// Mark the root value as complete once its container is closed
+    fn close_root_value(&mut self) {
+        if self.stack.is_empty() {
+            self.root_done = true;
+        }
+    }
+
+    fn indented(&self, depth: usize, text: &str) -> String {
+        let indent = " ".repeat(depth * self.config.indent_size as usize);
+        format!("{}{}", indent, text)
+    }
+
+    /// Write a line at a depth into the innermost buffer or the writer
+    fn line(&mut self, depth: usize, text: &str) -> ConversionResult<()> {
+        let line = self.indented(depth, text);
+        self.write_line(&line)
+    }
+
+    fn write_line(&mut self, line: &str) -> ConversionResult<()> {
+        if let Some(buffer) = self.innermost_buffer() {
+            buffer.push('\n');
+            buffer.push_str(line);
+            return Ok(());
+        }
+
+        if self.wrote_line {
+            self.writer.write_all(b"\n").map_err(io_error)?;
+        }
+        self.wrote_line = true;
+        self.writer.write_all(line.as_bytes()).map_err(io_error)
+    }
+
+    /// Append already separated lines (each starting with a newline)
+    fn write_raw(&mut self, lines: &str) -> ConversionResult<()> {
+        if let Some(buffer) = self.innermost_buffer() {
+            buffer.push_str(lines);
+            return Ok(());
+        }
+        self.writer.write_all(lines.as_bytes()).map_err(io_error)
+    }
+
+    fn innermost_buffer(&mut self) -> Option<&mut String> {
+        self.stack
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.buffer.as_mut())
+    }
+}
+
+/// Where the next value is written
+enum Position {
+    Root,
+    Field { depth: usize, key: String },
+    Item { depth: usize },
+}
+
+fn misuse(message: &str) -> ConversionError {
+    ConversionError::FormattingError(FormattingError::invalid_structure(message.to_string()))
+}
+
+fn io_error(error: std::io::Error) -> ConversionError {
+    ConversionError::conversion(ConversionErrorKind::io(
+        format!("Failed to write TOON output: {}", error),
+        None,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::DelimiterType;
+    use crate::decoder::{decode_toon, DecodeOptions};
+    use serde_json::json;
+
+    fn emit(build: impl FnOnce(&mut ToonEmitter<Vec<u8>>) -> ConversionResult<()>) -> String {
+        let mut emitter = ToonEmitter::new(Vec::new(), ConversionConfig::default());
+        build(&mut emitter).unwrap();
+        String::from_utf8(emitter.finish().unwrap()).unwrap()
+    }
+
+    fn format(value: &Value) -> String {
+        ToonFormatter::new(ConversionConfig::default())
+            .format(value)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_formatter_for_objects_and_tables() {
+        let output = emit(|e| {
+            e.begin_object()?;
+            e.key("name")?;
+            e.value("my app")?;
+            e.key("version")?;
+            e.value("1.0")?;
+            e.key("users")?;
+            e.begin_table(Some(2), &["id", "name", "email"])?;
+            e.row(&[1.into(), "Ann".into(), Scalar::Null])?;
+            e.row(&[2.into(), "Bo, Jr".into(), "bo@example.com".into()])?;
+            e.end()?;
+            e.key("meta")?;
+            e.begin_object()?;
+            e.key("tags")?;
+            e.inline_array(&["a".into(), "b".into()])?;
+            e.key("ratio")?;
+            e.value(0.5)?;
+            e.end()?;
+            e.end()
+        });
+
+        let expected = json!({
+            "name": "my app",
+            "version": "1.0",
+            "users": [
+                {"id": 1, "name": "Ann", "email": null},
+                {"id": 2, "name": "Bo, Jr", "email": "bo@example.com"}
+            ],
+            "meta": {"tags": ["a", "b"], "ratio": 0.5}
+        });
+        assert_eq!(output, format(&expected));
+    }
+
+    #[test]
+    fn test_unknown_row_count_is_buffered() {
+        let output = emit(|e| {
+            e.begin_object()?;
+            e.key("rows")?;
+            e.begin_table(None, &["n"])?;
+            for n in 0..3 {
+                e.row(&[n.into()])?;
+            }
+            e.end()?;
+            e.key("after")?;
+            e.value(true)?;
+            e.end()
+        });
+        assert_eq!(output, "rows[3]{n}:\n  0\n  1\n  2\nafter: true");
+    }
+
+    #[test]
+    fn test_lists_round_trip_through_decoder() {
+        let output = emit(|e| {
+            e.begin_list(None)?;
+            e.value("plain")?;
+            e.begin_object()?;
+            e.key("id")?;
+            e.value(1)?;
+            e.key("tags")?;
+            e.begin_list(Some(1))?;
+            e.value("x")?;
+            e.end()?;
+            e.end()?;
+            e.begin_object()?;
+            e.key("rows")?;
+            e.begin_table(None, &["a"])?;
+            e.row(&[1.into()])?;
+            e.end()?;
+            e.end()?;
+            e.begin_object()?;
+            e.end()?;
+            e.end()
+        });
+
+        assert_eq!(
+            output,
+            "[4]:\n  - plain\n  -\n    id: 1\n    tags[1]:\n      - x\n  -\n    rows[1]{a}:\n      1\n  -"
+        );
+        let expected = json!(["plain", {"id": 1, "tags": ["x"]}, {"rows": [{"a": 1}]}, {}]);
+        assert_eq!(
+            decode_toon(&output, &DecodeOptions::strict())
+                .unwrap()
+                .value,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_objects_in_nested_lists_match_formatter() {
+        let output = emit(|e| {
+            e.begin_object()?;
+            e.key("groups")?;
+            e.begin_list(None)?;
+            e.begin_object()?;
+            e.key("id")?;
+            e.value(1)?;
+            e.key("users")?;
+            e.begin_list(Some(2))?;
+            e.begin_object()?;
+            e.key("a")?;
+            e.value(1)?;
+            e.key("b")?;
+            e.inline_array(&[1.into()])?;
+            e.end()?;
+            e.begin_object()?;
+            e.key("a")?;
+            e.value(2)?;
+            e.end()?;
+            e.end()?;
+            e.end()?;
+            e.value("x")?;
+            e.end()?;
+            e.end()
+        });
+
+        let expected = json!({
+            "groups": [{"id": 1, "users": [{"a": 1, "b": [1]}, {"a": 2}]}, "x"]
+        });
+        assert_eq!(output, format(&expected));
+    }
+
+    #[test]
+    fn test_quoting_and_delimiter_follow_config() {
+        let config = ConversionConfig {
+            delimiter: DelimiterType::Pipe,
+            indent_size: 4,
+            ..ConversionConfig::default()
+        };
+        let mut emitter = ToonEmitter::new(Vec::new(), config);
+        emitter.begin_object().unwrap();
+        emitter.key("my key").unwrap();
+        emitter.begin_table(Some(1), &["a", "b"]).unwrap();
+        emitter.row(&["x|y".into(), "true".into()]).unwrap();
+        emitter.end().unwrap();
+        emitter.end().unwrap();
+
+        let output = String::from_utf8(emitter.finish().unwrap()).unwrap();
+        assert_eq!(output, "\"my key\"[1]{a,b}:\n    \"x|y\"|\"true\"");
+    }
+
+    #[test]
+    fn test_misuse_is_an_error() {
+        let mut emitter = ToonEmitter::new(Vec::new(), ConversionConfig::default());
+        emitter.begin_object().unwrap();
+        assert!(emitter.value(1).is_err());
+        emitter.key("t").unwrap();
+        emitter.begin_table(Some(2), &["a"]).unwrap();
+        assert!(emitter.row(&[1.into(), 2.into()]).is_err());
+        emitter.row(&[1.into()]).unwrap();
+        assert!(emitter.end().is_err());
+    }
+
+    #[test]
+    fn test_finish_requires_closed_containers() {
+        let mut emitter = ToonEmitter::new(Vec::new(), ConversionConfig::default());
+        emitter.begin_list(None).unwrap();
+        assert!(emitter.finish().is_err());
+    }
+}
@@ -1,50 +1,62 @@
 //! TOON formatting module
 
+pub mod document;
+pub mod emitter;
 pub mod mixed_arrays;
 pub mod nested;
 pub mod quotes;
 pub mod schema;
+pub mod template;
 
 use crate::conversion::{ConversionConfig, ConversionResult};
+use crate::document::ToonDocument;
 use crate::error::{FormattingError, FormattingResult};
 use serde_json::{Map, Value};
 
 /// Main TOON formatter
 pub struct ToonFormatter {
     config: ConversionConfig,
-    indent_level: usize,
-    in_tabular_array: bool,
 }
 
 impl ToonFormatter {
     /// Create a new formatter with configuration
     pub fn new(config: ConversionConfig) -> Self {
-        Self {
-            config,
-            indent_level: 0,
-            in_tabular_array: false,
-        }
+        Self { config }
     }
 
     /// Format a JSON value as TOON
+    ///
+    /// The value is written through a [`ToonDocument`] with the layouts
+    /// [`ToonDocument::from_value`] chooses, so decoding the output and
+    /// writing the document back reproduces it.
     pub fn format(&mut self, value: &Value) -> ConversionResult<String> {
-        let result = match value {
-            Value::Null => self.format_null(),
-            Value::Bool(b) => self.format_bool(*b),
-            Value::Number(n) => self.format_number(n),
-            Value::String(s) => self.format_string(s),
-            Value::Array(a) => self.format_array(a),
-            Value::Object(o) => self.format_object(o),
-        };
-
-        match result {
-            Ok(output) => {
-                if self.config.validate_output {
-                    self.validate_output(&output)?;
-                }
-                Ok(output)
+        if let (false, Value::Object(object)) = (self.config.pretty, value) {
+            let output = self.format_compact_object(object)?;
+            if self.config.validate_output {
+                self.validate_output(&output)?;
             }
-            Err(e) => Err(crate::error::ConversionError::FormattingError(e)),
+            return Ok(output);
+        }
+        self.format_document(&self.document(value))
+    }
+
+    /// Format a [`ToonDocument`] with the layout it records
+    ///
+    /// Indentation and the default delimiter come from the document.
+    pub fn format_document(&self, document: &ToonDocument) -> ConversionResult<String> {
+        let output = document::DocumentWriter::new(&self.config, document).write(document)?;
+        if self.config.validate_output {
+            self.validate_output(&output)?;
+        }
+        Ok(output)
+    }
+
+    /// Document for a JSON value with the configured indentation and delimiter
+    fn document(&self, value: &Value) -> ToonDocument {
+        ToonDocument {
+            indent_size: self.config.indent_size as usize,
+            delimiter: self.config.delimiter,
+            ..ToonDocument::from_value(value)
         }
     }
 
@@ -200,7 +212,7 @@ impl ToonFormatter {
     }
 
     /// Format a key with quoting if needed
-    fn format_key(&self, key: &str) -> FormattingResult<String> {
+    pub fn format_key(&self, key: &str) -> FormattingResult<String> {
         if self.should_quote_key(key) {
             self.quote_string(key)
         } else {
@@ -232,421 +244,20 @@ impl ToonFormatter {
         Ok(quoted)
     }
 
-    /// Format an array
-    fn format_array(&mut self, array: &[Value]) -> FormattingResult<String> {
-        if array.is_empty() {
-            return Ok("[0]:".to_string());
-        }
-
-        // Check if this is a uniform array of objects (tabular format)
-        if self.is_uniform_object_array(array) {
-            return self.format_tabular_array(array);
-        }
-
-        // Check if this is a uniform array of primitives
-        if self.is_uniform_primitive_array(array) {
-            return self.format_primitive_array(array);
-        }
-
-        // Mixed or complex array
-        // Increment indent so items have proper indentation
-        self.indent_level += 1;
-        let result = self.format_mixed_array(array);
-        self.indent_level -= 1;
-        result
-    }
-
-    /// Check if array contains uniform objects (truly uniform for tabular format)
-    fn is_uniform_object_array(&self, array: &[Value]) -> bool {
-        if array.is_empty() {
-            return false;
-        }
-
-        // All elements must be objects
-        if !array.iter().all(|v| v.is_object()) {
-            return false;
-        }
-
-        // All objects must have the same keys
-        let first_obj = array[0].as_object().unwrap();
-        let first_keys: std::collections::HashSet<&str> =
-            first_obj.keys().map(|k| k.as_str()).collect();
-
-        // Check if all objects have same keys
-        if !array.iter().all(|v| {
-            let keys: std::collections::HashSet<&str> =
-                v.as_object().unwrap().keys().map(|k| k.as_str()).collect();
-            keys == first_keys
-        }) {
-            return false;
-        }
-
-        // Additional check: if any value is an array or object, verify they're uniform too
-        // This prevents using tabular format for objects with nested complex structures
-        for key in first_keys {
-            let first_val = first_obj.get(key).unwrap();
-
-            // If the value is an array, check if all corresponding arrays are identical in structure
-            if first_val.is_array() {
-                let first_arr = first_val.as_array().unwrap();
-                let first_len = first_arr.len();
-
-                // Check if all objects have arrays of the same length for this key
-                for obj in array.iter().skip(1) {
-                    let val = obj.as_object().unwrap().get(key).unwrap();
-                    if !val.is_array() || val.as_array().unwrap().len() != first_len {
-                        return false;
-                    }
-                }
-            }
-        }
-
-        true
-    }
-
-    /// Check if array contains uniform primitives
-    fn is_uniform_primitive_array(&self, array: &[Value]) -> bool {
-        if array.is_empty() {
-            return false;
-        }
-
-        // Primitives are: null, bool, number, string (NOT objects or arrays)
-        array.iter().all(|v| !v.is_object() && !v.is_array())
-    }
-
-    /// Format primitive array (TOON inline format: [count]: val1,val2,val3)
-    fn format_primitive_array(&mut self, array: &[Value]) -> FormattingResult<String> {
-        let length = array.len();
-
-        let mut values = Vec::with_capacity(length);
-        for value in array {
-            let formatted = self.format_value(value)?;
-            values.push(formatted);
-        }
-
-        let delimiter = self.config.delimiter.as_str();
-        let values_str = values.join(delimiter);
-
-        // TOON format: [count]: val1,val2,val3 (inline with count)
-        Ok(format!("[{}]: {}", length, values_str))
-    }
-
-    /// Format tabular array (uniform objects) - TOON format: [count]{field1,field2}:
-    fn format_tabular_array(&mut self, array: &[Value]) -> FormattingResult<String> {
-        if array.is_empty() {
-            return Ok("[0]:".to_string());
-        }
-
-        let first_obj = array[0].as_object().unwrap();
-        let fields: Vec<&str> = first_obj.keys().map(|k| k.as_str()).collect();
-
-        // Build TOON schema declaration: [count]{field1,field2}:
-        // Quote field names that need quoting
+    /// Tabular array header without the colon: `[count]{field1,field2}`
+    ///
+    /// Field names are quoted when needed and always separated by commas; the
+    /// configured delimiter only separates the cells of each row. `count` may
+    /// be a placeholder such as `N`.
+    pub fn tabular_header<S: AsRef<str>>(&self, count: &str, fields: &[S]) -> String {
         let quoted_fields: Vec<String> = fields
             .iter()
-            .map(|f| self.format_key(f).unwrap_or_else(|_| f.to_string()))
+            .map(|f| {
+                let f = f.as_ref();
+                self.format_key(f).unwrap_or_else(|_| f.to_string())
+            })
             .collect();
-        let field_list = quoted_fields.join(",");
-        let schema = format!("[{}]{{{}}}", array.len(), field_list);
-
-        let delimiter = self.config.delimiter.as_str();
-        let mut result = String::new();
-
-        result.push_str(&schema);
-        result.push(':');
-        result.push('\n');
-
-        // Set flag to indicate we're in a tabular array (for number formatting)
-        self.in_tabular_array = true;
-
-        // Increment indent for the data rows
-        self.indent_level += 1;
-
-        // Format each row with proper indentation
-        for (i, obj) in array.iter().enumerate() {
-            // Indent the row one level deeper than the schema header
-            result.push_str(&self.indent());
-
-            let obj_map = obj.as_object().unwrap();
-            let mut row_values = Vec::with_capacity(fields.len());
-
-            for field in &fields {
-                let value = obj_map.get(&field.to_string()).unwrap();
-                let formatted = self.format_value(value)?;
-                row_values.push(formatted);
-            }
-
-            let row_str = row_values.join(delimiter);
-            result.push_str(&row_str);
-
-            if i < array.len() - 1 {
-                result.push('\n');
-            }
-        }
-
-        // Reset indent level and flag after formatting tabular array
-        self.indent_level -= 1;
-        self.in_tabular_array = false;
-
-        Ok(result)
-    }
-
-    /// Format mixed array (TOON dash-prefix format)
-    fn format_mixed_array(&mut self, array: &[Value]) -> FormattingResult<String> {
-        // TOON format for non-uniform arrays: use dash prefix with count
-        let mut result = String::new();
-        result.push_str(&format!("[{}]:", array.len()));
-
-        // Note: caller handles base indentation, we just add one level for array items
-        for value in array.iter() {
-            result.push('\n');
-            result.push_str(&self.indent());
-
-            // For objects in mixed arrays: dash then newline, content indented
-            if value.is_object() {
-                let obj = value.as_object().unwrap();
-                let keys: Vec<&str> = obj.keys().map(|k| k.as_str()).collect();
-
-                // Objects: dash followed by newline (no space)
-                result.push('-');
-                result.push('\n');
-
-                // Increment indent for object content
-                self.indent_level += 1;
-                result.push_str(&self.indent());
-
-                for (j, key) in keys.iter().enumerate() {
-                    if j > 0 {
-                        result.push('\n');
-                        result.push_str(&self.indent());
-                    }
-
-                    let formatted_key = self.format_key(key)?;
-                    result.push_str(&formatted_key);
-
-                    let val = obj.get(*key).unwrap();
-
-                    // Handle arrays specially - inline primitive arrays
-                    if val.is_array() {
-                        let arr = val.as_array().unwrap();
-                        if self.is_uniform_primitive_array(arr) {
-                            // Inline format: methods[2]: GET,POST
-                            let formatted = self.format_primitive_array(arr)?;
-                            result.push_str(&formatted);
-                        } else {
-                            // Complex array - format as nested structure
-                            result.push_str(&format!("[{}]:", arr.len()));
-                            result.push('\n');
-                            self.indent_level += 1;
-
-                            // Format each element with proper indentation
-                            for item in arr.iter() {
-                                result.push_str(&self.indent());
-                                result.push_str("- ");
-
-                                if item.is_object() {
-                                    // Format object fields inline after the dash
-                                    let obj_inner = item.as_object().unwrap();
-                                    let inner_keys: Vec<&str> =
-                                        obj_inner.keys().map(|k| k.as_str()).collect();
-
-                                    for (k, inner_key) in inner_keys.iter().enumerate() {
-                                        if k > 0 {
-                                            result.push('\n');
-                                            result.push_str(&self.indent());
-                                            result.push_str("  ");
-                                        }
-
-                                        result.push_str(inner_key);
-
-                                        let inner_val = obj_inner.get(*inner_key).unwrap();
-
-                                        // For nested values in deeply nested structures
-                                        if inner_val.is_array() {
-                                            let inner_arr = inner_val.as_array().unwrap();
-                                            if self.is_uniform_primitive_array(inner_arr) {
-                                                let formatted =
-                                                    self.format_primitive_array(inner_arr)?;
-                                                result.push_str(&formatted);
-                                            } else {
-                                                // Recursively format complex nested arrays with dash-prefix
-                                                // Add array count directly after key: medications[2]:
-                                                result.push_str(&format!("[{}]:", inner_arr.len()));
-                                                result.push('\n');
-                                                self.indent_level += 1;
-
-                                                // Format each item with dash-prefix
-                                                for item in inner_arr.iter() {
-                                                    result.push_str(&self.indent());
-                                                    result.push_str("- ");
-
-                                                    if item.is_object() {
-                                                        let obj_deep = item.as_object().unwrap();
-                                                        let deep_keys: Vec<&str> = obj_deep
-                                                            .keys()
-                                                            .map(|k| k.as_str())
-                                                            .collect();
-
-                                                        for (dk, deep_key) in
-                                                            deep_keys.iter().enumerate()
-                                                        {
-                                                            if dk > 0 {
-                                                                result.push('\n');
-                                                                result.push_str(&self.indent());
-                                                                result.push_str("  ");
-                                                            }
-
-                                                            result.push_str(deep_key);
-                                                            result.push_str(": ");
-
-                                                            let deep_val =
-                                                                obj_deep.get(*deep_key).unwrap();
-                                                            let formatted_deep =
-                                                                self.format_value(deep_val)?;
-                                                            result.push_str(&formatted_deep);
-                                                        }
-                                                    } else {
-                                                        let formatted_item =
-                                                            self.format_value(item)?;
-                                                        result.push_str(&formatted_item);
-                                                    }
-                                                    result.push('\n');
-                                                }
-
-                                                self.indent_level -= 1;
-                                                // Remove trailing newline
-                                                if result.ends_with('\n') {
-                                                    result.pop();
-                                                }
-                                            }
-                                        } else if inner_val.is_object() {
-                                            // Format nested object
-                                            result.push(':');
-                                            result.push('\n');
-                                            self.indent_level += 1;
-                                            let formatted =
-                                                self.format_object(inner_val.as_object().unwrap())?;
-                                            result.push_str(&formatted);
-                                            self.indent_level -= 1;
-                                        } else {
-                                            result.push_str(": ");
-                                            let formatted_val = self.format_value(inner_val)?;
-                                            result.push_str(&formatted_val);
-                                        }
-                                    }
-                                } else {
-                                    let formatted = self.format_value(item)?;
-                                    result.push_str(&formatted);
-                                }
-                                result.push('\n');
-                            }
-
-                            self.indent_level -= 1;
-                            // Remove trailing newline
-                            if result.ends_with('\n') {
-                                result.pop();
-                            }
-                        }
-                    } else if val.is_object() {
-                        // Nested object - format on new line
-                        result.push(':');
-                        result.push('\n');
-                        let formatted = self.format_object(val.as_object().unwrap())?;
-                        result.push_str(&formatted);
-                    } else {
-                        // Simple value - inline
-                        result.push_str(": ");
-                        let formatted = self.format_value(val)?;
-                        result.push_str(&formatted);
-                    }
-                }
-                // Restore indent level after object content
-                self.indent_level -= 1;
-            } else if value.is_array() {
-                // Nested array: - [count]: ...
-                result.push_str("- ");
-                let formatted = self.format_value(value)?;
-                result.push_str(&formatted);
-            } else {
-                // Primitive value: - value
-                result.push_str("- ");
-                let formatted = self.format_value(value)?;
-                result.push_str(&formatted);
-            }
-        }
-
-        Ok(result)
-    }
-
-    /// Format an object
-    fn format_object(&mut self, object: &Map<String, Value>) -> FormattingResult<String> {
-        if object.is_empty() {
-            // TOON spec: empty object is represented as empty string
-            return Ok("".to_string());
-        }
-
-        if self.config.pretty {
-            self.format_pretty_object(object)
-        } else {
-            self.format_compact_object(object)
-        }
-    }
-
-    /// Format object in pretty mode (TOON compliant - no braces)
-    fn format_pretty_object(&mut self, object: &Map<String, Value>) -> FormattingResult<String> {
-        let mut result = String::new();
-        let keys: Vec<&str> = object.keys().map(|k| k.as_str()).collect();
-
-        for (i, key) in keys.iter().enumerate() {
-            if i > 0 {
-                result.push('\n');
-            }
-
-            result.push_str(&self.indent());
-
-            // Format key with quoting if needed
-            let formatted_key = self.format_key(key)?;
-            result.push_str(&formatted_key);
-
-            let value = object.get(*key).unwrap();
-
-            // Check if this is an array that should be formatted with tabular/inline format
-            if value.is_array() {
-                let arr = value.as_array().unwrap();
-
-                // Check if it's a uniform object array (tabular)
-                if self.is_uniform_object_array(arr) {
-                    let formatted = self.format_tabular_array(arr)?;
-                    result.push_str(&formatted);
-                } else if self.is_uniform_primitive_array(arr) {
-                    // Inline primitive array
-                    let formatted = self.format_primitive_array(arr)?;
-                    result.push_str(&formatted);
-                } else {
-                    // Mixed array with dash-prefix
-                    self.indent_level += 1;
-                    let formatted = self.format_mixed_array(arr)?;
-                    result.push_str(&formatted);
-                    self.indent_level -= 1;
-                }
-            } else if value.is_object() {
-                // Nested object goes on next line
-                result.push(':');
-                result.push('\n');
-                self.indent_level += 1;
-                let formatted_value = self.format_value(value)?;
-                result.push_str(&formatted_value);
-                self.indent_level -= 1;
-            } else {
-                // Primitive values go on same line
-                result.push(':');
-                result.push(' ');
-                let formatted_value = self.format_value(value)?;
-                result.push_str(&formatted_value);
-            }
-        }
-
-        Ok(result)
+        format!("[{}]{{{}}}", count, quoted_fields.join(","))
     }
 
     /// Format object in compact mode (TOON compliant - no braces)
@@ -673,21 +284,32 @@ impl ToonFormatter {
         Ok(result)
     }
 
-    /// Format a single value (used by arrays)
-    fn format_value(&mut self, value: &Value) -> FormattingResult<String> {
+    /// Format a primitive value (null, bool, number or string) on its own
+    ///
+    /// Used by streaming converters that emit TOON without building a full
+    /// JSON document, so their cells follow the same quoting rules.
+    pub fn format_primitive(&self, value: &Value) -> FormattingResult<String> {
         match value {
             Value::Null => self.format_null(),
             Value::Bool(b) => self.format_bool(*b),
             Value::Number(n) => self.format_number(n),
             Value::String(s) => self.format_string(s),
-            Value::Array(a) => self.format_array(a),
-            Value::Object(o) => self.format_object(o),
+            Value::Array(_) | Value::Object(_) => Err(FormattingError::invalid_structure(
+                "Expected a primitive value".to_string(),
+            )),
         }
     }
 
-    /// Get current indentation string
-    fn indent(&self) -> String {
-        " ".repeat(self.indent_level * self.config.indent_size as usize)
+    /// Format a field value of a compact object
+    fn format_value(&mut self, value: &Value) -> FormattingResult<String> {
+        match value {
+            Value::Object(o) => self.format_compact_object(o),
+            Value::Array(_) => {
+                let document = self.document(value);
+                document::DocumentWriter::new(&self.config, &document).write(&document)
+            }
+            primitive => self.format_primitive(primitive),
+        }
     }
 
     /// Validate TOON output compliance
@@ -925,6 +547,68 @@ mod tests {
         assert!(toon.contains("2,Bob"));
     }
 
+    #[test]
+    fn test_rows_with_nested_values_are_list_items() {
+        let config = ConversionConfig::default();
+        let mut formatter = ToonFormatter::new(config);
+
+        let json = serde_json::json!({
+            "orders": [
+                {"id": 1, "customer": {"name": "Ann"}, "items": [{"sku": "A", "qty": 2}]},
+                {"id": 2, "customer": {"name": "Bo"}, "items": [{"sku": "B", "qty": 1}]}
+            ]
+        });
+
+        let toon = formatter.format(&json).unwrap();
+        assert!(toon.starts_with("orders[2]:\n  -\n    id: 1\n    customer:\n      name: Ann\n"));
+        assert!(toon.contains("    items[1]{sku,qty}:\n      A,2\n"));
+    }
+
+    /// Format with the default configuration and check the TOON decodes back
+    fn format_round_trip(json: &Value) -> String {
+        let toon = format_to_toon(json, &ConversionConfig::default()).unwrap();
+        let decoded = crate::decoder::decode_toon(&toon, &crate::decoder::DecodeOptions::strict())
+            .unwrap()
+            .value;
+        assert_eq!(&decoded, json);
+        toon
+    }
+
+    #[test]
+    fn test_rows_with_nested_arrays_are_list_items() {
+        // Rows are only tables when every cell is a primitive; an inline array
+        // in a cell (`1,[2]: 1,2`) does not decode
+        let json = serde_json::json!({
+            "o": [{"id": 1, "t": [1, 2]}, {"id": 2, "t": [3, 4]}]
+        });
+        assert_eq!(
+            format_round_trip(&json),
+            "o[2]:\n  -\n    id: 1\n    t[2]: 1,2\n  -\n    id: 2\n    t[2]: 3,4"
+        );
+    }
+
+    #[test]
+    fn test_nested_object_in_list_item_is_indented() {
+        let json = serde_json::json!({
+            "items": [{"id": 1, "meta": {"a": 1, "b": 2}}, "x"]
+        });
+        assert_eq!(
+            format_round_trip(&json),
+            "items[2]:\n  -\n    id: 1\n    meta:\n      a: 1\n      b: 2\n  - x"
+        );
+    }
+
+    #[test]
+    fn test_table_in_list_item() {
+        let json = serde_json::json!({
+            "items": [{"id": 1, "rows": [{"a": 1}, {"a": 2}]}, "x"]
+        });
+        assert_eq!(
+            format_round_trip(&json),
+            "items[2]:\n  -\n    id: 1\n    rows[2]{a}:\n      1\n      2\n  - x"
+        );
+    }
+
     #[test]
     fn test_primitive_array_inline() {
         let config = ConversionConfig::default();
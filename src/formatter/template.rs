@@ -0,0 +1,502 @@
+//! TOON response templates from JSON Schema
+//!
+//! Renders a skeleton document for a JSON Schema, meant to be pasted into a
+//! prompt to show a model the exact TOON shape to answer with. Arrays of
+//! objects become tabular headers with their field lists and `[N]` in place of
+//! a length, and values become placeholders:
+//!
+//! | Schema | Placeholder |
+//! |--------|-------------|
+//! | `"type": "string"` | `<string>` (`<string:date>` with a `format`) |
+//! | `"type": "integer"` / `"number"` / `"boolean"` | `<integer>` / `<number>` / `<boolean>` |
+//! | `"type": ["string", "null"]` | `<string\|null>` |
+//! | `"enum": ["a", "b"]` | `<a\|b>` (`<a/b>` with the pipe delimiter) |
+//! | `"const": value` | The value itself |
+//!
+//! Local `$ref`s (`#/$defs/...`, `#/definitions/...`) are followed, `allOf`
+//! members are merged, and the first `anyOf`/`oneOf` option is used.
+
+use super::ToonFormatter;
+use crate::conversion::{ConversionConfig, DelimiterType};
+use crate::error::{FormattingError, FormattingResult};
+use serde_json::{Map, Value};
+
+/// Maximum `$ref` nesting followed before a schema is treated as recursive
+const MAX_REF_DEPTH: usize = 32;
+
+/// Maximum template nesting, which stops schemas that recurse through properties
+const MAX_NESTING: usize = 32;
+
+/// TOON template generator for JSON Schemas
+pub struct TemplateGenerator {
+    config: ConversionConfig,
+    formatter: ToonFormatter,
+}
+
+/// Shape a schema describes, after resolving references and combinators
+enum Shape {
+    Object(Vec<(String, Value)>),
+    Array(Value),
+    Primitive(String),
+}
+
+impl TemplateGenerator {
+    /// Create a new template generator with configuration
+    pub fn new(config: ConversionConfig) -> Self {
+        let formatter = ToonFormatter::new(config.clone());
+        Self { config, formatter }
+    }
+
+    /// Render a TOON skeleton for a JSON Schema
+    pub fn generate(&self, schema: &Value) -> FormattingResult<String> {
+        let mut lines = Vec::new();
+
+        match self.shape(schema, schema, 0)? {
+            Shape::Object(properties) => self.render_fields(schema, &properties, 0, &mut lines)?,
+            Shape::Array(items) => self.render_array(schema, "", &items, 0, 0, &mut lines)?,
+            Shape::Primitive(placeholder) => lines.push(placeholder),
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    fn indent(&self, depth: usize) -> String {
+        " ".repeat(depth * self.config.indent_size as usize)
+    }
+
+    fn delimiter(&self) -> &'static str {
+        self.config.delimiter.as_str()
+    }
+
+    /// Resolve a schema into the shape it describes
+    fn shape(&self, root: &Value, schema: &Value, depth: usize) -> FormattingResult<Shape> {
+        let schema = self.resolve(root, schema, depth)?;
+
+        if let Some(value) = schema.get("const") {
+            return Ok(Shape::Primitive(self.formatter.format_primitive(value)?));
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            return Ok(Shape::Primitive(self.enum_hint(values)));
+        }
+        for combinator in ["anyOf", "oneOf"] {
+            if let Some(first) = schema
+                .get(combinator)
+                .and_then(Value::as_array)
+                .and_then(|options| options.first())
+            {
+                return self.shape(root, first, depth + 1);
+            }
+        }
+
+        let types = schema_types(&schema);
+        let main_type = types
+            .iter()
+            .find(|t| *t != "null")
+            .map(String::as_str)
+            .or_else(|| {
+                if schema.get("properties").is_some() || schema.get("allOf").is_some() {
+                    Some("object")
+                } else if schema.get("items").is_some() {
+                    Some("array")
+                } else {
+                    None
+                }
+            });
+
+        match main_type {
+            Some("object") => Ok(Shape::Object(self.properties(root, &schema, depth)?)),
+            Some("array") => Ok(Shape::Array(
+                schema.get("items").cloned().unwrap_or(Value::Bool(true)),
+            )),
+            Some(primitive) => {
+                let mut name = match schema.get("format").and_then(Value::as_str) {
+                    Some(format) if primitive == "string" => format!("string:{}", format),
+                    _ => primitive.to_string(),
+                };
+                if types.iter().any(|t| t == "null") {
+                    name.push(self.hint_separator());
+                    name.push_str("null");
+                }
+                Ok(Shape::Primitive(format!("<{}>", name)))
+            }
+            None if types.iter().any(|t| t == "null") => Ok(Shape::Primitive("null".to_string())),
+            None => Ok(Shape::Primitive("<value>".to_string())),
+        }
+    }
+
+    /// Follow local `$ref`s
+    fn resolve(&self, root: &Value, schema: &Value, depth: usize) -> FormattingResult<Value> {
+        let Some(reference) = schema.get("$ref").and_then(Value::as_str) else {
+            return Ok(schema.clone());
+        };
+        if depth > MAX_REF_DEPTH {
+            return Err(FormattingError::schema(format!(
+                "Schema reference '{}' is recursive",
+                reference
+            )));
+        }
+
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .ok_or_else(|| {
+                FormattingError::schema(format!(
+                    "Cannot resolve schema reference '{}'; only local references are supported",
+                    reference
+                ))
+            })?;
+        self.resolve(root, target, depth + 1)
+    }
+
+    /// Collect object properties, merging `allOf` members
+    fn properties(
+        &self,
+        root: &Value,
+        schema: &Value,
+        depth: usize,
+    ) -> FormattingResult<Vec<(String, Value)>> {
+        let mut merged = Map::new();
+
+        if let Some(members) = schema.get("allOf").and_then(Value::as_array) {
+            for member in members {
+                let member = self.resolve(root, member, depth + 1)?;
+                if let Some(Value::Object(properties)) = member.get("properties") {
+                    merged.extend(properties.clone());
+                }
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            merged.extend(properties.clone());
+        }
+        // Required names without a declared schema still belong in the skeleton
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !merged.contains_key(name) {
+                    merged.insert(name.to_string(), Value::Bool(true));
+                }
+            }
+        }
+
+        Ok(merged.into_iter().collect())
+    }
+
+    fn hint_separator(&self) -> char {
+        match self.config.delimiter {
+            DelimiterType::Pipe => '/',
+            DelimiterType::Comma | DelimiterType::Tab => '|',
+        }
+    }
+
+    fn enum_hint(&self, values: &[Value]) -> String {
+        let options: Vec<String> = values
+            .iter()
+            .map(|value| match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        format!("<{}>", options.join(&self.hint_separator().to_string()))
+    }
+
+    /// Render object fields at a depth
+    fn render_fields(
+        &self,
+        root: &Value,
+        properties: &[(String, Value)],
+        depth: usize,
+        lines: &mut Vec<String>,
+    ) -> FormattingResult<()> {
+        if depth > MAX_NESTING {
+            return Err(FormattingError::schema(format!(
+                "Schema nests more than {} levels; recursive schemas cannot be templated",
+                MAX_NESTING
+            )));
+        }
+        for (name, schema) in properties {
+            let key = self.formatter.format_key(name)?;
+            match self.shape(root, schema, 0)? {
+                Shape::Object(nested) => {
+                    lines.push(format!("{}{}:", self.indent(depth), key));
+                    self.render_fields(root, &nested, depth + 1, lines)?;
+                }
+                Shape::Array(items) => {
+                    self.render_array(root, &key, &items, depth, 0, lines)?;
+                }
+                Shape::Primitive(placeholder) => {
+                    lines.push(format!("{}{}: {}", self.indent(depth), key, placeholder));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Render an array header (with `key` empty at the root or in list items)
+    /// and a sample entry
+    fn render_array(
+        &self,
+        root: &Value,
+        key: &str,
+        items: &Value,
+        depth: usize,
+        ref_depth: usize,
+        lines: &mut Vec<String>,
+    ) -> FormattingResult<()> {
+        let prefix = self.indent(depth);
+
+        match self.shape(root, items, ref_depth)? {
+            Shape::Primitive(placeholder) => {
+                lines.push(format!(
+                    "{}{}[N]: {}{}...",
+                    prefix,
+                    key,
+                    placeholder,
+                    self.delimiter()
+                ));
+            }
+            Shape::Object(fields) if self.is_tabular(root, &fields)? => {
+                let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                let cells = fields
+                    .iter()
+                    .map(|(_, schema)| match self.shape(root, schema, 0)? {
+                        Shape::Primitive(placeholder) => Ok(placeholder),
+                        Shape::Object(_) | Shape::Array(_) => {
+                            Err(FormattingError::invalid_structure(
+                                "Tabular fields must be primitive".to_string(),
+                            ))
+                        }
+                    })
+                    .collect::<FormattingResult<Vec<_>>>()?;
+                lines.push(format!(
+                    "{}{}{}:",
+                    prefix,
+                    key,
+                    self.formatter.tabular_header("N", &names)
+                ));
+                lines.push(format!(
+                    "{}{}",
+                    self.indent(depth + 1),
+                    cells.join(self.delimiter())
+                ));
+            }
+            Shape::Object(fields) => {
+                lines.push(format!("{}{}[N]:", prefix, key));
+                let start = lines.len();
+                self.render_fields(root, &fields, depth + 2, lines)?;
+                self.hyphenate(depth + 1, start, lines);
+            }
+            Shape::Array(nested) => {
+                lines.push(format!("{}{}[N]:", prefix, key));
+                let start = lines.len();
+                self.render_array(root, "", &nested, depth + 2, ref_depth + 1, lines)?;
+                self.hyphenate(depth + 1, start, lines);
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn the line at `start`, rendered one level deeper, into a `- ` list item
+    fn hyphenate(&self, depth: usize, start: usize, lines: &mut [String]) {
+        if let Some(line) = lines.get_mut(start) {
+            *line = format!("{}- {}", self.indent(depth), line.trim_start());
+        }
+    }
+
+    /// Check if all fields of an item schema are primitives
+    fn is_tabular(&self, root: &Value, fields: &[(String, Value)]) -> FormattingResult<bool> {
+        if fields.is_empty() {
+            return Ok(false);
+        }
+        for (_, schema) in fields {
+            if !matches!(self.shape(root, schema, 0)?, Shape::Primitive(_)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Types listed in a schema's `type` keyword
+fn schema_types(schema: &Value) -> Vec<String> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Render a TOON template for a JSON Schema
+pub fn generate_template(schema: &Value, config: &ConversionConfig) -> FormattingResult<String> {
+    TemplateGenerator::new(config.clone()).generate(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn template(schema: Value) -> String {
+        generate_template(&schema, &ConversionConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_object_placeholders_by_type() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer"},
+                "score": {"type": ["number", "null"]},
+                "active": {"type": "boolean"},
+                "created": {"type": "string", "format": "date-time"},
+                "address": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}}
+                }
+            }
+        });
+        assert_eq!(
+            template(schema),
+            "name: <string>\nage: <integer>\nscore: <number|null>\nactive: <boolean>\n\
+             created: <string:date-time>\naddress:\n  city: <string>"
+        );
+    }
+
+    #[test]
+    fn test_array_of_objects_becomes_table() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {"type": "integer"},
+                            "status": {"enum": ["active", "inactive"]},
+                            "kind": {"const": "user"}
+                        }
+                    }
+                },
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+        assert_eq!(
+            template(schema),
+            "users[N]{id,status,kind}:\n  <integer>,<active|inactive>,user\ntags[N]: <string>,..."
+        );
+    }
+
+    #[test]
+    fn test_nested_items_use_list_form() {
+        let schema = json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                }
+            }
+        });
+        assert_eq!(
+            template(schema),
+            "[N]:\n  - name: <string>\n    tags[N]: <string>,..."
+        );
+    }
+
+    #[test]
+    fn test_refs_and_all_of() {
+        let schema = json!({
+            "$defs": {
+                "id": {"type": "integer"},
+                "base": {"properties": {"id": {"$ref": "#/$defs/id"}}}
+            },
+            "allOf": [{"$ref": "#/$defs/base"}],
+            "properties": {"name": {"type": "string"}},
+            "required": ["name", "extra"]
+        });
+        assert_eq!(
+            template(schema),
+            "id: <integer>\nname: <string>\nextra: <value>"
+        );
+    }
+
+    #[test]
+    fn test_pipe_delimiter_changes_hint_separator() {
+        let config = ConversionConfig {
+            delimiter: DelimiterType::Pipe,
+            ..ConversionConfig::default()
+        };
+        let schema = json!({"properties": {"level": {"enum": ["low", "high"]}}});
+        assert_eq!(
+            generate_template(&schema, &config).unwrap(),
+            "level: <low/high>"
+        );
+    }
+
+    #[test]
+    fn test_table_header_matches_encoder() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {"type": "integer"},
+                            "full name": {"type": "string"}
+                        }
+                    }
+                }
+            }
+        });
+        let data = json!({"users": [{"id": 1, "full name": "Ann"}]});
+
+        for delimiter in [
+            DelimiterType::Comma,
+            DelimiterType::Pipe,
+            DelimiterType::Tab,
+        ] {
+            let config = ConversionConfig {
+                delimiter,
+                ..ConversionConfig::default()
+            };
+            let template = generate_template(&schema, &config).unwrap();
+            let encoded = crate::conversion::convert_json_to_toon(&data, &config)
+                .unwrap()
+                .content;
+
+            let header = |text: &str| text.lines().next().unwrap().replace("[1]", "[N]");
+            assert_eq!(header(&template), header(&encoded), "{:?}", delimiter);
+            let row = template.lines().nth(1).unwrap();
+            assert_eq!(
+                row.matches(delimiter.as_str()).count(),
+                1,
+                "{:?}",
+                delimiter
+            );
+        }
+    }
+
+    #[test]
+    fn test_recursive_properties_are_an_error() {
+        let schema = json!({
+            "$defs": {"node": {"properties": {"child": {"$ref": "#/$defs/node"}}}},
+            "$ref": "#/$defs/node"
+        });
+        let err = generate_template(&schema, &ConversionConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("recursive"));
+    }
+
+    #[test]
+    fn test_recursive_ref_is_an_error() {
+        let schema = json!({"$defs": {"a": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"});
+        let err = generate_template(&schema, &ConversionConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("recursive"));
+    }
+}
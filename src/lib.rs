@@ -8,6 +8,8 @@
 
 pub mod cli;
 pub mod conversion;
+pub mod decoder;
+pub mod document;
 pub mod error;
 pub mod formatter;
 pub mod parser;
@@ -15,6 +17,10 @@ pub mod validation;
 
 // Re-export commonly used types
 pub use conversion::{convert_json_to_toon, ConversionConfig, ConversionResult, ToonData};
+pub use decoder::reader::{ToonEvent, ToonReader};
+pub use decoder::{decode_toon, DecodeOptions, DecodedToon, ToonDecoder};
+pub use document::source_map::SourceMap;
+pub use document::{ArrayLayout, ToonArray, ToonDocument, ToonField, ToonNode, ToonObject};
 pub use error::{ConversionError, ConversionErrorKind, ParseError};
 pub use formatter::ToonFormatter;
 pub use parser::JsonSource;
@@ -2,207 +2,409 @@
 #![allow(dead_code)]
 
 use clap::Parser;
-use std::io::Read;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
 mod cli;
 mod conversion;
+mod decoder;
+mod document;
 mod error;
 mod formatter;
 mod parser;
 mod validation;
 
+use crate::cli::benchmark::TokenCounter;
+use crate::cli::report::{FileReport, RunReport};
+use crate::cli::summary::{FileStatistics, RunSummary};
+use crate::cli::{Args, CliConfig, Commands, ReportFormat};
+use crate::conversion::engine::{ConversionMetadata, ToonData};
+use crate::conversion::stats::SavingsThresholds;
 use crate::conversion::{convert_json_to_toon, ConversionConfig};
-use crate::parser::JsonSource;
+use crate::error::{ConversionError, ConversionErrorKind};
+use crate::parser::csv::CsvOptions;
+use crate::parser::toml::TomlOptions;
+use crate::parser::xml::XmlOptions;
+use crate::parser::{InputFormat, JsonSource};
 
-/// TOON (Token-Oriented Object Notation) Converter
-#[derive(Parser, Debug)]
-#[command(name = "toonconv")]
-#[command(about = "Convert JSON to TOON (Token-Oriented Object Notation) format")]
-#[command(version = "0.1.0")]
-struct CliArgs {
-    /// Input JSON source (string, file, or directory)
-    #[arg()]
-    input: Option<String>,
+fn main() -> Result<()> {
+    let cli = CliConfig::discover(Args::parse())?;
 
-    /// Output file path (default: stdout)
-    #[arg(short, long)]
-    output: Option<PathBuf>,
+    // Set up logging
+    if cli.is_verbose() {
+        eprintln!("Verbose mode enabled");
+        for file in &cli.project_files {
+            eprintln!("Using settings from {}", file.display());
+        }
+    }
 
-    /// Read JSON from standard input
-    #[arg(long)]
-    stdin: bool,
+    let result = run(&cli);
+    // Missed savings thresholds get their own exit code, so CI can tell them from errors
+    if let Err(e) = &result {
+        if let Some(error) = e.downcast_ref::<ConversionError>() {
+            if error.is_threshold_not_met() {
+                eprintln!("Error: {}", error);
+                std::process::exit(cli::THRESHOLD_EXIT_CODE);
+            }
+        }
+    }
+    result
+}
 
-    /// Recursively process directories
-    #[arg(long)]
-    recursive: bool,
+/// Dispatch to the requested subcommand, or convert the input
+fn run(cli: &CliConfig) -> Result<()> {
+    let args = &cli.args;
+    let config = &cli.conversion_config;
 
-    /// Spaces per indentation level (0-8, default: 2)
-    #[arg(long)]
-    indent: Option<u8>,
+    match &args.command {
+        Some(Commands::Validate { input, output }) => {
+            handle_validate(Some(input), output.as_deref(), args)
+        }
+        Some(Commands::Examples { output_dir, count }) => {
+            handle_examples(output_dir.as_deref(), *count, args, config)
+        }
+        Some(Commands::Benchmark {
+            input,
+            iterations,
+            output,
+            format,
+        }) => handle_benchmark(input, *iterations, output.as_deref(), *format, args, config),
+        Some(Commands::Check {
+            input,
+            schema,
+            lenient,
+        }) => handle_check(input, schema, *lenient, args),
+        Some(Commands::Template { schema, output }) => handle_template(
+            schema,
+            output.as_ref().or(args.output.as_ref()),
+            args,
+            config,
+        ),
+        Some(Commands::Set { file, path, value }) => handle_set(file, path, value, args),
+        Some(Commands::Profiles) => {
+            print!("{}", cli::describe_profiles());
+            Ok(())
+        }
+        None if cli.is_validate_only() => handle_validate(args.input.as_ref(), None, args),
+        None => handle_conversion(cli),
+    }
+}
 
-    /// Array delimiter: comma, tab, or pipe (default: comma)
-    #[arg(long)]
-    delimiter: Option<String>,
+/// Determine the input format from --input-format or the file extension
+fn resolve_input_format(args: &Args, path: Option<&Path>) -> Result<InputFormat> {
+    match args.input_format.as_deref() {
+        Some(format) => format
+            .parse::<InputFormat>()
+            .map_err(|e| anyhow::anyhow!(e)),
+        None => Ok(path
+            .and_then(InputFormat::from_path)
+            .unwrap_or(InputFormat::Json)),
+    }
+}
 
-    /// Include length markers in arrays (default: true)
-    #[arg(long, default_value_t = true)]
-    length_marker: bool,
+fn create_csv_options(args: &Args, format: InputFormat) -> Result<CsvOptions> {
+    let mut options = match format {
+        InputFormat::Tsv => CsvOptions::tsv(),
+        _ => CsvOptions::new(),
+    };
 
-    /// Disable pretty-printing
-    #[arg(long)]
-    plain: bool,
+    if let Some(delimiter) = args.csv_delimiter {
+        if !delimiter.is_ascii() {
+            return Err(anyhow::anyhow!(
+                "Invalid CSV delimiter '{}'. Use a single ASCII character",
+                delimiter
+            ));
+        }
+        options = options.with_delimiter(delimiter as u8);
+    }
 
-    /// Maximum memory usage limit (e.g., 100MB, default: 100MB)
-    #[arg(long)]
-    memory_limit: Option<String>,
+    Ok(options
+        .with_headers(!args.no_header)
+        .with_type_inference(!args.no_type_inference))
+}
 
-    /// Maximum processing time in seconds (default: 300)
-    #[arg(long)]
-    timeout: Option<u64>,
+/// Decode a TOON document and validate it against a JSON Schema
+fn handle_check(input: &str, schema_path: &Path, lenient: bool, args: &Args) -> Result<()> {
+    let label = if input == "-" {
+        "standard input"
+    } else {
+        input
+    };
+    let content = if input == "-" {
+        read_stdin()?
+    } else {
+        std::fs::read_to_string(input)
+            .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", input, e))?
+    };
+    let schema = read_schema(schema_path)?;
 
-    /// Use SIMD-optimized JSON parser
-    #[arg(long)]
-    simd: bool,
+    let options = if lenient {
+        crate::decoder::DecodeOptions::lenient()
+    } else {
+        crate::decoder::DecodeOptions::strict()
+    };
+    let decoded = crate::decoder::decode_toon(&content, &options)?;
+    let report = crate::validation::SchemaValidator::new(&schema)?.validate(&decoded);
 
-    /// Only validate JSON, don't convert
-    #[arg(long)]
-    validate_only: bool,
+    for issue in &report.issues {
+        match issue.severity {
+            crate::validation::toon_compliance::IssueSeverity::Error => {
+                crate::cli::CliUtils::show_error(&issue.to_string())
+            }
+            crate::validation::toon_compliance::IssueSeverity::Warning => {
+                crate::cli::CliUtils::show_warning(&issue.to_string(), args.quiet)
+            }
+        }
+    }
 
-    /// Output conversion statistics
-    #[arg(long)]
-    stats: bool,
+    if report.is_valid() {
+        crate::cli::CliUtils::show_success(&format!("{} matches the schema", label), args.quiet);
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} schema violation(s) in {}",
+            report.error_count(),
+            label
+        ))
+    }
+}
 
-    /// Enable verbose logging
-    #[arg(long)]
-    verbose: bool,
+/// Render a TOON skeleton for a JSON Schema
+fn handle_template(
+    schema_path: &Path,
+    output: Option<&PathBuf>,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<()> {
+    let schema = read_schema(schema_path)?;
+    let template = crate::formatter::template::generate_template(&schema, config)?;
 
-    /// Suppress non-error output
-    #[arg(long)]
-    quiet: bool,
+    if let Some(output_path) = output {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(output_path, format!("{}\n", template))?;
+        crate::cli::CliUtils::show_success(
+            &format!("Template written to: {}", output_path.display()),
+            args.quiet,
+        );
+    } else {
+        println!("{}", template);
+    }
 
-    /// Continue converting other files when one file fails
-    #[arg(long)]
-    continue_on_error: bool,
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let args = CliArgs::parse();
+/// Set one value in a TOON file, rewriting only the lines that hold it
+fn handle_set(file: &Path, path: &str, value: &str, args: &Args) -> Result<()> {
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", file.display(), e))?;
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+
+    let mut cst = crate::document::cst::ToonCst::parse(&content)?;
+    cst.set(&path_to_pointer(path), &value)?;
+
+    let output_path = args.output.as_deref().unwrap_or(file);
+    std::fs::write(output_path, cst.to_string())?;
+    crate::cli::CliUtils::show_success(
+        &format!("Set {} in {}", path, output_path.display()),
+        args.quiet,
+    );
+    Ok(())
+}
 
-    // Set up logging
-    if args.verbose {
-        eprintln!("Verbose mode enabled");
-    }
+/// Write the built-in example pairs
+fn handle_examples(
+    output_dir: Option<&Path>,
+    count: Option<u32>,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<()> {
+    let output_dir = output_dir.unwrap_or(Path::new(crate::cli::examples::DEFAULT_OUTPUT_DIR));
+    let written =
+        crate::cli::examples::write_examples(output_dir, count.map(|c| c as usize), config)?;
+
+    crate::cli::CliUtils::show_success(
+        &format!(
+            "Wrote {} example files to {}",
+            written.len(),
+            output_dir.display()
+        ),
+        args.quiet,
+    );
+    Ok(())
+}
 
-    // Create conversion configuration
-    let config = create_conversion_config(&args)?;
+/// Time repeated conversions of one input file and compare output formats
+fn handle_benchmark(
+    input: &str,
+    iterations: Option<u32>,
+    output: Option<&Path>,
+    report_format: cli::BenchmarkFormat,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<()> {
+    let path = Path::new(input);
+    let format = resolve_input_format(args, Some(path))?;
+    let json_value = if format.is_delimited() {
+        return Err(anyhow::anyhow!(
+            "Benchmarking streamed CSV/TSV input is not supported"
+        ));
+    } else if format.is_binary() {
+        crate::parser::binary::parse_binary(&std::fs::read(path)?, format)?
+    } else if format == InputFormat::Ndjson {
+        crate::parser::ndjson::parse_ndjson(&std::fs::read_to_string(path)?)?
+    } else {
+        parse_document(&std::fs::read_to_string(path)?, format, args)?
+    };
+
+    let report = crate::cli::benchmark::run_benchmark(
+        input,
+        &json_value,
+        iterations.unwrap_or(crate::cli::benchmark::DEFAULT_ITERATIONS),
+        config,
+    )?;
+    let rendered = match report_format {
+        cli::BenchmarkFormat::Markdown => report.to_markdown(),
+        cli::BenchmarkFormat::Json => report.to_json(),
+    };
 
-    // Handle different input sources
-    if args.validate_only {
-        handle_validation(&args, &config)
+    if let Some(output_path) = output {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(output_path, format!("{}\n", rendered))?;
+        crate::cli::CliUtils::show_success(
+            &format!("Benchmark written to: {}", output_path.display()),
+            args.quiet,
+        );
     } else {
-        handle_conversion(&args, &config)
+        println!("{}", rendered);
     }
+    Ok(())
 }
 
-fn create_conversion_config(args: &CliArgs) -> Result<ConversionConfig> {
-    let delimiter = match args.delimiter.as_deref() {
-        Some("tab") => crate::conversion::DelimiterType::Tab,
-        Some("pipe") => crate::conversion::DelimiterType::Pipe,
-        Some("comma") | None => crate::conversion::DelimiterType::Comma,
-        Some(other) => {
-            return Err(anyhow::anyhow!(
-                "Invalid delimiter '{}'. Use 'comma', 'tab', or 'pipe'",
-                other
-            ))
-        }
-    };
+/// Turn a dotted path (`users.0.name`) into a JSON Pointer; pointers are
+/// returned unchanged
+fn path_to_pointer(path: &str) -> String {
+    if path.is_empty() || path.starts_with('/') {
+        return path.to_string();
+    }
+    path.split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
 
-    let memory_limit = parse_memory_limit(&args.memory_limit)?;
-    let timeout = Duration::from_secs(args.timeout.unwrap_or(300));
-
-    Ok(ConversionConfig {
-        indent_size: args.indent.unwrap_or(2),
-        delimiter,
-        length_marker: args.length_marker,
-        quote_strings: crate::conversion::QuoteStrategy::Smart,
-        memory_limit,
-        timeout,
-        enable_simd: args.simd,
-        pretty: !args.plain,
-        validate_output: true,
-        include_schema: true,
-        max_depth: Some(1000),
-    })
+fn read_schema(schema_path: &Path) -> Result<serde_json::Value> {
+    let content = std::fs::read_to_string(schema_path)
+        .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", schema_path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid JSON in {}: {}", schema_path.display(), e))
 }
 
-fn parse_memory_limit(limit: &Option<String>) -> Result<usize> {
-    match limit {
-        None => Ok(100 * 1024 * 1024), // 100MB default
-        Some(limit_str) => {
-            // Parse memory limit string (e.g., "100MB", "1GB", "500KB")
-            if limit_str.ends_with("MB") {
-                let size = &limit_str[..limit_str.len() - 2];
-                Ok(size
-                    .parse::<f64>()
-                    .map(|mb| (mb * 1024.0 * 1024.0) as usize)?)
-            } else if limit_str.ends_with("KB") {
-                let size = &limit_str[..limit_str.len() - 2];
-                Ok(size.parse::<f64>().map(|kb| (kb * 1024.0) as usize)?)
-            } else if limit_str.ends_with("GB") {
-                let size = &limit_str[..limit_str.len() - 2];
-                Ok(size
-                    .parse::<f64>()
-                    .map(|gb| (gb * 1024.0 * 1024.0 * 1024.0) as usize)?)
-            } else if limit_str.ends_with("B") {
-                let size = &limit_str[..limit_str.len() - 1];
-                Ok(size.parse::<usize>()?)
+/// Validate a file, every input file in a directory, or standard input
+/// (when `input` is `None` and `--stdin` is given)
+fn handle_validate(input: Option<&String>, report: Option<&Path>, args: &Args) -> Result<()> {
+    let results = match input {
+        _ if args.stdin => vec![("standard input".to_string(), validate_stdin(args))],
+        Some(input) => {
+            let path = PathBuf::from(input);
+            if path.is_file() {
+                vec![(input.clone(), validate_file(&path, args))]
+            } else if path.is_dir() {
+                let results = validate_directory(&path, args)?;
+                if args.report_format.is_validation_only() {
+                    // CI annotations need paths that resolve from the working directory
+                    results
+                        .into_iter()
+                        .map(|(label, result)| (path.join(label).display().to_string(), result))
+                        .collect()
+                } else {
+                    results
+                }
             } else {
-                // Assume bytes
-                Ok(limit_str.parse::<usize>()?)
+                return Err(anyhow::anyhow!("Input path does not exist: {}", input));
             }
         }
-    }
-}
-
-fn handle_validation(args: &CliArgs, _config: &ConversionConfig) -> Result<()> {
-    // Implementation for JSON validation only
-    if args.stdin {
-        let json_str = read_stdin()?;
-        let _json_value = parse_json_validation(&json_str)?;
-        if !args.quiet {
-            println!("✓ Valid JSON");
+        None => {
+            return Err(anyhow::anyhow!(
+                "No input provided. Use --stdin or provide an input path"
+            ))
         }
-        Ok(())
-    } else if let Some(input) = &args.input {
-        let path = PathBuf::from(input);
-        if path.is_file() {
-            let json_str = std::fs::read_to_string(path)?;
-            let _json_value = parse_json_validation(&json_str)?;
-            if !args.quiet {
-                println!("✓ Valid JSON");
+    };
+
+    let text = if args.report_format == ReportFormat::Text {
+        let mut lines = Vec::new();
+        for (label, result) in &results {
+            match result {
+                Ok(()) => {
+                    crate::cli::CliUtils::show_success(label, args.quiet);
+                    lines.push(format!("✓ {}", label));
+                }
+                Err(e) => {
+                    crate::cli::CliUtils::show_error(&format!("{}: {}", label, e));
+                    lines.push(format!("✗ {}: {}", label, e));
+                }
             }
-            Ok(())
-        } else if path.is_dir() {
-            // Validate all JSON files in directory
-            validate_directory(&path, args.recursive)?;
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Input path does not exist: {}", input))
         }
+        lines.join("\n")
     } else {
-        Err(anyhow::anyhow!(
-            "No input provided. Use --stdin or provide an input path"
-        ))
+        let mut run = RunReport::new("validate");
+        for (label, result) in results.iter() {
+            run.push(match result {
+                Ok(()) => FileReport::valid(label.clone()),
+                Err(e) => FileReport::failed(label.clone(), None, e),
+            });
+        }
+        let rendered = match args.report_format {
+            ReportFormat::Junit => run.to_junit(),
+            ReportFormat::Sarif => run.to_sarif(),
+            _ => run.to_json(),
+        };
+        // The report goes to the report file when one is given, otherwise stdout
+        if report.is_none() {
+            println!("{}", rendered);
+        }
+        rendered
+    };
+
+    if let Some(report) = report {
+        if let Some(parent) = report.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(report, format!("{}\n", text))?;
     }
+
+    let invalid = results.iter().filter(|(_, result)| result.is_err()).count();
+    if invalid > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} input(s) failed validation",
+            invalid,
+            results.len()
+        ));
+    }
+    Ok(())
 }
 
-fn handle_conversion(args: &CliArgs, config: &ConversionConfig) -> Result<()> {
-    if args.stdin {
-        convert_stdin(args, config)
+fn handle_conversion(cli: &CliConfig) -> Result<()> {
+    let args = &cli.args;
+    let config = &cli.conversion_config;
+
+    if args.report_format.is_validation_only() {
+        return Err(
+            ConversionError::conversion(ConversionErrorKind::configuration(
+                "JUnit and SARIF reports are only supported by validate".to_string(),
+            ))
+            .into(),
+        );
+    }
+
+    let (label, converted) = if args.stdin {
+        ("<stdin>".to_string(), convert_stdin(args, config))
     } else if let Some(input) = &args.input {
         let path = PathBuf::from(input);
 
@@ -212,27 +414,62 @@ fn handle_conversion(args: &CliArgs, config: &ConversionConfig) -> Result<()> {
             || (trimmed.starts_with('[') && trimmed.ends_with(']'))
         {
             // Treat as JSON string
-            convert_string(input, args, config)
+            (
+                "<argument>".to_string(),
+                convert_string(input, args, config),
+            )
         } else if path.is_file() {
-            convert_file(&path, args, config)
+            (input.clone(), convert_file(&path, args, config))
         } else if path.is_dir() {
-            convert_directory(&path, args, config)
+            return convert_directory(&path, cli);
         } else {
-            Err(anyhow::anyhow!("Input path does not exist: {}", input))
+            return Err(anyhow::anyhow!("Input path does not exist: {}", input));
         }
     } else {
-        Err(anyhow::anyhow!(
+        return Err(anyhow::anyhow!(
             "No input provided. Use --stdin or provide an input path"
-        ))
+        ));
+    };
+
+    if args.json_report() {
+        let mut report = RunReport::new("convert");
+        report.push(match &converted {
+            Ok(toon_data) => FileReport::converted(label, args.output.clone(), toon_data),
+            Err(e) => FileReport::failed(label, args.output.clone(), e),
+        });
+        println!("{}", report.to_json());
     }
+    converted.map(|_| ())
 }
 
-fn convert_stdin(args: &CliArgs, config: &ConversionConfig) -> Result<()> {
-    let json_str = read_stdin()?;
-    convert_string(&json_str, args, config)
+fn convert_stdin(args: &Args, config: &ConversionConfig) -> Result<ToonData> {
+    let format = resolve_input_format(args, None)?;
+    if format.is_delimited() {
+        return convert_delimited(DelimitedInput::Stdin, format, args, config);
+    }
+    if format == InputFormat::Ndjson {
+        return convert_ndjson(std::io::stdin().lock(), args, config);
+    }
+    if format.is_binary() {
+        let mut buffer = Vec::new();
+        std::io::stdin().read_to_end(&mut buffer)?;
+        let json_value = crate::parser::binary::parse_binary(&buffer, format)?;
+        return convert_value(&json_value, None, args, config);
+    }
+
+    let content = read_stdin()?;
+    let json_value = parse_document(&content, format, args)?;
+    convert_value(&json_value, Some(&content), args, config)
 }
 
-fn convert_file(input_path: &PathBuf, args: &CliArgs, config: &ConversionConfig) -> Result<()> {
+fn convert_file(input_path: &PathBuf, args: &Args, config: &ConversionConfig) -> Result<ToonData> {
+    let format = resolve_input_format(args, Some(input_path))?;
+    if format.is_delimited() {
+        // Delimited input is streamed, so the memory limit does not apply to file size
+        let file = std::fs::File::open(input_path)?;
+        return convert_delimited(DelimitedInput::File(file), format, args, config);
+    }
+
     // Check file size before reading to avoid exhausting memory
     if let Ok(metadata) = std::fs::metadata(input_path) {
         if metadata.len() > config.memory_limit as u64 {
@@ -244,17 +481,101 @@ fn convert_file(input_path: &PathBuf, args: &CliArgs, config: &ConversionConfig)
         }
     }
 
-    let json_str = std::fs::read_to_string(input_path)?;
-    convert_string(&json_str, args, config)
+    if format == InputFormat::Ndjson {
+        let reader = BufReader::new(std::fs::File::open(input_path)?);
+        return convert_ndjson(reader, args, config);
+    }
+    if format.is_binary() {
+        let bytes = std::fs::read(input_path)?;
+        let json_value = crate::parser::binary::parse_binary(&bytes, format)?;
+        return convert_value(&json_value, None, args, config);
+    }
+
+    let content = std::fs::read_to_string(input_path)?;
+    let json_value = parse_document(&content, format, args)?;
+    convert_value(&json_value, Some(&content), args, config)
+}
+
+fn convert_string(json_str: &str, args: &Args, config: &ConversionConfig) -> Result<ToonData> {
+    // Parse JSON (leniently with --lenient or --input-format json5)
+    let format = match resolve_input_format(args, None)? {
+        InputFormat::Json5 => InputFormat::Json5,
+        _ => InputFormat::Json,
+    };
+    let json_value = parse_document(json_str, format, args)?;
+
+    convert_value(&json_value, Some(json_str), args, config)
 }
 
-fn convert_string(json_str: &str, args: &CliArgs, config: &ConversionConfig) -> Result<()> {
-    // Parse JSON
-    let json_source = JsonSource::String(json_str.to_string());
-    let json_value = json_source.parse()?;
+/// Convert NDJSON records, read line by line, into a single root array
+fn convert_ndjson<R: std::io::BufRead>(
+    reader: R,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<ToonData> {
+    let records = crate::parser::ndjson::read_ndjson(reader, args.continue_on_error)?;
+
+    for error in &records.invalid_lines {
+        eprintln!("✗ Skipped invalid record: {}", error);
+    }
+
+    convert_value(&records.into_value(), None, args, config)
+}
 
+/// Parse an in-memory document (JSON, XML or TOML) into a JSON value
+fn parse_document(content: &str, format: InputFormat, args: &Args) -> Result<serde_json::Value> {
+    match format {
+        InputFormat::Xml => {
+            let options = XmlOptions::new()
+                .with_attribute_prefix(&args.xml_attr_prefix)
+                .with_text_key(&args.xml_text_key);
+            Ok(crate::parser::xml::parse_xml(content, &options)?)
+        }
+        InputFormat::Toml => {
+            let mut options = TomlOptions::new();
+            if let Some(format) = &args.toml_date_format {
+                options = options.with_date_format(format);
+            }
+            Ok(crate::parser::toml::parse_toml(content, &options)?)
+        }
+        InputFormat::Ndjson => Ok(crate::parser::ndjson::parse_ndjson(content)?),
+        InputFormat::Json5 => parse_lenient_json(content, args),
+        InputFormat::Json if args.lenient => parse_lenient_json(content, args),
+        InputFormat::Json if args.repair => parse_repaired_json(content, args),
+        _ => Ok(JsonSource::String(content.to_string()).parse()?),
+    }
+}
+
+/// Parse possibly truncated JSON, reporting every repair that was applied
+fn parse_repaired_json(content: &str, args: &Args) -> Result<serde_json::Value> {
+    let repaired = crate::parser::repair::repair_json(content)?;
+    for repair in &repaired.repairs {
+        crate::cli::CliUtils::show_warning(&format!("Repaired: {}", repair), args.quiet);
+    }
+    Ok(repaired.value)
+}
+
+/// Parse JSON5/JSONC input, warning about any non-standard syntax it relied on
+fn parse_lenient_json(content: &str, args: &Args) -> Result<serde_json::Value> {
+    let parsed = crate::parser::lenient::parse_lenient(content)?;
+    if let Some(warning) = parsed.warning() {
+        crate::cli::CliUtils::show_warning(&warning, args.quiet);
+    }
+    Ok(parsed.value)
+}
+
+/// Convert a parsed value to TOON and write it to the requested destination;
+/// with a JSON report, output meant for stdout is left to the report.
+/// Savings thresholds compare against `source`, or pretty-printed JSON when
+/// the input was not text
+fn convert_value(
+    json_value: &serde_json::Value,
+    source: Option<&str>,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<ToonData> {
     // Convert to TOON
-    let toon_data = convert_json_to_toon(&json_value, config)?;
+    let toon_data = convert_json_to_toon(json_value, config)?;
 
     // Output result
     if let Some(output_path) = &args.output {
@@ -263,28 +584,248 @@ fn convert_string(json_str: &str, args: &CliArgs, config: &ConversionConfig) ->
             std::fs::create_dir_all(parent)?;
         }
         std::fs::write(output_path, &toon_data.content)?;
+        write_source_map(output_path, &toon_data)?;
 
-        if !args.quiet {
+        if !args.quiet && !args.json_report() {
             println!("✓ Converted to: {}", output_path.display());
         }
-    } else {
+    } else if !args.json_report() {
         // Write to stdout
         println!("{}", toon_data.content);
     }
 
     // Output statistics if requested
-    if args.stats {
+    if args.stats && !args.json_report() {
         output_statistics(&toon_data, args.quiet)?;
     }
 
-    Ok(())
+    let thresholds = args.savings_thresholds();
+    if thresholds.is_enabled() {
+        let pretty;
+        let source = match source {
+            Some(source) => source,
+            None => {
+                pretty = serde_json::to_string_pretty(json_value)?;
+                &pretty
+            }
+        };
+        let stats = FileStatistics::measure(
+            String::new(),
+            source,
+            &toon_data.content,
+            Default::default(),
+        );
+        check_savings(&stats, &thresholds)?;
+    }
+
+    Ok(toon_data)
+}
+
+/// Convert CSV/TSV input straight to a TOON table without a JSON intermediate
+///
+/// Savings thresholds are checked against tokens counted while the input and
+/// output stream through
+fn convert_delimited(
+    input: DelimitedInput,
+    format: InputFormat,
+    args: &Args,
+    config: &ConversionConfig,
+) -> Result<ToonData> {
+    let options = create_csv_options(args, format)?;
+    if config.source_map {
+        crate::cli::CliUtils::show_warning(
+            "Source maps are not written for streamed CSV/TSV input",
+            args.quiet,
+        );
+    }
+    let thresholds = args.savings_thresholds();
+    let count_tokens = thresholds.is_enabled();
+
+    let start = std::time::Instant::now();
+
+    // The table is only kept in memory when a JSON report needs it inline
+    let mut content = String::new();
+    let (input, output_size, output_tokens) = if let Some(output_path) = &args.output {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(std::fs::File::create(output_path)?);
+        let mut writer = TokenCounter::new(file).with_tokens(count_tokens);
+        let input = input.convert(&mut writer, &options, config, count_tokens)?;
+        writer.flush()?;
+
+        if !args.quiet && !args.json_report() {
+            println!("✓ Converted to: {}", output_path.display());
+        }
+        (input, writer.bytes(), writer.tokens())
+    } else if args.json_report() {
+        let mut buffer = Vec::new();
+        let input = input.convert(&mut buffer, &options, config, count_tokens)?;
+        content = String::from_utf8(buffer)?;
+        let output_tokens = count_tokens.then(|| crate::cli::benchmark::count_tokens(&content));
+        (input, content.len() as u64, output_tokens)
+    } else {
+        let stdout = BufWriter::new(std::io::stdout().lock());
+        let mut writer = TokenCounter::new(stdout).with_tokens(count_tokens);
+        let input = input.convert(&mut writer, &options, config, count_tokens)?;
+        writeln!(writer.inner_mut())?;
+        writer.flush()?;
+        (input, writer.bytes(), writer.tokens())
+    };
+    let schema = &input.schema;
+
+    if args.stats && !args.quiet && !args.json_report() {
+        println!("\nConversion Statistics:");
+        println!("Rows: {}", schema.row_count);
+        println!("Columns: {}", schema.columns.len());
+    }
+
+    if let (Some(input_tokens), Some(output_tokens)) = (input.tokens, output_tokens) {
+        let stats = FileStatistics {
+            path: String::new(),
+            input_bytes: input.bytes,
+            output_bytes: output_size,
+            input_tokens,
+            output_tokens,
+            processing_time_ms: 0.0,
+        };
+        check_savings(&stats, &thresholds)?;
+    }
+
+    Ok(ToonData::new(
+        content,
+        delimited_metadata(schema, input.bytes, output_size, start.elapsed()),
+    ))
+}
+
+/// Fail with the threshold error when a conversion misses `thresholds`
+fn check_savings(stats: &FileStatistics, thresholds: &SavingsThresholds) -> Result<()> {
+    let check = stats.check_savings(thresholds);
+    if check.is_success() {
+        return Ok(());
+    }
+    Err(
+        ConversionError::conversion(ConversionErrorKind::threshold_not_met(
+            check.failed.join("; "),
+        ))
+        .into(),
+    )
+}
+
+/// Conversion metadata for a table converted from delimited input
+fn delimited_metadata(
+    schema: &crate::parser::csv::CsvSchema,
+    input_size: u64,
+    output_size: u64,
+    elapsed: std::time::Duration,
+) -> ConversionMetadata {
+    let token_reduction = if input_size > 0 {
+        (input_size as f32 - output_size as f32) / input_size as f32 * 100.0
+    } else {
+        0.0
+    };
+    ConversionMetadata {
+        input_size,
+        output_size,
+        token_reduction,
+        processing_time_ms: elapsed.as_millis() as u64,
+        memory_peak_kb: 0,
+        schema_info: Some(schema.schema_info()),
+    }
 }
 
+/// Delimited input: a file is read twice, standard input is spooled to disk
+/// by the first pass since it cannot be rewound
+enum DelimitedInput {
+    File(std::fs::File),
+    Stdin,
+}
+
+/// Schema and size of converted delimited input
+struct ConvertedInput {
+    schema: crate::parser::csv::CsvSchema,
+    bytes: u64,
+    /// Only counted when requested
+    tokens: Option<usize>,
+}
+
+impl DelimitedInput {
+    /// Convert to `writer`, counting the input's tokens if `count_tokens`
+    fn convert<W: Write>(
+        self,
+        writer: &mut W,
+        options: &CsvOptions,
+        config: &ConversionConfig,
+        count_tokens: bool,
+    ) -> Result<ConvertedInput> {
+        match self {
+            Self::File(mut file) => {
+                let bytes = file.metadata()?.len();
+                // A separate pass, as the conversion reads the file twice
+                let tokens = if count_tokens {
+                    let mut counter = TokenCounter::new(BufReader::new(&file));
+                    std::io::copy(&mut counter, &mut std::io::sink())?;
+                    let tokens = counter.tokens();
+                    file.rewind()?;
+                    tokens
+                } else {
+                    None
+                };
+                let schema =
+                    crate::parser::csv::convert_csv(BufReader::new(file), writer, options, config)?;
+                Ok(ConvertedInput {
+                    schema,
+                    bytes,
+                    tokens,
+                })
+            }
+            Self::Stdin => {
+                let mut stdin =
+                    TokenCounter::new(std::io::stdin().lock()).with_tokens(count_tokens);
+                let (schema, bytes) =
+                    crate::parser::csv::convert_csv_spooled(&mut stdin, writer, options, config)?;
+                Ok(ConvertedInput {
+                    schema,
+                    bytes,
+                    tokens: stdin.tokens(),
+                })
+            }
+        }
+    }
+}
+
+/// Convert one file of a directory run, returning its statistics (without a
+/// path) with the result
 fn convert_single_file(
     input_path: &PathBuf,
     output_path: &PathBuf,
+    args: &Args,
     config: &ConversionConfig,
-) -> Result<()> {
+) -> Result<(FileStatistics, ToonData)> {
+    let start = std::time::Instant::now();
+    let format = resolve_input_format(args, Some(input_path))?;
+    if format.is_delimited() {
+        // Streamed from and to disk, so no memory limit applies
+        let options = create_csv_options(args, format)?;
+        let input = DelimitedInput::File(std::fs::File::open(input_path)?);
+        let file = BufWriter::new(std::fs::File::create(output_path)?);
+        let mut writer = TokenCounter::new(file);
+        let input = input.convert(&mut writer, &options, config, true)?;
+        writer.flush()?;
+
+        let stats = FileStatistics {
+            path: String::new(),
+            input_bytes: input.bytes,
+            output_bytes: writer.bytes(),
+            input_tokens: input.tokens.unwrap_or_default(),
+            output_tokens: writer.tokens().unwrap_or_default(),
+            processing_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+        };
+        let metadata =
+            delimited_metadata(&input.schema, input.bytes, writer.bytes(), start.elapsed());
+        return Ok((stats, ToonData::new(String::new(), metadata)));
+    }
+
     // Check file size before reading to avoid exhausting memory
     if let Ok(metadata) = std::fs::metadata(input_path) {
         if metadata.len() > config.memory_limit as u64 {
@@ -296,23 +837,38 @@ fn convert_single_file(
         }
     }
 
-    // Read JSON file
-    let json_str = std::fs::read_to_string(input_path)?;
-
-    // Parse JSON
-    let json_source = JsonSource::String(json_str.to_string());
-    let json_value = json_source.parse()?;
-
-    // Convert to TOON
-    let toon_data = convert_json_to_toon(&json_value, config)?;
+    let (source, toon_data) = if format.is_binary() {
+        let json_value = crate::parser::binary::parse_binary(&std::fs::read(input_path)?, format)?;
+        // Binary input has no text of its own, so savings are measured against pretty JSON
+        let source = serde_json::to_string_pretty(&json_value)?;
+        (source, convert_json_to_toon(&json_value, config)?)
+    } else {
+        let content = std::fs::read_to_string(input_path)?;
+        let json_value = parse_document(&content, format, args)?;
+        (content, convert_json_to_toon(&json_value, config)?)
+    };
 
     // Write to output file
     std::fs::write(output_path, &toon_data.content)?;
+    write_source_map(output_path, &toon_data)?;
+
+    let stats =
+        FileStatistics::measure(String::new(), &source, &toon_data.content, start.elapsed());
+    Ok((stats, toon_data))
+}
 
+/// Write the source map of a conversion, if one was built, next to its output
+fn write_source_map(output_path: &Path, toon_data: &crate::conversion::ToonData) -> Result<()> {
+    if let Some(source_map) = &toon_data.source_map {
+        let mut map_path = output_path.as_os_str().to_owned();
+        map_path.push(".map");
+        std::fs::write(PathBuf::from(map_path), source_map.to_json())?;
+    }
     Ok(())
 }
 
-fn convert_directory(input_dir: &PathBuf, args: &CliArgs, config: &ConversionConfig) -> Result<()> {
+fn convert_directory(input_dir: &PathBuf, cli: &CliConfig) -> Result<()> {
+    let args = &cli.args;
     let output_dir = args
         .output
         .as_ref()
@@ -321,21 +877,36 @@ fn convert_directory(input_dir: &PathBuf, args: &CliArgs, config: &ConversionCon
     // Create output directory
     std::fs::create_dir_all(output_dir)?;
 
-    // Find all JSON files (use parser utilities)
-    let json_files = crate::parser::directory::find_json_files(input_dir, args.recursive)
-        .map_err(|e| anyhow::anyhow!("Failed finding JSON files: {}", e))?;
+    // Find all input files (use parser utilities)
+    let json_files = crate::parser::directory::find_input_files(input_dir, &args.directory_walk())
+        .map_err(|e| anyhow::anyhow!("Failed finding input files: {}", e))?;
+
+    let targets = args.performance_targets()?;
+    let thresholds = args.savings_thresholds();
+    let mut summary = RunSummary::start();
+
+    // With a JSON report, results are collected instead of printed
+    let mut report = args.json_report().then(|| RunReport::new("convert"));
+    let show_progress = !args.quiet && report.is_none();
 
     if json_files.is_empty() {
-        if !args.quiet {
-            println!("No JSON files found in {}", input_dir.display());
+        if let Some(report) = &report {
+            println!("{}", report.to_json());
+        } else if !args.quiet {
+            println!("No input files found in {}", input_dir.display());
         }
         return Ok(());
     }
 
-    if !args.quiet {
-        println!("Found {} JSON files", json_files.len());
+    if show_progress {
+        println!("Found {} input files", json_files.len());
     }
 
+    // Settings per directory, so nested toonconv.toml files apply to their subtree
+    let mut configs = HashMap::new();
+    let json_files_count = json_files.len();
+    let (mut failed, mut below_threshold) = (0, 0);
+
     // Process files
     for json_file in json_files {
         let relative_path = json_file
@@ -352,72 +923,165 @@ fn convert_directory(input_dir: &PathBuf, args: &CliArgs, config: &ConversionCon
         }
 
         // Read and convert file directly
-        match convert_single_file(&json_file, &output_file, config) {
-            Ok(_) => {
-                if !args.quiet {
-                    println!("✓ {} -> {}", relative_path.display(), output_file.display());
+        let converted = directory_config(cli, &mut configs, &json_file)
+            .and_then(|config| convert_single_file(&json_file, &output_file, args, &config));
+        let label = relative_path.display().to_string();
+        match converted {
+            Ok((stats, toon_data)) => {
+                let stats = FileStatistics {
+                    path: label.clone(),
+                    ..stats
+                };
+                let savings = stats.check_savings(&thresholds);
+                summary.record(stats, toon_data.metadata.memory_peak_kb * 1024);
+
+                let mut file_report =
+                    FileReport::converted(label.clone(), Some(output_file.clone()), &toon_data);
+                if savings.is_success() {
+                    if show_progress {
+                        println!("✓ {} -> {}", label, output_file.display());
+                    }
+                } else {
+                    below_threshold += 1;
+                    let message = savings.failed.join("; ");
+                    if report.is_none() && !args.quiet {
+                        eprintln!("✗ {}: {}", label, message);
+                    }
+                    file_report = file_report.with_error(
+                        &ConversionError::conversion(ConversionErrorKind::threshold_not_met(
+                            message,
+                        ))
+                        .into(),
+                    );
+                }
+                if let Some(report) = &mut report {
+                    report.push(file_report);
                 }
             }
             Err(e) => {
-                eprintln!("✗ Error converting {}: {}", relative_path.display(), e);
+                failed += 1;
+                match &mut report {
+                    Some(report) => report.push(FileReport::failed(label, Some(output_file), &e)),
+                    None => eprintln!("✗ Error converting {}: {}", label, e),
+                }
                 if !args.continue_on_error {
+                    if let Some(report) = &report {
+                        println!("{}", report.to_json());
+                    }
                     return Err(anyhow::anyhow!("Aborting due to conversion error: {}", e));
                 }
             }
         }
     }
 
-    Ok(())
-}
+    let summary = summary.finish(&targets, &thresholds);
+    let run_below_threshold = summary
+        .savings
+        .as_ref()
+        .is_some_and(|check| !check.is_success());
+    if let Some(report) = &mut report {
+        report.summary = Some(summary);
+        println!("{}", report.to_json());
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} file(s) failed to convert",
+                failed,
+                json_files_count
+            ));
+        }
+    } else if !args.quiet {
+        print!("{}", summary.to_text());
+    }
 
-fn validate_directory(dir: &PathBuf, recursive: bool) -> Result<()> {
-    let json_files = find_json_files(dir, recursive)?;
+    let mut misses = Vec::new();
+    if below_threshold > 0 {
+        misses.push(format!(
+            "{} of {} file(s) below threshold",
+            below_threshold, json_files_count
+        ));
+    }
+    if run_below_threshold {
+        misses.push("overall token reduction below minimum".to_string());
+    }
+    if !misses.is_empty() {
+        return Err(
+            ConversionError::conversion(ConversionErrorKind::threshold_not_met(misses.join("; ")))
+                .into(),
+        );
+    }
 
-    for json_file in json_files {
-        let relative_path = json_file
-            .strip_prefix(dir)
-            .map_err(|_| anyhow::anyhow!("Failed to get relative path"))?;
+    Ok(())
+}
 
-        match std::fs::read_to_string(&json_file) {
-            Ok(content) => match parse_json_validation(&content) {
-                Ok(_) => println!("✓ {}", relative_path.display()),
-                Err(e) => eprintln!("✗ {}: {}", relative_path.display(), e),
-            },
-            Err(e) => eprintln!("✗ {}: {}", relative_path.display(), e),
-        }
+/// Conversion settings for a file in directory mode, looked up once per directory
+fn directory_config(
+    cli: &CliConfig,
+    configs: &mut HashMap<PathBuf, ConversionConfig>,
+    file: &Path,
+) -> Result<ConversionConfig> {
+    let dir = file.parent().unwrap_or(Path::new(".")).to_path_buf();
+    if let Some(config) = configs.get(&dir) {
+        return Ok(config.clone());
     }
+    let config = cli.config_for(&dir)?;
+    configs.insert(dir, config.clone());
+    Ok(config)
+}
 
-    Ok(())
+/// Validate every input file below `dir`, labelled by relative path
+fn validate_directory(dir: &PathBuf, args: &Args) -> Result<Vec<(String, Result<()>)>> {
+    let files = crate::parser::directory::find_input_files(dir, &args.directory_walk())
+        .map_err(|e| anyhow::anyhow!("Failed finding input files: {}", e))?;
+
+    Ok(files
+        .iter()
+        .map(|file| {
+            let label = file.strip_prefix(dir).unwrap_or(file).display().to_string();
+            (label, validate_file(file, args))
+        })
+        .collect())
 }
 
-fn find_json_files(dir: &PathBuf, recursive: bool) -> Result<Vec<PathBuf>> {
-    let mut json_files = Vec::new();
+fn validate_stdin(args: &Args) -> Result<()> {
+    let format = resolve_input_format(args, None)?;
+    if format.is_binary() {
+        let mut buffer = Vec::new();
+        std::io::stdin().read_to_end(&mut buffer)?;
+        crate::parser::binary::parse_binary(&buffer, format)?;
+        return Ok(());
+    }
+    validate_content(&read_stdin()?, format, args)
+}
 
-    if recursive {
-        for entry in walkdir::WalkDir::new(dir) {
-            let entry = entry?;
-            let path = entry.path();
+fn validate_file(path: &Path, args: &Args) -> Result<()> {
+    let format = resolve_input_format(args, Some(path))?;
+    if format.is_binary() {
+        crate::parser::binary::parse_binary(&std::fs::read(path)?, format)?;
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(path)?;
+    validate_content(&content, format, args)
+}
 
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
-                json_files.push(path.to_path_buf());
-            }
+fn validate_content(content: &str, format: InputFormat, args: &Args) -> Result<()> {
+    match format {
+        InputFormat::Json if !args.lenient && !args.repair => {
+            parse_json_validation(content).map(|_| ())
         }
-    } else {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
-                json_files.push(path);
-            }
+        InputFormat::Csv | InputFormat::Tsv => {
+            let options = create_csv_options(args, format)?;
+            crate::parser::csv::scan_csv(content.as_bytes(), &options)?;
+            Ok(())
         }
+        _ => parse_document(content, format, args).map(|_| ()),
     }
-
-    Ok(json_files)
 }
 
 fn parse_json_validation(json_str: &str) -> Result<serde_json::Value> {
-    serde_json::from_str(json_str).map_err(|e| anyhow::anyhow!("JSON parse error: {}", e))
+    serde_json::from_str(json_str).map_err(|e| {
+        let location = Some((e.line(), e.column()));
+        ConversionError::conversion(ConversionErrorKind::json_parse(e.to_string(), location)).into()
+    })
 }
 
 fn read_stdin() -> Result<String> {
@@ -460,27 +1124,12 @@ mod tests {
         let tmp = tempdir().unwrap();
         let output_path = tmp.path().join("nested/out.toon");
 
-        let args = CliArgs {
-            input: None,
-            output: Some(output_path.clone()),
-            stdin: false,
-            recursive: false,
-            indent: None,
-            delimiter: None,
-            length_marker: false,
-            plain: false,
-            memory_limit: None,
-            timeout: None,
-            simd: false,
-            validate_only: false,
-            stats: false,
-            verbose: false,
-            quiet: true,
-            continue_on_error: false,
-        };
+        let args = Args::parse_from(["toonconv", "--quiet", "-o", output_path.to_str().unwrap()]);
 
         let json = r#"{"message": "hello"}"#;
-        let cfg = create_conversion_config(&args).unwrap();
+        let cfg = CliConfig::from_args(args.clone())
+            .unwrap()
+            .conversion_config;
 
         // Convert - expect no error
         assert!(convert_string(json, &args, &cfg).is_ok());
@@ -502,26 +1151,17 @@ mod tests {
         use std::io::Write;
         f.write_all(&payload).unwrap();
 
-        let args = CliArgs {
-            input: Some(file_path.to_string_lossy().to_string()),
-            output: None,
-            stdin: false,
-            recursive: false,
-            indent: None,
-            delimiter: None,
-            length_marker: false,
-            plain: false,
-            memory_limit: Some("1KB".to_string()),
-            timeout: None,
-            simd: false,
-            validate_only: false,
-            stats: false,
-            verbose: false,
-            quiet: true,
-            continue_on_error: false,
-        };
-
-        let cfg = create_conversion_config(&args).unwrap();
+        let args = Args::parse_from([
+            "toonconv",
+            file_path.to_str().unwrap(),
+            "--quiet",
+            "--memory-limit",
+            "1KB",
+        ]);
+
+        let cfg = CliConfig::from_args(args.clone())
+            .unwrap()
+            .conversion_config;
 
         // convert_file uses metadata check which will fail due to limit
         if let Some(input) = &args.input {
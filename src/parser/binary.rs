@@ -0,0 +1,476 @@
+//! Binary JSON-like input parsing (MessagePack, CBOR and BSON)
+//!
+//! Binary formats are decoded and mapped onto JSON values before formatting.
+//! Values JSON can represent map directly; the remaining types use these
+//! text mappings:
+//!
+//! | Source type | Result |
+//! |-------------|--------|
+//! | Byte strings (all formats) | `"base64:<standard base64>"` |
+//! | Non-string map keys (MessagePack, CBOR) | The key rendered as JSON text |
+//! | Integers outside the 64-bit range (CBOR) | Decimal string |
+//! | Non-finite floats | `"inf"`, `"-inf"` or `"nan"` |
+//! | MessagePack extension | `{"$ext": <type>, "$data": "base64:..."}` |
+//! | CBOR tag 0 (date/time string) | The string |
+//! | CBOR tag 1 (epoch time) | RFC 3339 string in UTC |
+//! | CBOR tags 2/3 (bignums) | Number when it fits in 64 bits, else decimal string (base64 marker beyond 128 bits) |
+//! | CBOR tag 37 (UUID) | Hyphenated UUID string |
+//! | Other CBOR tags | `{"$tag": <tag>, "$value": <value>}` |
+//! | BSON ObjectId | 24-character hex string |
+//! | BSON date | RFC 3339 string in UTC with milliseconds |
+//! | BSON UUID binary (subtypes 3 and 4) | Hyphenated UUID string |
+//! | BSON Decimal128 | Decimal string |
+//! | BSON timestamp | `{"t": <seconds>, "i": <increment>}` |
+//! | BSON regular expression | `"/pattern/options"` |
+//! | BSON JavaScript code, symbol | The string |
+//! | BSON MinKey / MaxKey | `"$minKey"` / `"$maxKey"` |
+//! | BSON undefined | `null` |
+//! | Other BSON types | MongoDB relaxed extended JSON |
+//!
+//! Files holding several concatenated values (such as `mongodump` output)
+//! become a single root array, so uniform records render as a TOON table.
+//! BSON input always becomes an array of documents.
+
+use crate::error::{ParseError, ParseResult};
+use crate::parser::InputFormat;
+use base64::Engine;
+use serde_json::{Map, Number, Value};
+use std::io::Cursor;
+
+/// Prefix marking a string as base64-encoded binary data
+pub const BINARY_MARKER: &str = "base64:";
+
+/// Decode binary input in the given format into a JSON value
+pub fn parse_binary(bytes: &[u8], format: InputFormat) -> ParseResult<Value> {
+    match format {
+        InputFormat::MessagePack => parse_msgpack(bytes),
+        InputFormat::Cbor => parse_cbor(bytes),
+        InputFormat::Bson => parse_bson(bytes),
+        other => Err(ParseError::new(
+            format!("'{}' is not a binary input format", other.as_str()),
+            None,
+        )),
+    }
+}
+
+/// Decode MessagePack input (one value, or several concatenated values)
+pub fn parse_msgpack(bytes: &[u8]) -> ParseResult<Value> {
+    read_sequence(bytes, "MessagePack", |cursor| {
+        rmpv::decode::read_value(cursor)
+            .map(msgpack_to_json)
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Decode CBOR input (one value, or several concatenated values)
+pub fn parse_cbor(bytes: &[u8]) -> ParseResult<Value> {
+    read_sequence(bytes, "CBOR", |cursor| {
+        ciborium::de::from_reader::<ciborium::Value, _>(cursor)
+            .map(cbor_to_json)
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Decode BSON input (concatenated documents) into an array of documents
+pub fn parse_bson(bytes: &[u8]) -> ParseResult<Value> {
+    let mut cursor = Cursor::new(bytes);
+    let mut documents = Vec::new();
+
+    while (cursor.position() as usize) < bytes.len() {
+        let offset = cursor.position();
+        let document = bson::Document::from_reader(&mut cursor)
+            .map_err(|e| decode_error("BSON", offset, &e.to_string()))?;
+        documents.push(bson_document_to_json(document));
+    }
+
+    Ok(Value::Array(documents))
+}
+
+/// Read values until the input is exhausted, collapsing several into an array
+fn read_sequence<F>(bytes: &[u8], format_name: &str, mut read_one: F) -> ParseResult<Value>
+where
+    F: FnMut(&mut Cursor<&[u8]>) -> Result<Value, String>,
+{
+    if bytes.is_empty() {
+        return Err(ParseError::new(
+            format!("Empty {} input", format_name),
+            None,
+        ));
+    }
+
+    let mut cursor = Cursor::new(bytes);
+    let mut values = Vec::new();
+
+    while (cursor.position() as usize) < bytes.len() {
+        let offset = cursor.position();
+        let value = read_one(&mut cursor).map_err(|e| decode_error(format_name, offset, &e))?;
+        values.push(value);
+    }
+
+    if values.len() == 1 {
+        Ok(values.remove(0))
+    } else {
+        Ok(Value::Array(values))
+    }
+}
+
+fn decode_error(format_name: &str, offset: u64, reason: &str) -> ParseError {
+    ParseError::new(
+        format!(
+            "Invalid {} value starting at byte {}: {}",
+            format_name, offset, reason
+        ),
+        None,
+    )
+}
+
+/// Encode bytes as a marked base64 string
+pub fn encode_binary(bytes: &[u8]) -> Value {
+    Value::String(format!(
+        "{}{}",
+        BINARY_MARKER,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn float_to_json(f: f64) -> Value {
+    match Number::from_f64(f) {
+        Some(n) => Value::Number(n),
+        None if f.is_nan() => Value::String("nan".to_string()),
+        None if f.is_sign_negative() => Value::String("-inf".to_string()),
+        None => Value::String("inf".to_string()),
+    }
+}
+
+/// Format 16 bytes as a hyphenated UUID, if the length fits
+fn format_uuid(bytes: &[u8]) -> Option<String> {
+    if bytes.len() != 16 {
+        return None;
+    }
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+/// Render a non-string map key as text
+fn key_to_string(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(b) => Value::Bool(b),
+        rmpv::Value::Integer(i) => {
+            if let Some(n) = i.as_i64() {
+                Value::Number(n.into())
+            } else if let Some(n) = i.as_u64() {
+                Value::Number(n.into())
+            } else {
+                Value::Null
+            }
+        }
+        rmpv::Value::F32(f) => float_to_json(f as f64),
+        rmpv::Value::F64(f) => float_to_json(f),
+        rmpv::Value::String(s) => {
+            if s.is_str() {
+                Value::String(s.into_str().unwrap_or_default())
+            } else {
+                encode_binary(s.as_bytes())
+            }
+        }
+        rmpv::Value::Binary(bytes) => encode_binary(&bytes),
+        rmpv::Value::Array(items) => Value::Array(items.into_iter().map(msgpack_to_json).collect()),
+        rmpv::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key_to_string(msgpack_to_json(k)), msgpack_to_json(v)))
+                .collect(),
+        ),
+        rmpv::Value::Ext(ext_type, data) => {
+            let mut object = Map::new();
+            object.insert("$ext".to_string(), Value::Number(ext_type.into()));
+            object.insert("$data".to_string(), encode_binary(&data));
+            Value::Object(object)
+        }
+    }
+}
+
+fn cbor_integer_to_json(n: i128) -> Value {
+    if let Ok(i) = i64::try_from(n) {
+        Value::Number(i.into())
+    } else if let Ok(u) = u64::try_from(n) {
+        Value::Number(u.into())
+    } else {
+        Value::String(n.to_string())
+    }
+}
+
+fn cbor_to_json(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Integer(i) => cbor_integer_to_json(i128::from(i)),
+        ciborium::Value::Float(f) => float_to_json(f),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bytes(bytes) => encode_binary(&bytes),
+        ciborium::Value::Array(items) => {
+            Value::Array(items.into_iter().map(cbor_to_json).collect())
+        }
+        ciborium::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key_to_string(cbor_to_json(k)), cbor_to_json(v)))
+                .collect(),
+        ),
+        ciborium::Value::Tag(tag, inner) => cbor_tag_to_json(tag, *inner),
+        // ciborium::Value is non-exhaustive
+        _ => Value::Null,
+    }
+}
+
+fn cbor_tag_to_json(tag: u64, inner: ciborium::Value) -> Value {
+    match (tag, inner) {
+        // Standard date/time string
+        (0, ciborium::Value::Text(s)) => Value::String(s),
+        // Epoch-based date/time
+        (1, ciborium::Value::Integer(i)) => epoch_seconds(i128::from(i) as f64)
+            .unwrap_or_else(|| cbor_integer_to_json(i128::from(i))),
+        (1, ciborium::Value::Float(f)) => epoch_seconds(f).unwrap_or_else(|| float_to_json(f)),
+        // Unsigned and negative bignums
+        (2 | 3, ciborium::Value::Bytes(bytes)) if bytes.len() <= 16 => {
+            let magnitude = bytes.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128);
+            match (tag, i128::try_from(magnitude)) {
+                (2, Ok(n)) => cbor_integer_to_json(n),
+                (2, Err(_)) => Value::String(magnitude.to_string()),
+                (_, Ok(n)) => cbor_integer_to_json(-1 - n),
+                (_, Err(_)) => Value::String(negative_bignum(magnitude)),
+            }
+        }
+        (2 | 3, ciborium::Value::Bytes(bytes)) => encode_binary(&bytes),
+        // UUID
+        (37, ciborium::Value::Bytes(bytes)) => match format_uuid(&bytes) {
+            Some(uuid) => Value::String(uuid),
+            None => encode_binary(&bytes),
+        },
+        (tag, inner) => {
+            let mut object = Map::new();
+            object.insert("$tag".to_string(), Value::Number(tag.into()));
+            object.insert("$value".to_string(), cbor_to_json(inner));
+            Value::Object(object)
+        }
+    }
+}
+
+/// Value of a CBOR negative bignum, which encodes -1 - n
+fn negative_bignum(magnitude: u128) -> String {
+    match magnitude.checked_add(1) {
+        Some(n) => format!("-{}", n),
+        // -1 - (2^128 - 1)
+        None => "-340282366920938463463374607431768211456".to_string(),
+    }
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 UTC string
+fn epoch_seconds(seconds: f64) -> Option<Value> {
+    let millis = (seconds * 1000.0).round();
+    if !millis.is_finite() {
+        return None;
+    }
+    rfc3339_millis(millis as i64)
+}
+
+fn rfc3339_millis(millis: i64) -> Option<Value> {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|dt| Value::String(dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)))
+}
+
+fn bson_document_to_json(document: bson::Document) -> Value {
+    Value::Object(
+        document
+            .into_iter()
+            .map(|(k, v)| (k, bson_to_json(v)))
+            .collect(),
+    )
+}
+
+fn bson_to_json(value: bson::Bson) -> Value {
+    use bson::spec::BinarySubtype;
+    use bson::Bson;
+
+    match value {
+        Bson::Double(f) => float_to_json(f),
+        Bson::String(s) | Bson::JavaScriptCode(s) | Bson::Symbol(s) => Value::String(s),
+        Bson::Array(items) => Value::Array(items.into_iter().map(bson_to_json).collect()),
+        Bson::Document(document) => bson_document_to_json(document),
+        Bson::Boolean(b) => Value::Bool(b),
+        Bson::Null | Bson::Undefined => Value::Null,
+        Bson::RegularExpression(regex) => {
+            Value::String(format!("/{}/{}", regex.pattern, regex.options))
+        }
+        Bson::Int32(i) => Value::Number(i.into()),
+        Bson::Int64(i) => Value::Number(i.into()),
+        Bson::Timestamp(ts) => {
+            let mut object = Map::new();
+            object.insert("t".to_string(), Value::Number(ts.time.into()));
+            object.insert("i".to_string(), Value::Number(ts.increment.into()));
+            Value::Object(object)
+        }
+        Bson::Binary(binary) => match binary.subtype {
+            BinarySubtype::Uuid | BinarySubtype::UuidOld => match format_uuid(&binary.bytes) {
+                Some(uuid) => Value::String(uuid),
+                None => encode_binary(&binary.bytes),
+            },
+            _ => encode_binary(&binary.bytes),
+        },
+        Bson::ObjectId(oid) => Value::String(oid.to_hex()),
+        Bson::DateTime(dt) => rfc3339_millis(dt.timestamp_millis())
+            .unwrap_or(Value::Number(dt.timestamp_millis().into())),
+        Bson::Decimal128(d) => Value::String(d.to_string()),
+        Bson::MinKey => Value::String("$minKey".to_string()),
+        Bson::MaxKey => Value::String("$maxKey".to_string()),
+        other => other.into_relaxed_extjson(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use serde_json::json;
+
+    #[test]
+    fn test_msgpack_map_with_binary() {
+        let value = rmpv::Value::Map(vec![
+            (rmpv::Value::from("id"), rmpv::Value::from(7)),
+            (
+                rmpv::Value::from("blob"),
+                rmpv::Value::Binary(vec![1, 2, 3]),
+            ),
+            (rmpv::Value::from(1), rmpv::Value::from(true)),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &value).unwrap();
+
+        assert_eq!(
+            parse_msgpack(&bytes).unwrap(),
+            json!({"id": 7, "blob": "base64:AQID", "1": true})
+        );
+    }
+
+    #[test]
+    fn test_msgpack_sequence_becomes_table() {
+        let mut bytes = Vec::new();
+        for (id, name) in [(1, "a"), (2, "b")] {
+            let record = rmpv::Value::Map(vec![
+                (rmpv::Value::from("id"), rmpv::Value::from(id)),
+                (rmpv::Value::from("name"), rmpv::Value::from(name)),
+            ]);
+            rmpv::encode::write_value(&mut bytes, &record).unwrap();
+        }
+
+        let value = parse_binary(&bytes, InputFormat::MessagePack).unwrap();
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert_eq!(toon.content, "[2]{id,name}:\n  1,a\n  2,b");
+    }
+
+    #[test]
+    fn test_msgpack_ext() {
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &rmpv::Value::Ext(5, vec![0xff])).unwrap();
+        assert_eq!(
+            parse_msgpack(&bytes).unwrap(),
+            json!({"$ext": 5, "$data": "base64:/w=="})
+        );
+    }
+
+    #[test]
+    fn test_cbor_tags() {
+        let value = ciborium::Value::Map(vec![
+            (
+                ciborium::Value::from("at"),
+                ciborium::Value::Tag(1, Box::new(ciborium::Value::from(0))),
+            ),
+            (
+                ciborium::Value::from("big"),
+                ciborium::Value::Tag(
+                    2,
+                    Box::new(ciborium::Value::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])),
+                ),
+            ),
+            (
+                ciborium::Value::from("neg"),
+                ciborium::Value::Tag(3, Box::new(ciborium::Value::Bytes(vec![1]))),
+            ),
+            (
+                ciborium::Value::from("id"),
+                ciborium::Value::Tag(37, Box::new(ciborium::Value::Bytes(vec![0xab; 16]))),
+            ),
+            (
+                ciborium::Value::from("custom"),
+                ciborium::Value::Tag(99, Box::new(ciborium::Value::from("x"))),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+
+        assert_eq!(
+            parse_cbor(&bytes).unwrap(),
+            json!({
+                "at": "1970-01-01T00:00:00.000Z",
+                "big": "18446744073709551616",
+                "neg": -2,
+                "id": "abababab-abab-abab-abab-abababababab",
+                "custom": {"$tag": 99, "$value": "x"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_bson_documents() {
+        let oid = bson::oid::ObjectId::parse_str("507f1f77bcf86cd799439011").unwrap();
+        let mut bytes = Vec::new();
+        for n in 0..2 {
+            let document = bson::doc! {
+                "_id": oid,
+                "n": n,
+                "at": bson::DateTime::from_millis(1_700_000_000_000),
+                "data": bson::Binary { subtype: bson::spec::BinarySubtype::Generic, bytes: vec![104, 105] },
+            };
+            document.to_writer(&mut bytes).unwrap();
+        }
+
+        let value = parse_bson(&bytes).unwrap();
+        assert_eq!(
+            value[1],
+            json!({
+                "_id": "507f1f77bcf86cd799439011",
+                "n": 1,
+                "at": "2023-11-14T22:13:20.000Z",
+                "data": "base64:aGk="
+            })
+        );
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert!(toon.content.starts_with("[2]{_id,n,at,data}:"));
+    }
+
+    #[test]
+    fn test_truncated_input_reports_offset() {
+        let err = parse_cbor(&[0xa1, 0x61]).unwrap_err();
+        assert!(err
+            .message
+            .starts_with("Invalid CBOR value starting at byte 0"));
+
+        let err = parse_bson(&[5, 0, 0]).unwrap_err();
+        assert!(err.message.starts_with("Invalid BSON"));
+    }
+}
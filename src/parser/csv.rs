@@ -0,0 +1,492 @@
+//! CSV/TSV input conversion
+//!
+//! Delimited text maps directly onto a TOON tabular array, so rows are
+//! written straight out as `[N]{cols}:` without building a JSON value. The
+//! input is read twice: the first pass counts rows and infers a type for each
+//! column, the second pass streams the formatted rows to the writer. Input
+//! that cannot be rewound, such as standard input, is copied to a temporary
+//! file during the first pass.
+
+use crate::conversion::engine::{ArraySchema, SchemaInfo};
+use crate::conversion::{ConversionConfig, ConversionResult};
+use crate::error::{ConversionError, ConversionErrorKind, ParseError, ParseResult};
+use crate::formatter::ToonFormatter;
+use serde_json::{Number, Value};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+
+/// Options controlling how delimited input is read
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field delimiter of the input (independent of the TOON output delimiter)
+    pub delimiter: u8,
+    /// Treat the first record as the header row
+    pub has_headers: bool,
+    /// Infer numbers, booleans and nulls per column instead of keeping strings
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+            infer_types: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Create options for comma-separated input
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create options for tab-separated input
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Default::default()
+        }
+    }
+
+    /// Set the input field delimiter
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Enable or disable the header row
+    pub fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Enable or disable per-column type inference
+    pub fn with_type_inference(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    fn reader<R: Read>(&self, reader: R) -> csv::Reader<R> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .from_reader(reader)
+    }
+}
+
+/// Type inferred for a column from all of its non-empty cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    /// Every cell in the column was empty
+    Empty,
+    /// Every non-empty cell was `true` or `false`
+    Boolean,
+    /// Every non-empty cell was a valid JSON number
+    Number,
+    /// Anything else
+    String,
+}
+
+impl ColumnType {
+    /// Classify a single cell
+    fn of_cell(cell: &str) -> Self {
+        if cell.is_empty() {
+            ColumnType::Empty
+        } else if parse_bool(cell).is_some() {
+            ColumnType::Boolean
+        } else if cell.parse::<Number>().is_ok() {
+            ColumnType::Number
+        } else {
+            ColumnType::String
+        }
+    }
+
+    /// Combine the type seen so far with the type of another cell
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (ColumnType::Empty, other) => other,
+            (current, ColumnType::Empty) => current,
+            (current, other) if current == other => current,
+            _ => ColumnType::String,
+        }
+    }
+}
+
+/// Column layout of delimited input, gathered by the first pass
+#[derive(Debug, Clone)]
+pub struct CsvSchema {
+    /// Column names, from the header row or generated as `col1..colN`
+    pub columns: Vec<String>,
+    /// Inferred type of each column
+    pub column_types: Vec<ColumnType>,
+    /// Number of data rows (excluding the header)
+    pub row_count: usize,
+}
+
+impl CsvSchema {
+    /// The table as conversion schema information
+    pub fn schema_info(&self) -> SchemaInfo {
+        SchemaInfo {
+            array_count: 1,
+            uniform_arrays: vec![ArraySchema {
+                element_count: self.row_count,
+                field_count: Some(self.columns.len()),
+                field_names: Some(self.columns.clone()),
+            }],
+        }
+    }
+}
+
+/// Scan delimited input to collect column names, types and the row count
+pub fn scan_csv<R: Read>(reader: R, options: &CsvOptions) -> ParseResult<CsvSchema> {
+    let mut csv_reader = options.reader(reader);
+
+    let mut columns = if options.has_headers {
+        csv_reader
+            .headers()
+            .map_err(csv_parse_error)?
+            .iter()
+            .map(|h| h.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut column_types = vec![ColumnType::Empty; columns.len()];
+    let mut row_count = 0;
+    let mut record = csv::StringRecord::new();
+
+    while csv_reader
+        .read_record(&mut record)
+        .map_err(csv_parse_error)?
+    {
+        if row_count == 0 && !options.has_headers {
+            columns = (1..=record.len()).map(|i| format!("col{}", i)).collect();
+            column_types = vec![ColumnType::Empty; columns.len()];
+        }
+
+        for (column_type, cell) in column_types.iter_mut().zip(record.iter()) {
+            *column_type = column_type.merge(ColumnType::of_cell(cell));
+        }
+        row_count += 1;
+    }
+
+    Ok(CsvSchema {
+        columns,
+        column_types,
+        row_count,
+    })
+}
+
+/// Write delimited input as a TOON tabular array using a previously scanned schema
+pub fn write_csv_rows<R: Read, W: Write>(
+    reader: R,
+    schema: &CsvSchema,
+    options: &CsvOptions,
+    config: &ConversionConfig,
+    writer: &mut W,
+) -> ConversionResult<()> {
+    let formatter = ToonFormatter::new(config.clone());
+    let delimiter = config.delimiter.as_str();
+    let indent = " ".repeat(config.indent_size as usize);
+
+    if schema.columns.is_empty() {
+        return write_all(writer, "[0]:");
+    }
+
+    let fields = schema
+        .columns
+        .iter()
+        .map(|c| formatter.format_key(c))
+        .collect::<Result<Vec<_>, _>>()?;
+    write_all(
+        writer,
+        &format!("[{}]{{{}}}:", schema.row_count, fields.join(",")),
+    )?;
+
+    let mut csv_reader = options.reader(reader);
+    let mut record = csv::StringRecord::new();
+    let mut line = String::new();
+
+    while csv_reader
+        .read_record(&mut record)
+        .map_err(|e| ConversionError::ParseError(csv_parse_error(e)))?
+    {
+        line.clear();
+        line.push('\n');
+        line.push_str(&indent);
+
+        for (i, cell) in record.iter().enumerate() {
+            if i > 0 {
+                line.push_str(delimiter);
+            }
+            let value = cell_value(cell, schema.column_types[i], options.infer_types);
+            line.push_str(&formatter.format_primitive(&value)?);
+        }
+
+        write_all(writer, &line)?;
+    }
+
+    Ok(())
+}
+
+/// Convert seekable delimited input to TOON, streaming rows to `writer`
+///
+/// The reader is rewound after the scanning pass, so only one record is held
+/// in memory at a time regardless of the input size.
+pub fn convert_csv<R: Read + Seek, W: Write>(
+    mut reader: R,
+    writer: &mut W,
+    options: &CsvOptions,
+    config: &ConversionConfig,
+) -> ConversionResult<CsvSchema> {
+    let schema = scan_csv(&mut reader, options)?;
+
+    reader.seek(SeekFrom::Start(0)).map_err(|e| {
+        ConversionError::conversion(ConversionErrorKind::io(
+            format!("Failed to rewind CSV input: {}", e),
+            None,
+        ))
+    })?;
+
+    write_csv_rows(reader, &schema, options, config, writer)?;
+    Ok(schema)
+}
+
+/// Convert delimited input that cannot be rewound, streaming rows to `writer`
+///
+/// The first pass reads `reader` directly and copies it to an anonymous
+/// temporary file, which the second pass reads back, so memory use stays
+/// bounded by one record. Returns the schema and the number of input bytes.
+pub fn convert_csv_spooled<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    options: &CsvOptions,
+    config: &ConversionConfig,
+) -> ConversionResult<(CsvSchema, u64)> {
+    let spool_error = |e: std::io::Error| {
+        ConversionError::conversion(ConversionErrorKind::io(
+            format!("Failed to spool CSV input: {}", e),
+            None,
+        ))
+    };
+
+    let mut spool = tempfile::tempfile().map_err(spool_error)?;
+    let mut tee = TeeReader {
+        inner: reader,
+        copy: std::io::BufWriter::new(&mut spool),
+    };
+    let schema = scan_csv(&mut tee, options)?;
+    tee.copy.flush().map_err(spool_error)?;
+    drop(tee);
+
+    let input_size = spool.stream_position().map_err(spool_error)?;
+    spool.rewind().map_err(spool_error)?;
+    write_csv_rows(BufReader::new(spool), &schema, options, config, writer)?;
+    Ok((schema, input_size))
+}
+
+/// Reader that copies everything read through it to `copy`
+struct TeeReader<R: Read, W: Write> {
+    inner: R,
+    copy: W,
+}
+
+impl<R: Read, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.copy.write_all(&buf[..read])?;
+        Ok(read)
+    }
+}
+
+/// Convert in-memory delimited text to a TOON string
+pub fn csv_to_toon(
+    content: &str,
+    options: &CsvOptions,
+    config: &ConversionConfig,
+) -> ConversionResult<String> {
+    let mut output = Vec::new();
+    convert_csv(
+        Cursor::new(content.as_bytes()),
+        &mut output,
+        options,
+        config,
+    )?;
+
+    String::from_utf8(output).map_err(|e| {
+        ConversionError::conversion(ConversionErrorKind::UnsupportedEncoding {
+            encoding: e.to_string(),
+        })
+    })
+}
+
+/// Build the JSON value for a cell according to its column type
+fn cell_value(cell: &str, column_type: ColumnType, infer_types: bool) -> Value {
+    if !infer_types {
+        return Value::String(cell.to_string());
+    }
+
+    if cell.is_empty() {
+        return Value::Null;
+    }
+
+    match column_type {
+        ColumnType::Boolean => parse_bool(cell)
+            .map(Value::Bool)
+            .unwrap_or_else(|| Value::String(cell.to_string())),
+        ColumnType::Number => cell
+            .parse::<Number>()
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(cell.to_string())),
+        ColumnType::Empty | ColumnType::String => Value::String(cell.to_string()),
+    }
+}
+
+/// Parse a boolean cell, accepting `true`/`false` in any case
+fn parse_bool(cell: &str) -> Option<bool> {
+    if cell.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if cell.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Convert a csv error into a parse error with the record's line number
+fn csv_parse_error(error: csv::Error) -> ParseError {
+    let location = error.position().map(|pos| (pos.line() as usize, 1));
+
+    let message = match error.kind() {
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!(
+            "Invalid CSV record: found {} fields, expected {}",
+            len, expected_len
+        ),
+        csv::ErrorKind::Utf8 { .. } => "Invalid CSV: input is not valid UTF-8".to_string(),
+        _ => format!("Invalid CSV: {}", error),
+    };
+
+    ParseError::new(message, location)
+}
+
+fn write_all<W: Write>(writer: &mut W, content: &str) -> ConversionResult<()> {
+    writer.write_all(content.as_bytes()).map_err(|e| {
+        ConversionError::conversion(ConversionErrorKind::io(
+            format!("Failed to write TOON output: {}", e),
+            None,
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::DelimiterType;
+
+    fn convert(content: &str, options: &CsvOptions) -> String {
+        csv_to_toon(content, options, &ConversionConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_csv_to_tabular() {
+        let csv = "id,name,active\n1,Alice,true\n2,Bob,false\n";
+        let toon = convert(csv, &CsvOptions::default());
+        assert_eq!(toon, "[2]{id,name,active}:\n  1,Alice,true\n  2,Bob,false");
+    }
+
+    #[test]
+    fn test_column_type_inference() {
+        let csv = "zip,score,flag,note\n02134,1.50,TRUE,\n90210,2,false,x\n";
+        let toon = convert(csv, &CsvOptions::default());
+        // Leading zeros are not JSON numbers, so the zip column stays textual
+        // and its cells must be quoted to survive a round trip.
+        assert_eq!(
+            toon,
+            "[2]{zip,score,flag,note}:\n  \"02134\",1.5,true,null\n  \"90210\",2,false,x"
+        );
+    }
+
+    #[test]
+    fn test_mixed_column_falls_back_to_string() {
+        let csv = "value\n1\nabc\n";
+        let toon = convert(csv, &CsvOptions::default());
+        assert_eq!(toon, "[2]{value}:\n  \"1\"\n  abc");
+    }
+
+    #[test]
+    fn test_without_type_inference() {
+        let csv = "a,b\n1,\n";
+        let options = CsvOptions::default().with_type_inference(false);
+        assert_eq!(convert(csv, &options), "[1]{a,b}:\n  \"1\",\"\"");
+    }
+
+    #[test]
+    fn test_headerless_input() {
+        let csv = "1,2\n3,4\n";
+        let options = CsvOptions::default().with_headers(false);
+        assert_eq!(convert(csv, &options), "[2]{col1,col2}:\n  1,2\n  3,4");
+    }
+
+    #[test]
+    fn test_tsv_and_custom_delimiters() {
+        let tsv = "name\tcity\nAlice\tNew York, NY\n";
+        let toon = convert(tsv, &CsvOptions::tsv());
+        assert_eq!(toon, "[1]{name,city}:\n  Alice,\"New York, NY\"");
+
+        let semicolons = "a;b\n1;2\n";
+        let options = CsvOptions::default().with_delimiter(b';');
+        assert_eq!(convert(semicolons, &options), "[1]{a,b}:\n  1,2");
+    }
+
+    #[test]
+    fn test_output_delimiter_from_config() {
+        let config = ConversionConfig::default().with_delimiter(DelimiterType::Pipe);
+        let toon = csv_to_toon("a,b\nx,y\n", &CsvOptions::default(), &config).unwrap();
+        assert_eq!(toon, "[1]{a,b}:\n  x|y");
+    }
+
+    #[test]
+    fn test_spooled_input_matches_seekable() {
+        let csv = "id,name,score\n1,Alice,1.5\n2,Bob,x\n";
+        let mut output = Vec::new();
+        // A byte slice cannot seek, like standard input
+        let (schema, input_size) = convert_csv_spooled(
+            csv.as_bytes(),
+            &mut output,
+            &CsvOptions::default(),
+            &ConversionConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(schema.row_count, 2);
+        assert_eq!(input_size, csv.len() as u64);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            convert(csv, &CsvOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_header_only_and_empty_input() {
+        assert_eq!(convert("a,b\n", &CsvOptions::default()), "[0]{a,b}:");
+        assert_eq!(convert("", &CsvOptions::default()), "[0]:");
+    }
+
+    #[test]
+    fn test_ragged_row_reports_line() {
+        let csv = "a,b\n1,2\n3\n";
+        let err =
+            csv_to_toon(csv, &CsvOptions::default(), &ConversionConfig::default()).unwrap_err();
+        match err {
+            ConversionError::ParseError(parse) => {
+                assert_eq!(parse.location, Some((3, 1)));
+                assert!(parse.message.contains("expected 2"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
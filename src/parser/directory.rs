@@ -1,29 +1,286 @@
-use std::path::PathBuf;
-// use crate::error::ParseResult;
-use std::fs;
-use walkdir::WalkDir;
-
-/// Find JSON files in a directory. If recursive is true, use walkdir; otherwise list files.
-pub fn find_json_files(dir: &PathBuf, recursive: bool) -> Result<Vec<PathBuf>, std::io::Error> {
-    let mut json_files = Vec::new();
-
-    if recursive {
-        for entry in WalkDir::new(dir) {
-            let entry = entry?;
-            let path = entry.path();
-            if crate::parser::filter::is_json_file(path) {
-                json_files.push(path.to_path_buf());
-            }
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::parser::filter::{self, DEFAULT_EXTENSIONS};
+
+/// Name of the toonconv-specific ignore file, read like `.gitignore`
+pub const IGNORE_FILE: &str = ".toonignore";
+
+/// Name of the project configuration file
+pub const PROJECT_CONFIG_FILE: &str = "toonconv.toml";
+
+/// Files that configure toonconv or the walk itself, never taken as input
+const SETTINGS_FILES: [&str; 3] = [PROJECT_CONFIG_FILE, IGNORE_FILE, ".gitignore"];
+
+/// Which files a directory walk picks up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryWalk {
+    /// Descend into subdirectories
+    pub recursive: bool,
+    /// Deepest level to visit, where 1 is the directory's own files;
+    /// setting it implies `recursive`
+    pub max_depth: Option<usize>,
+    /// Follow symbolic links to directories
+    pub follow_symlinks: bool,
+    /// Only take files matching one of these globs (all files when empty)
+    pub include: Vec<String>,
+    /// Skip files and directories matching any of these globs
+    pub exclude: Vec<String>,
+    /// File extensions to take, without the dot
+    pub extensions: Vec<String>,
+    /// Honor `.gitignore` and `.toonignore` files
+    pub ignore_files: bool,
+}
+
+impl Default for DirectoryWalk {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            max_depth: None,
+            follow_symlinks: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            extensions: DEFAULT_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            ignore_files: true,
         }
-    } else {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if crate::parser::filter::is_json_file(&path) {
-                json_files.push(path);
+    }
+}
+
+impl DirectoryWalk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    pub fn with_include(mut self, patterns: Vec<String>) -> Self {
+        self.include = patterns;
+        self
+    }
+
+    pub fn with_exclude(mut self, patterns: Vec<String>) -> Self {
+        self.exclude = patterns;
+        self
+    }
+
+    /// Take files with these extensions as well as the defaults
+    pub fn with_extra_extensions(mut self, extensions: &[String]) -> Self {
+        for extension in extensions {
+            let extension = extension.trim_start_matches('.').to_lowercase();
+            if !self.extensions.contains(&extension) {
+                self.extensions.push(extension);
             }
         }
+        self
     }
 
-    Ok(json_files)
+    pub fn with_ignore_files(mut self, ignore_files: bool) -> Self {
+        self.ignore_files = ignore_files;
+        self
+    }
+
+    fn depth(&self) -> Option<usize> {
+        match (self.max_depth, self.recursive) {
+            (Some(depth), _) => Some(depth),
+            (None, true) => None,
+            (None, false) => Some(1),
+        }
+    }
+}
+
+/// Find the input files below `dir` selected by `walk`, sorted by path
+pub fn find_input_files(dir: &Path, walk: &DirectoryWalk) -> Result<Vec<PathBuf>, io::Error> {
+    // Globs follow .gitignore rules relative to `dir`. Excludes prune the
+    // walk; includes are matched separately so they never bring back files
+    // that an ignore file skips
+    let mut excludes = OverrideBuilder::new(dir);
+    for pattern in &walk.exclude {
+        excludes
+            .add(&format!("!{}", pattern))
+            .map_err(invalid_glob)?;
+    }
+    let excludes = excludes.build().map_err(invalid_glob)?;
+    let mut includes = OverrideBuilder::new(dir);
+    for pattern in &walk.include {
+        includes.add(pattern).map_err(invalid_glob)?;
+    }
+    let includes = includes.build().map_err(invalid_glob)?;
+
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .standard_filters(false)
+        .git_ignore(walk.ignore_files)
+        .git_exclude(walk.ignore_files)
+        .parents(walk.ignore_files)
+        .require_git(false)
+        .follow_links(walk.follow_symlinks)
+        .max_depth(walk.depth())
+        .overrides(excludes)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b));
+    if walk.ignore_files {
+        builder.add_custom_ignore_filename(IGNORE_FILE);
+    }
+
+    let mut files = Vec::new();
+    for entry in builder.build() {
+        let entry = entry.map_err(io::Error::other)?;
+        let included =
+            walk.include.is_empty() || includes.matched(entry.path(), false).is_whitelist();
+        let settings = SETTINGS_FILES.iter().any(|name| entry.file_name() == *name);
+        if included && !settings && filter::has_extension(entry.path(), &walk.extensions) {
+            files.push(entry.into_path());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Find JSON and TOML files in a directory, descending into subdirectories
+/// if `recursive` is true
+pub fn find_json_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, io::Error> {
+    find_input_files(dir, &DirectoryWalk::new().with_recursive(recursive))
+}
+
+fn invalid_glob(error: ignore::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn tree() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        for file in [
+            "top.json",
+            "map.geojson",
+            "notes.txt",
+            "a/mid.json",
+            "a/skip.json",
+            "a/b/deep.json",
+            "vendor/lib.json",
+        ] {
+            fs::write(root.join(file), "{}").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "vendor/\n").unwrap();
+        fs::write(root.join("a").join(IGNORE_FILE), "skip.json\n").unwrap();
+        tmp
+    }
+
+    fn names(dir: &Path, walk: &DirectoryWalk) -> Vec<String> {
+        find_input_files(dir, walk)
+            .unwrap()
+            .iter()
+            .map(|file| file.strip_prefix(dir).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_honors_ignore_files_and_depth() {
+        let tmp = tree();
+        let dir = tmp.path();
+
+        assert_eq!(names(dir, &DirectoryWalk::new()), ["top.json"]);
+        assert_eq!(
+            names(dir, &DirectoryWalk::new().with_recursive(true)),
+            ["a/b/deep.json", "a/mid.json", "top.json"]
+        );
+        assert_eq!(
+            names(dir, &DirectoryWalk::new().with_max_depth(Some(2))),
+            ["a/mid.json", "top.json"]
+        );
+        assert_eq!(
+            names(
+                dir,
+                &DirectoryWalk::new()
+                    .with_recursive(true)
+                    .with_ignore_files(false)
+            )
+            .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_walk_globs_and_extensions() {
+        let tmp = tree();
+        let dir = tmp.path();
+        let walk = DirectoryWalk::new().with_recursive(true);
+
+        assert_eq!(
+            names(dir, &walk.clone().with_exclude(vec!["b".to_string()])),
+            ["a/mid.json", "top.json"]
+        );
+        assert_eq!(
+            names(
+                dir,
+                &walk
+                    .clone()
+                    .with_include(vec!["a/**".to_string()])
+                    .with_exclude(vec!["deep.*".to_string()])
+            ),
+            ["a/mid.json"]
+        );
+        assert_eq!(
+            names(dir, &walk.with_extra_extensions(&[".geojson".to_string()])),
+            ["a/b/deep.json", "a/mid.json", "map.geojson", "top.json"]
+        );
+
+        let invalid = DirectoryWalk::new().with_include(vec!["a/{".to_string()]);
+        assert!(find_input_files(dir, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_walk_skips_settings_files() {
+        let tmp = tree();
+        let dir = tmp.path();
+        fs::write(dir.join(PROJECT_CONFIG_FILE), "indent = 4\n").unwrap();
+        fs::write(dir.join("a").join(PROJECT_CONFIG_FILE), "indent = 2\n").unwrap();
+        fs::write(dir.join("data.toml"), "a = 1\n").unwrap();
+
+        let walk = DirectoryWalk::new()
+            .with_recursive(true)
+            .with_extra_extensions(&["toonignore".to_string()]);
+        assert_eq!(
+            names(dir, &walk),
+            ["a/b/deep.json", "a/mid.json", "data.toml", "top.json"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_follows_symlinks_on_request() {
+        let tmp = tree();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("linked.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(outside.path(), tmp.path().join("link")).unwrap();
+
+        let walk = DirectoryWalk::new().with_recursive(true);
+        assert!(!names(tmp.path(), &walk).contains(&"link/linked.json".to_string()));
+        assert!(names(tmp.path(), &walk.with_follow_symlinks(true))
+            .contains(&"link/linked.json".to_string()));
+    }
 }
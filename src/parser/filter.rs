@@ -1,6 +1,36 @@
 use std::path::Path;
 
+/// Extensions picked up by directory conversion unless more are added
+pub const DEFAULT_EXTENSIONS: &[&str] = &["json", "toml"];
+
 /// Return true if the file has a .json extension and exists
 pub fn is_json_file(path: &Path) -> bool {
     path.is_file() && path.extension().is_some_and(|ext| ext == "json")
 }
+
+/// Return true if the file has a .toml extension and exists
+pub fn is_toml_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "toml")
+}
+
+/// Return true if the file is a document picked up by directory conversion
+pub fn is_input_file(path: &Path) -> bool {
+    is_json_file(path) || is_toml_file(path)
+}
+
+/// Return true if the file exists and its extension, compared without case
+/// or a leading dot, is one of `extensions`
+pub fn has_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                extensions.iter().any(|wanted| {
+                    wanted
+                        .as_ref()
+                        .trim_start_matches('.')
+                        .eq_ignore_ascii_case(ext)
+                })
+            })
+}
@@ -0,0 +1,198 @@
+//! Input format selection and detection
+//!
+//! JSON is the native input, but other formats can be converted to TOON
+//! directly. The format is either chosen explicitly (`--input-format`) or
+//! detected from the file extension.
+
+use std::path::Path;
+
+/// Supported input formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// A single JSON document
+    Json,
+    /// JSON with JSON5/JSONC extensions (comments, trailing commas, ...)
+    Json5,
+    /// Newline-delimited JSON (JSON Lines), one record per line
+    Ndjson,
+    /// Comma-separated values with an optional header row
+    Csv,
+    /// Tab-separated values with an optional header row
+    Tsv,
+    /// An XML document, mapped to JSON before formatting
+    Xml,
+    /// A TOML document, with dates rendered as strings
+    Toml,
+    /// MessagePack, one value or several concatenated values
+    MessagePack,
+    /// CBOR, one value or several concatenated values
+    Cbor,
+    /// BSON documents, as written by `mongodump`
+    Bson,
+}
+
+impl InputFormat {
+    /// Canonical name used on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputFormat::Json => "json",
+            InputFormat::Json5 => "json5",
+            InputFormat::Ndjson => "ndjson",
+            InputFormat::Csv => "csv",
+            InputFormat::Tsv => "tsv",
+            InputFormat::Xml => "xml",
+            InputFormat::Toml => "toml",
+            InputFormat::MessagePack => "msgpack",
+            InputFormat::Cbor => "cbor",
+            InputFormat::Bson => "bson",
+        }
+    }
+
+    /// Detect the format from a file extension (case-insensitive)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "json" => Some(InputFormat::Json),
+            "json5" | "jsonc" => Some(InputFormat::Json5),
+            "ndjson" | "jsonl" => Some(InputFormat::Ndjson),
+            "csv" => Some(InputFormat::Csv),
+            "tsv" | "tab" => Some(InputFormat::Tsv),
+            "xml" => Some(InputFormat::Xml),
+            "toml" => Some(InputFormat::Toml),
+            "msgpack" | "messagepack" | "mpk" => Some(InputFormat::MessagePack),
+            "cbor" => Some(InputFormat::Cbor),
+            "bson" => Some(InputFormat::Bson),
+            _ => None,
+        }
+    }
+
+    /// Detect the format from a path's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Check if this format is delimiter-separated text (CSV or TSV)
+    pub fn is_delimited(&self) -> bool {
+        matches!(self, InputFormat::Csv | InputFormat::Tsv)
+    }
+
+    /// Check if this format is binary (MessagePack, CBOR or BSON)
+    pub fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            InputFormat::MessagePack | InputFormat::Cbor | InputFormat::Bson
+        )
+    }
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept exactly the names recognized as file extensions
+        Self::from_extension(s).ok_or_else(|| {
+            format!(
+                "Invalid input format '{}'. Use 'json', 'json5', 'ndjson', 'csv', 'tsv', 'xml', 'toml', 'msgpack', 'cbor', or 'bson'",
+                s
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_input_format_from_str() {
+        assert_eq!(InputFormat::from_str("json").unwrap(), InputFormat::Json);
+        assert_eq!(InputFormat::from_str("CSV").unwrap(), InputFormat::Csv);
+        assert_eq!(InputFormat::from_str("tsv").unwrap(), InputFormat::Tsv);
+        assert_eq!(InputFormat::from_str("jsonl").unwrap(), InputFormat::Ndjson);
+        assert_eq!(InputFormat::from_str("XML").unwrap(), InputFormat::Xml);
+        assert_eq!(InputFormat::from_str("toml").unwrap(), InputFormat::Toml);
+        assert_eq!(InputFormat::from_str("jsonc").unwrap(), InputFormat::Json5);
+        assert_eq!(
+            InputFormat::from_str("msgpack").unwrap(),
+            InputFormat::MessagePack
+        );
+        assert_eq!(InputFormat::from_str("bson").unwrap(), InputFormat::Bson);
+        assert!(InputFormat::from_str("yaml").is_err());
+    }
+
+    #[test]
+    fn test_from_str_accepts_every_extension() {
+        for name in [
+            "json",
+            "json5",
+            "jsonc",
+            "ndjson",
+            "jsonl",
+            "csv",
+            "tsv",
+            "tab",
+            "xml",
+            "toml",
+            "msgpack",
+            "messagepack",
+            "mpk",
+            "cbor",
+            "bson",
+        ] {
+            assert_eq!(
+                InputFormat::from_str(name).ok(),
+                InputFormat::from_extension(name),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            InputFormat::from_str("mpk").unwrap(),
+            InputFormat::MessagePack
+        );
+    }
+
+    #[test]
+    fn test_input_format_from_path() {
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("data/users.csv")),
+            Some(InputFormat::Csv)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("export.TSV")),
+            Some(InputFormat::Tsv)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("config.json")),
+            Some(InputFormat::Json)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("events.jsonl")),
+            Some(InputFormat::Ndjson)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("feed.xml")),
+            Some(InputFormat::Xml)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("Cargo.toml")),
+            Some(InputFormat::Toml)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("cache.mpk")),
+            Some(InputFormat::MessagePack)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from("payload.cbor")),
+            Some(InputFormat::Cbor)
+        );
+        assert_eq!(
+            InputFormat::from_path(&PathBuf::from(".vscode/settings.jsonc")),
+            Some(InputFormat::Json5)
+        );
+        assert_eq!(InputFormat::from_path(&PathBuf::from("README")), None);
+    }
+}
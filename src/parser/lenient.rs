@@ -0,0 +1,492 @@
+//! Lenient JSON parsing (JSON5 / JSONC)
+//!
+//! Hand-written configuration and fixture files often use syntax that strict
+//! JSON rejects. The lenient parser accepts these extensions:
+//!
+//! - `//` line comments and `/* */` block comments
+//! - Trailing commas in arrays and objects
+//! - Single-quoted strings
+//! - Unquoted object keys (identifiers such as `name` or `$id`)
+//! - Hexadecimal integers (`0x1F`, `-0xff`)
+//!
+//! Each extension used is recorded with the location of its first use, so the
+//! caller can warn about input that is not portable JSON.
+
+use crate::error::{ParseError, ParseResult};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// A non-standard JSON extension accepted by the lenient parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LenientExtension {
+    Comments,
+    TrailingCommas,
+    SingleQuotes,
+    UnquotedKeys,
+    HexNumbers,
+}
+
+impl fmt::Display for LenientExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LenientExtension::Comments => "comments",
+            LenientExtension::TrailingCommas => "trailing commas",
+            LenientExtension::SingleQuotes => "single-quoted strings",
+            LenientExtension::UnquotedKeys => "unquoted keys",
+            LenientExtension::HexNumbers => "hexadecimal numbers",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Result of a lenient parse
+#[derive(Debug, Clone)]
+pub struct LenientParse {
+    /// Parsed value
+    pub value: Value,
+    /// Extensions used, each with the (line, column) of its first use
+    pub extensions: Vec<(LenientExtension, (usize, usize))>,
+}
+
+impl LenientParse {
+    /// Check if the input was strict JSON
+    pub fn is_strict(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    /// Warning listing the accepted extensions, if any were used
+    pub fn warning(&self) -> Option<String> {
+        if self.is_strict() {
+            return None;
+        }
+
+        let used: Vec<String> = self
+            .extensions
+            .iter()
+            .map(|(extension, (line, _))| format!("{} (line {})", extension, line))
+            .collect();
+        Some(format!("Accepted non-standard JSON: {}", used.join(", ")))
+    }
+}
+
+/// Parse JSON, accepting the JSON5/JSONC extensions listed in the module docs
+pub fn parse_lenient(content: &str) -> ParseResult<LenientParse> {
+    let mut parser = LenientParser {
+        input: content.as_bytes(),
+        content,
+        pos: 0,
+        extensions: Vec::new(),
+    };
+
+    parser.skip_insignificant()?;
+    if parser.peek().is_none() {
+        return Err(ParseError::new("Empty JSON string".to_string(), None));
+    }
+
+    let value = parser.parse_value()?;
+    parser.skip_insignificant()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("Invalid JSON: trailing characters"));
+    }
+
+    let mut extensions = parser.extensions;
+    extensions.sort_by_key(|(extension, _)| *extension);
+
+    Ok(LenientParse { value, extensions })
+}
+
+struct LenientParser<'a> {
+    input: &'a [u8],
+    content: &'a str,
+    pos: usize,
+    extensions: Vec<(LenientExtension, (usize, usize))>,
+}
+
+impl LenientParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.input.get(self.pos + offset).copied()
+    }
+
+    /// 1-based (line, column) of a byte offset
+    fn location_of(&self, pos: usize) -> (usize, usize) {
+        let before = &self.content[..pos.min(self.content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |p| p + 1)..]
+            .chars()
+            .count()
+            + 1;
+        (line, column)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let location = self.location_of(self.pos);
+        let line = self.content.lines().nth(location.0 - 1).unwrap_or("");
+        ParseError::new(message.to_string(), Some(location)).with_preview(line.trim().to_string())
+    }
+
+    /// Record an extension the first time it is used
+    fn record(&mut self, extension: LenientExtension, pos: usize) {
+        if !self.extensions.iter().any(|(e, _)| *e == extension) {
+            let location = self.location_of(pos);
+            self.extensions.push((extension, location));
+        }
+    }
+
+    /// Skip whitespace and comments
+    fn skip_insignificant(&mut self) -> ParseResult<()> {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(b' ' | b'\t' | b'\n' | b'\r'), _) => self.pos += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    self.record(LenientExtension::Comments, self.pos);
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    self.record(LenientExtension::Comments, self.pos);
+                    let start = self.pos;
+                    self.pos += 2;
+                    loop {
+                        match (self.peek(), self.peek_at(1)) {
+                            (Some(b'*'), Some(b'/')) => {
+                                self.pos += 2;
+                                break;
+                            }
+                            (Some(_), _) => self.pos += 1,
+                            (None, _) => {
+                                self.pos = start;
+                                return Err(self.error("Invalid JSON: unterminated block comment"));
+                            }
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> ParseResult<Value> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(quote @ (b'"' | b'\'')) => self.parse_string(quote).map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't') if self.input[self.pos..].starts_with(b"true") => {
+                self.pos += 4;
+                Ok(Value::Bool(true))
+            }
+            Some(b'f') if self.input[self.pos..].starts_with(b"false") => {
+                self.pos += 5;
+                Ok(Value::Bool(false))
+            }
+            Some(b'n') if self.input[self.pos..].starts_with(b"null") => {
+                self.pos += 4;
+                Ok(Value::Null)
+            }
+            Some(_) => Err(self.error("Invalid JSON: expected value")),
+            None => Err(self.error("Invalid JSON: unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self) -> ParseResult<Value> {
+        self.pos += 1;
+        let mut object = Map::new();
+
+        loop {
+            self.skip_insignificant()?;
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(object));
+                }
+                None => return Err(self.error("Invalid JSON: unterminated object")),
+                _ => {}
+            }
+
+            let key = match self.peek() {
+                Some(quote @ (b'"' | b'\'')) => self.parse_string(quote)?,
+                _ => self.parse_identifier()?,
+            };
+
+            self.skip_insignificant()?;
+            if self.peek() != Some(b':') {
+                return Err(self.error("Invalid JSON: expected ':' after object key"));
+            }
+            self.pos += 1;
+            self.skip_insignificant()?;
+
+            let value = self.parse_value()?;
+            object.insert(key, value);
+
+            self.skip_insignificant()?;
+            match self.peek() {
+                Some(b',') => {
+                    let comma = self.pos;
+                    self.pos += 1;
+                    self.skip_insignificant()?;
+                    if self.peek() == Some(b'}') {
+                        self.record(LenientExtension::TrailingCommas, comma);
+                    }
+                }
+                Some(b'}') => {}
+                _ => return Err(self.error("Invalid JSON: expected ',' or '}' in object")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> ParseResult<Value> {
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_insignificant()?;
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                None => return Err(self.error("Invalid JSON: unterminated array")),
+                _ => {}
+            }
+
+            items.push(self.parse_value()?);
+
+            self.skip_insignificant()?;
+            match self.peek() {
+                Some(b',') => {
+                    let comma = self.pos;
+                    self.pos += 1;
+                    self.skip_insignificant()?;
+                    if self.peek() == Some(b']') {
+                        self.record(LenientExtension::TrailingCommas, comma);
+                    }
+                }
+                Some(b']') => {}
+                _ => return Err(self.error("Invalid JSON: expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    /// Parse an unquoted key: letters, digits, `_` and `$`, not starting with a digit
+    fn parse_identifier(&mut self) -> ParseResult<String> {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if b.is_ascii_alphanumeric() || b == b'_' || b == b'$' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        if self.pos == start || self.input[start].is_ascii_digit() {
+            self.pos = start;
+            return Err(self.error("Invalid JSON: expected object key"));
+        }
+
+        self.record(LenientExtension::UnquotedKeys, start);
+        Ok(self.content[start..self.pos].to_string())
+    }
+
+    fn parse_string(&mut self, quote: u8) -> ParseResult<String> {
+        let start = self.pos;
+        if quote == b'\'' {
+            self.record(LenientExtension::SingleQuotes, start);
+        }
+        self.pos += 1;
+
+        let mut result = String::new();
+        let mut run_start = self.pos;
+
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => {
+                    self.pos = start;
+                    return Err(self.error("Invalid JSON: unterminated string"));
+                }
+                Some(b) if b == quote => {
+                    result.push_str(&self.content[run_start..self.pos]);
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some(b'\\') => {
+                    result.push_str(&self.content[run_start..self.pos]);
+                    self.pos += 1;
+                    self.parse_escape(&mut result)?;
+                    run_start = self.pos;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, out: &mut String) -> ParseResult<()> {
+        let escaped = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\'') => '\'',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.pos += 1;
+                let high = self.parse_hex4()?;
+                let c = if (0xD800..0xDC00).contains(&high) {
+                    // Surrogate pair
+                    if self.peek() != Some(b'\\') || self.peek_at(1) != Some(b'u') {
+                        return Err(self.error("Invalid JSON: unpaired surrogate in string"));
+                    }
+                    self.pos += 2;
+                    let low = self.parse_hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("Invalid JSON: unpaired surrogate in string"));
+                    }
+                    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                } else {
+                    char::from_u32(high)
+                };
+                out.push(c.ok_or_else(|| self.error("Invalid JSON: invalid unicode escape"))?);
+                return Ok(());
+            }
+            _ => return Err(self.error("Invalid JSON: invalid escape in string")),
+        };
+        out.push(escaped);
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_hex4(&mut self) -> ParseResult<u32> {
+        let digits = self
+            .content
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("Invalid JSON: invalid unicode escape"))?;
+        // Four hex digits always fit, and a sign was rejected above
+        let value = u32::from_str_radix(digits, 16).expect("four hex digits");
+        self.pos += 4;
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> ParseResult<Value> {
+        let start = self.pos;
+        let negative = self.peek() == Some(b'-');
+        let digits_start = if negative { start + 1 } else { start };
+
+        if self.input.get(digits_start) == Some(&b'0')
+            && matches!(self.input.get(digits_start + 1), Some(b'x' | b'X'))
+        {
+            self.record(LenientExtension::HexNumbers, start);
+            self.pos = digits_start + 2;
+            while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+                self.pos += 1;
+            }
+            let hex = &self.content[digits_start + 2..self.pos];
+            let magnitude = u64::from_str_radix(hex, 16)
+                .map_err(|_| self.error("Invalid JSON: invalid hexadecimal number"))?;
+            return if negative {
+                // Negate as unsigned so -0x8000000000000000 reaches i64::MIN
+                0i64.checked_sub_unsigned(magnitude)
+                    .map(|n| Value::Number(n.into()))
+                    .ok_or_else(|| self.error("Invalid JSON: hexadecimal number out of range"))
+            } else {
+                Ok(Value::Number(magnitude.into()))
+            };
+        }
+
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.pos += 1;
+        }
+
+        // Defer to serde_json for the standard number grammar and precision
+        serde_json::from_str::<Number>(&self.content[start..self.pos])
+            .map(Value::Number)
+            .map_err(|_| {
+                self.pos = start;
+                self.error("Invalid JSON: invalid number")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_strict_json_has_no_warning() {
+        let parsed = parse_lenient(r#"{"a": [1, 2.5, -3e2], "b": "x\ny", "c": null}"#).unwrap();
+        assert_eq!(
+            parsed.value,
+            json!({"a": [1, 2.5, -300.0], "b": "x\ny", "c": null})
+        );
+        assert!(parsed.warning().is_none());
+    }
+
+    #[test]
+    fn test_all_extensions() {
+        let input = r#"{
+  // editor settings
+  fontSize: 0x0E,
+  'theme': 'dark',
+  /* block */ "rulers": [80, 120,],
+}"#;
+        let parsed = parse_lenient(input).unwrap();
+        assert_eq!(
+            parsed.value,
+            json!({"fontSize": 14, "theme": "dark", "rulers": [80, 120]})
+        );
+        assert_eq!(
+            parsed.warning().unwrap(),
+            "Accepted non-standard JSON: comments (line 2), trailing commas (line 5), \
+             single-quoted strings (line 4), unquoted keys (line 3), hexadecimal numbers (line 3)"
+        );
+    }
+
+    #[test]
+    fn test_escapes_in_single_quotes() {
+        let parsed = parse_lenient(r#"['it\'s', "é😀"]"#).unwrap();
+        assert_eq!(parsed.value, json!(["it's", "é😀"]));
+    }
+
+    #[test]
+    fn test_comment_markers_inside_strings() {
+        let parsed = parse_lenient(r#"{"url": "http://example.com/*x*/"}"#).unwrap();
+        assert_eq!(parsed.value, json!({"url": "http://example.com/*x*/"}));
+        assert!(parsed.is_strict());
+    }
+
+    #[test]
+    fn test_errors_report_location() {
+        let err = parse_lenient("{\n  a: 1\n  b: 2\n}").unwrap_err();
+        assert_eq!(err.location, Some((3, 3)));
+        assert!(err.message.contains("expected ',' or '}'"));
+
+        assert!(parse_lenient("[1, /* open").is_err());
+        assert!(parse_lenient("{1: true}").is_err());
+        assert!(parse_lenient("   ").is_err());
+    }
+
+    #[test]
+    fn test_hex_number_range() {
+        let parsed = parse_lenient("[-0x8000000000000000, 0xFFFFFFFFFFFFFFFF]").unwrap();
+        assert_eq!(parsed.value, json!([i64::MIN, u64::MAX]));
+        assert!(parse_lenient("[-0x8000000000000001]").is_err());
+    }
+
+    #[test]
+    fn test_unicode_escape_rejects_sign() {
+        assert_eq!(parse_lenient(r#""\u0041""#).unwrap().value, json!("A"));
+        assert!(parse_lenient(r#""\u+041""#).is_err());
+        assert!(parse_lenient(r#""\u-041""#).is_err());
+    }
+}
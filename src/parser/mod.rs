@@ -1,9 +1,19 @@
 //! JSON parsing and validation module
 
+pub mod binary;
+pub mod csv;
 pub mod directory;
 pub mod filter;
+pub mod format;
+pub mod lenient;
+pub mod ndjson;
 pub mod recursive;
+pub mod repair;
+pub mod toml;
 pub mod validation;
+pub mod xml;
+
+pub use format::InputFormat;
 
 use crate::error::{ParseError, ParseResult};
 use serde::{Deserialize, Serialize};
@@ -112,6 +122,14 @@ impl JsonSource {
         }
     }
 
+    /// Parse this source leniently, accepting JSON5/JSONC extensions
+    pub fn parse_lenient(&self) -> ParseResult<lenient::LenientParse> {
+        let content = self
+            .read_content()
+            .map_err(|e| ParseError::new(format!("Failed to read input: {}", e), None))?;
+        lenient::parse_lenient(&content)
+    }
+
     /// Get the source type
     pub fn source_type(&self) -> JsonSourceType {
         match self {
@@ -148,9 +166,13 @@ fn parse_from_string(content: &str) -> ParseResult<serde_json::Value> {
     }
 
     serde_json::from_str(trimmed).map_err(|e| {
+        // The location is kept separately, so drop serde_json's own suffix
+        let message = e.to_string();
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        let message = message.strip_suffix(&position).unwrap_or(&message);
         ParseError::new(
-            format!("Invalid JSON: {}", e),
-            extract_error_location(&e, trimmed),
+            format!("Invalid JSON: {}", message),
+            extract_error_location(&e),
         )
         .with_preview(get_error_preview(trimmed, &e))
     })
@@ -175,43 +197,14 @@ fn parse_from_stdin() -> ParseResult<serde_json::Value> {
 }
 
 /// Extract error location from serde_json error
-fn extract_error_location(error: &serde_json::Error, content: &str) -> Option<(usize, usize)> {
-    // Try to extract line and column from error message
-    let error_msg = error.to_string();
-
-    // Look for patterns like "line X column Y"
-    if let Some(line_start) = error_msg.find("line ") {
-        if let Some(line_end) = error_msg.find(" column ") {
-            let col_start = line_end;
-            if let Some(col_end) = error_msg.find(" of ") {
-                let line_str = &error_msg[line_start + 5..line_end];
-                let col_str = &error_msg[col_start + 8..col_end];
-
-                if let (Ok(line), Ok(col)) = (line_str.parse::<usize>(), col_str.parse::<usize>()) {
-                    return Some((line, col));
-                }
-            }
-        }
-    }
-
-    // Fallback: try to find position in content
-    if let Some(pos) = error_msg.find("position ") {
-        if let Some(end_pos) = error_msg.find(')') {
-            let pos_str = &error_msg[pos + 9..end_pos];
-            if let Ok(position) = pos_str.parse::<usize>() {
-                let line = content[..position].chars().filter(|&c| c == '\n').count() + 1;
-                let col = position - content[..position].rfind('\n').map_or(0, |p| p + 1);
-                return Some((line, col + 1));
-            }
-        }
-    }
-
-    None
+fn extract_error_location(error: &serde_json::Error) -> Option<(usize, usize)> {
+    // serde_json reports line 0 for errors without a position, such as I/O errors
+    (error.line() > 0).then(|| (error.line(), error.column()))
 }
 
 /// Get a preview of the error location
 fn get_error_preview(content: &str, error: &serde_json::Error) -> String {
-    if let Some((line, col)) = extract_error_location(error, content) {
+    if let Some((line, col)) = extract_error_location(error) {
         let lines: Vec<&str> = content.lines().collect();
         if line > 0 && line <= lines.len() {
             let error_line = lines[line - 1];
@@ -377,8 +370,12 @@ mod tests {
     fn test_parse_invalid_json() {
         let json_str = r#"{"name": "test", "value": }"#;
         let source = JsonSource::String(json_str.to_string());
-        let result = source.parse();
-        assert!(result.is_err());
+        let error = source.parse().unwrap_err();
+        assert_eq!(error.location, Some((1, 27)));
+        assert_eq!(
+            error.to_string(),
+            "Invalid JSON: expected value at line 1, column 27"
+        );
     }
 
     #[test]
@@ -0,0 +1,172 @@
+//! NDJSON / JSON Lines input parsing
+//!
+//! Each non-blank line is parsed as one record, reading the input line by line
+//! rather than loading it as a single document. The records are collected into
+//! a root array, which the formatter renders as a tabular array when they are
+//! uniform objects and as list items otherwise.
+
+use crate::error::{ParseError, ParseResult};
+use serde_json::Value;
+use std::io::BufRead;
+
+/// Records read from NDJSON input
+#[derive(Debug, Clone, Default)]
+pub struct NdjsonRecords {
+    /// Successfully parsed records, in input order
+    pub values: Vec<Value>,
+    /// Lines that failed to parse and were skipped
+    pub invalid_lines: Vec<ParseError>,
+}
+
+impl NdjsonRecords {
+    /// Collapse the records into a single root array
+    pub fn into_value(self) -> Value {
+        Value::Array(self.values)
+    }
+}
+
+/// Iterator over the records of NDJSON input, one per non-blank line
+pub struct NdjsonReader<R: BufRead> {
+    reader: R,
+    line_number: usize,
+    buffer: String,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    /// Create a reader over buffered input
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_number: 0,
+            buffer: String::new(),
+        }
+    }
+
+    /// Line number of the most recently read line (1-based)
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = ParseResult<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            self.line_number += 1;
+
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(Err(ParseError::new(
+                        format!("Failed to read line {}: {}", self.line_number, e),
+                        Some((self.line_number, 1)),
+                    )))
+                }
+            }
+
+            let line = self.buffer.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let leading = self.buffer.len() - self.buffer.trim_start().len();
+
+            return Some(serde_json::from_str(line).map_err(|e| {
+                ParseError::new(
+                    format!("Invalid JSON record: {}", error_reason(&e)),
+                    Some((self.line_number, e.column() + leading)),
+                )
+                .with_preview(line.to_string())
+            }));
+        }
+    }
+}
+
+/// Describe a serde_json error without its position, which is reported
+/// separately relative to the whole input rather than the single line
+fn error_reason(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(pos) => message[..pos].to_string(),
+        None => message,
+    }
+}
+
+/// Read all NDJSON records from buffered input
+///
+/// When `skip_invalid` is false the first malformed line aborts the read;
+/// otherwise malformed lines are collected in `invalid_lines` and skipped.
+pub fn read_ndjson<R: BufRead>(reader: R, skip_invalid: bool) -> ParseResult<NdjsonRecords> {
+    let mut records = NdjsonRecords::default();
+
+    for result in NdjsonReader::new(reader) {
+        match result {
+            Ok(value) => records.values.push(value),
+            Err(e) if skip_invalid => records.invalid_lines.push(e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Parse NDJSON content held in memory into a root array
+pub fn parse_ndjson(content: &str) -> ParseResult<Value> {
+    read_ndjson(content.as_bytes(), false).map(NdjsonRecords::into_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_ndjson_records() {
+        let input = "{\"id\":1,\"level\":\"info\"}\n\n{\"id\":2,\"level\":\"warn\"}\n";
+        let value = parse_ndjson(input).unwrap();
+        assert_eq!(
+            value,
+            json!([{"id": 1, "level": "info"}, {"id": 2, "level": "warn"}])
+        );
+    }
+
+    #[test]
+    fn test_uniform_records_become_table() {
+        let input = "{\"id\":1,\"ok\":true}\r\n{\"id\":2,\"ok\":false}";
+        let value = parse_ndjson(input).unwrap();
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert_eq!(toon.content, "[2]{id,ok}:\n  1,true\n  2,false");
+    }
+
+    #[test]
+    fn test_non_uniform_records_become_list() {
+        let input = "{\"id\":1}\n{\"name\":\"x\"}\n3\n";
+        let value = parse_ndjson(input).unwrap();
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert!(toon.content.starts_with("[3]:\n"));
+        assert!(toon.content.contains("- 3"));
+    }
+
+    #[test]
+    fn test_bad_line_reports_line_number() {
+        let input = "{\"id\":1}\n{\"id\":2,}\n";
+        let err = parse_ndjson(input).unwrap_err();
+        assert_eq!(err.location, Some((2, 9)));
+        assert_eq!(
+            err.to_string(),
+            "Invalid JSON record: trailing comma at line 2, column 9"
+        );
+    }
+
+    #[test]
+    fn test_skip_invalid_lines() {
+        let input = "{\"id\":1}\nnot json\n{\"id\":3}\n";
+        let records = read_ndjson(input.as_bytes(), true).unwrap();
+        assert_eq!(records.values.len(), 2);
+        assert_eq!(records.invalid_lines.len(), 1);
+        assert_eq!(records.invalid_lines[0].location.map(|(l, _)| l), Some(2));
+    }
+}
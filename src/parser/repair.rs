@@ -0,0 +1,475 @@
+//! Repair of truncated or slightly malformed JSON
+//!
+//! JSON produced by an LLM is often cut off mid-document or carries a stray
+//! trailing comma. Repair mode recovers as much of it as possible:
+//!
+//! - Trailing commas before `]`, `}` or the end of input are removed
+//! - Strings, arrays and objects still open at the end of input are closed
+//! - In the innermost open array, the element cut off by the end of input is
+//!   dropped, so truncated rows do not leak into tables
+//! - An object member cut off before its value is dropped; a cut-off string
+//!   value is kept as far as it was written
+//! - A number ending at the end of input inside an open object or array is
+//!   kept, but reported, since more digits may have been cut off
+//!
+//! Every repair is reported as a `ParseError` with the location it applies
+//! to. Malformed input that is not a truncation (a missing comma, say) is
+//! still an error.
+
+use crate::error::{ParseError, ParseResult};
+use serde_json::{Map, Number, Value};
+
+/// Result of repairing JSON input
+#[derive(Debug, Clone)]
+pub struct RepairedJson {
+    /// Recovered value
+    pub value: Value,
+    /// Repairs in the order they were applied
+    pub repairs: Vec<ParseError>,
+}
+
+/// Parse JSON, repairing truncation and trailing commas
+pub fn repair_json(content: &str) -> ParseResult<RepairedJson> {
+    let mut parser = RepairParser {
+        content,
+        input: content.as_bytes(),
+        pos: 0,
+        repairs: Vec::new(),
+    };
+
+    parser.skip_whitespace();
+    if parser.peek().is_none() {
+        return Err(ParseError::new("Empty JSON string".to_string(), None));
+    }
+
+    let value = match parser.parse_value()? {
+        Parsed::Complete(value) | Parsed::Cut(Some(value), _) => value,
+        Parsed::Cut(None, _) => {
+            return Err(parser.error_at("No JSON value could be recovered", 0));
+        }
+    };
+
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(parser.error_at("Invalid JSON: trailing characters", parser.pos));
+    }
+
+    Ok(RepairedJson {
+        value,
+        repairs: parser.repairs,
+    })
+}
+
+/// Outcome of parsing one value
+enum Parsed {
+    /// The value was complete
+    Complete(Value),
+    /// The input ended inside the value. Holds what could be salvaged, and
+    /// whether an array below already dropped the cut-off element.
+    Cut(Option<Value>, bool),
+}
+
+struct RepairParser<'a> {
+    content: &'a str,
+    input: &'a [u8],
+    pos: usize,
+    repairs: Vec<ParseError>,
+}
+
+impl RepairParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn location_of(&self, pos: usize) -> (usize, usize) {
+        let before = &self.content[..pos.min(self.content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |p| p + 1)..]
+            .chars()
+            .count()
+            + 1;
+        (line, column)
+    }
+
+    fn error_at(&self, message: &str, pos: usize) -> ParseError {
+        ParseError::new(message.to_string(), Some(self.location_of(pos)))
+    }
+
+    fn repair(&mut self, message: &str, pos: usize) {
+        let repair = self.error_at(message, pos);
+        self.repairs.push(repair);
+    }
+
+    fn parse_value(&mut self) -> ParseResult<Parsed> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => self.parse_string(),
+            Some(_) => self.parse_scalar(),
+            None => Ok(Parsed::Cut(None, false)),
+        }
+    }
+
+    fn parse_object(&mut self) -> ParseResult<Parsed> {
+        self.pos += 1;
+        let mut object = Map::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(self.close_object(object, false)),
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Object(object)));
+                }
+                Some(b'"') => {}
+                Some(_) => {
+                    return Err(self.error_at("Invalid JSON: expected object key", self.pos));
+                }
+            }
+
+            let member_start = self.pos;
+            let key = match self.parse_string()? {
+                Parsed::Complete(Value::String(key)) => key,
+                _ => {
+                    self.repair("Dropped incomplete object member", member_start);
+                    return Ok(self.close_object(object, false));
+                }
+            };
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b':') => self.pos += 1,
+                None => {
+                    self.repair("Dropped incomplete object member", member_start);
+                    return Ok(self.close_object(object, false));
+                }
+                Some(_) => {
+                    return Err(self.error_at("Invalid JSON: expected ':' after key", self.pos));
+                }
+            }
+
+            self.skip_whitespace();
+            let value_start = self.pos;
+            match self.parse_value()? {
+                Parsed::Complete(value) => {
+                    self.check_cut_number(&value, value_start);
+                    object.insert(key, value);
+                }
+                Parsed::Cut(Some(value), resolved) => {
+                    object.insert(key, value);
+                    return Ok(self.close_object(object, resolved));
+                }
+                Parsed::Cut(None, resolved) => {
+                    self.repair("Dropped incomplete object member", member_start);
+                    return Ok(self.close_object(object, resolved));
+                }
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    let comma = self.pos;
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if matches!(self.peek(), None | Some(b'}')) {
+                        self.repair("Removed trailing comma", comma);
+                    }
+                }
+                Some(b'}') => {}
+                None => return Ok(self.close_object(object, false)),
+                Some(_) => {
+                    return Err(
+                        self.error_at("Invalid JSON: expected ',' or '}' in object", self.pos)
+                    );
+                }
+            }
+        }
+    }
+
+    fn close_object(&mut self, object: Map<String, Value>, resolved: bool) -> Parsed {
+        self.repair("Closed unterminated object", self.pos);
+        Parsed::Cut(Some(Value::Object(object)), resolved)
+    }
+
+    fn parse_array(&mut self) -> ParseResult<Parsed> {
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(self.close_array(items)),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Array(items)));
+                }
+                Some(_) => {}
+            }
+
+            let element_start = self.pos;
+            match self.parse_value()? {
+                Parsed::Complete(value) => {
+                    self.check_cut_number(&value, element_start);
+                    items.push(value);
+                }
+                Parsed::Cut(Some(value), true) => {
+                    // A nested array already dropped the cut-off element
+                    items.push(value);
+                    return Ok(self.close_array(items));
+                }
+                Parsed::Cut(_, _) => {
+                    self.repair("Dropped incomplete last element", element_start);
+                    return Ok(self.close_array(items));
+                }
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    let comma = self.pos;
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if matches!(self.peek(), None | Some(b']')) {
+                        self.repair("Removed trailing comma", comma);
+                    }
+                }
+                Some(b']') => {}
+                None => return Ok(self.close_array(items)),
+                Some(_) => {
+                    return Err(
+                        self.error_at("Invalid JSON: expected ',' or ']' in array", self.pos)
+                    );
+                }
+            }
+        }
+    }
+
+    fn close_array(&mut self, items: Vec<Value>) -> Parsed {
+        self.repair("Closed unterminated array", self.pos);
+        Parsed::Cut(Some(Value::Array(items)), true)
+    }
+
+    /// Report a number inside a container that runs up to the end of input,
+    /// as `{"a": 12` may have been `{"a": 123}`
+    fn check_cut_number(&mut self, value: &Value, start: usize) {
+        if value.is_number() && self.pos == self.input.len() {
+            self.repair("Kept number that may be cut off", start);
+        }
+    }
+
+    fn parse_string(&mut self) -> ParseResult<Parsed> {
+        let start = self.pos;
+        self.pos += 1;
+        // End of the last complete character or escape sequence
+        let mut safe_end = self.pos;
+
+        while let Some(b) = self.peek() {
+            match b {
+                b'"' => {
+                    self.pos += 1;
+                    let value = self.decode_string(start, self.pos)?;
+                    return Ok(Parsed::Complete(Value::String(value)));
+                }
+                b'\\' => {
+                    let len = self.escape_len();
+                    if self.pos + len > self.input.len() {
+                        break;
+                    }
+                    self.pos += len;
+                }
+                _ => self.pos += 1,
+            }
+            safe_end = self.pos;
+        }
+
+        // The input ended inside the string: close it, leaving out any
+        // escape sequence that was cut in half
+        self.pos = self.input.len();
+        let raw = format!("{}\"", &self.content[start..safe_end]);
+        let value = serde_json::from_str::<String>(&raw)
+            .map_err(|_| self.error_at("Invalid JSON: invalid string", start))?;
+        self.repair("Closed unterminated string", self.pos);
+        Ok(Parsed::Cut(Some(Value::String(value)), false))
+    }
+
+    /// Length of the escape sequence at the current position, counting a
+    /// UTF-16 surrogate pair as one sequence
+    fn escape_len(&self) -> usize {
+        if self.input.get(self.pos + 1) != Some(&b'u') {
+            return 2;
+        }
+        let high_surrogate = self
+            .content
+            .get(self.pos + 2..self.pos + 6)
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .is_some_and(|unit| (0xD800..0xDC00).contains(&unit));
+        if high_surrogate {
+            12
+        } else {
+            6
+        }
+    }
+
+    fn decode_string(&self, start: usize, end: usize) -> ParseResult<String> {
+        serde_json::from_str::<String>(&self.content[start..end]).map_err(|e| {
+            ParseError::new(
+                format!("Invalid JSON: invalid string ({})", e),
+                Some(self.location_of(start)),
+            )
+        })
+    }
+
+    /// Parse a number, `true`, `false` or `null`
+    fn parse_scalar(&mut self) -> ParseResult<Parsed> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.'))
+        {
+            self.pos += 1;
+        }
+
+        let text = &self.content[start..self.pos];
+        let value = match text {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            "null" => Some(Value::Null),
+            _ => serde_json::from_str::<Number>(text).ok().map(Value::Number),
+        };
+
+        match value {
+            Some(value) => Ok(Parsed::Complete(value)),
+            // A partial literal such as `tru` or `1e` at the end of input
+            None if self.pos == self.input.len() && !text.is_empty() => {
+                Ok(Parsed::Cut(None, false))
+            }
+            None => Err(self.error_at("Invalid JSON: expected value", start)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repairs(result: &RepairedJson) -> Vec<String> {
+        result.repairs.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn test_valid_json_needs_no_repair() {
+        let result = repair_json(r#"{"a": [1, "x\"y", true, null], "b": -1.5e3}"#).unwrap();
+        assert_eq!(
+            result.value,
+            json!({"a": [1, "x\"y", true, null], "b": -1500.0})
+        );
+        assert!(result.repairs.is_empty());
+    }
+
+    #[test]
+    fn test_drops_truncated_row() {
+        let input =
+            "{\"users\": [\n  {\"id\": 1, \"name\": \"Ann\"},\n  {\"id\": 2, \"name\": \"Bo";
+        let result = repair_json(input).unwrap();
+        assert_eq!(result.value, json!({"users": [{"id": 1, "name": "Ann"}]}));
+        assert_eq!(
+            repairs(&result),
+            [
+                "Closed unterminated string at line 3, column 24",
+                "Closed unterminated object at line 3, column 24",
+                "Dropped incomplete last element at line 3, column 3",
+                "Closed unterminated array at line 3, column 24",
+                "Closed unterminated object at line 3, column 24",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keeps_cut_string_value() {
+        let result = repair_json(r#"{"answer": "The capital is Par"#).unwrap();
+        assert_eq!(result.value, json!({"answer": "The capital is Par"}));
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        let result = repair_json("[1, 2,]").unwrap();
+        assert_eq!(result.value, json!([1, 2]));
+        assert_eq!(
+            repairs(&result),
+            ["Removed trailing comma at line 1, column 6"]
+        );
+
+        let result = repair_json(r#"{"a": [1, 2,"#).unwrap();
+        assert_eq!(result.value, json!({"a": [1, 2]}));
+        assert_eq!(result.repairs.len(), 3);
+    }
+
+    #[test]
+    fn test_drops_member_without_value() {
+        let result = repair_json(r#"{"a": 1, "b":"#).unwrap();
+        assert_eq!(result.value, json!({"a": 1}));
+
+        let result = repair_json(r#"{"a": 1, "b": tr"#).unwrap();
+        assert_eq!(result.value, json!({"a": 1}));
+    }
+
+    #[test]
+    fn test_reports_number_at_end_of_input() {
+        let result = repair_json(r#"{"a": 12"#).unwrap();
+        assert_eq!(result.value, json!({"a": 12}));
+        assert_eq!(
+            repairs(&result),
+            [
+                "Kept number that may be cut off at line 1, column 7",
+                "Closed unterminated object at line 1, column 9",
+            ]
+        );
+
+        let result = repair_json("[1, 2.5").unwrap();
+        assert_eq!(result.value, json!([1, 2.5]));
+        assert_eq!(result.repairs.len(), 2);
+
+        // A complete top-level number is not a truncation
+        assert!(repair_json("12").unwrap().repairs.is_empty());
+    }
+
+    #[test]
+    fn test_nested_arrays_drop_only_innermost() {
+        let result = repair_json("[[1, 2], [3, 4").unwrap();
+        assert_eq!(result.value, json!([[1, 2], [3, 4]]));
+
+        let result = repair_json("[[1, 2], [3, \"x").unwrap();
+        assert_eq!(result.value, json!([[1, 2], [3]]));
+    }
+
+    #[test]
+    fn test_cut_escape_is_left_out() {
+        let result = repair_json(r#"["ab\u00"#).unwrap();
+        assert_eq!(result.value, json!([]));
+
+        let result = repair_json(r#"{"s": "ab\u00"#).unwrap();
+        assert_eq!(result.value, json!({"s": "ab"}));
+
+        let result = repair_json(r#"{"s": "a\\"#).unwrap();
+        assert_eq!(result.value, json!({"s": "a\\"}));
+
+        let result = repair_json(r#"{"s": "x\ud83d\ude"#).unwrap();
+        assert_eq!(result.value, json!({"s": "x"}));
+    }
+
+    #[test]
+    fn test_non_truncation_errors_remain() {
+        let err = repair_json("[1 2]").unwrap_err();
+        assert_eq!(err.location, Some((1, 4)));
+        assert!(repair_json("{\"a\" 1}").is_err());
+    }
+}
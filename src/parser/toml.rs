@@ -0,0 +1,220 @@
+//! TOML input parsing
+//!
+//! TOML documents are mapped onto JSON values before formatting. Tables become
+//! objects (keeping the key order of the file), and arrays of tables such as
+//! `[[package]]` become arrays of objects, which render as TOON tables when
+//! their rows share the same fields.
+//!
+//! JSON has no date type, so TOML dates and times are always emitted as
+//! strings. By default they keep their RFC 3339 spelling from the source; a
+//! strftime-style format can be set to rewrite them. Non-finite floats
+//! (`inf`, `nan`) are also emitted as strings.
+
+use crate::error::{ParseError, ParseResult};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde_json::{Map, Number, Value};
+use std::fmt::Write;
+use toml::value::{Datetime, Offset};
+
+/// Options controlling the TOML to JSON mapping
+#[derive(Debug, Clone, Default)]
+pub struct TomlOptions {
+    /// strftime-style format for dates and times (None keeps RFC 3339)
+    pub date_format: Option<String>,
+}
+
+impl TomlOptions {
+    /// Create options with the default mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the format used to render dates and times
+    pub fn with_date_format(mut self, format: &str) -> Self {
+        self.date_format = Some(format.to_string());
+        self
+    }
+}
+
+/// Parse a TOML document into a JSON value
+pub fn parse_toml(content: &str, options: &TomlOptions) -> ParseResult<Value> {
+    let table: toml::Table = content.parse().map_err(|e: toml::de::Error| {
+        let location = e.span().map(|span| line_column(content, span.start));
+        ParseError::new(
+            format!("Invalid TOML: {}", e.message().trim_end()),
+            location,
+        )
+    })?;
+
+    convert_table(table, options)
+}
+
+fn convert_table(table: toml::Table, options: &TomlOptions) -> ParseResult<Value> {
+    let mut object = Map::with_capacity(table.len());
+    for (key, value) in table {
+        object.insert(key, convert_value(value, options)?);
+    }
+    Ok(Value::Object(object))
+}
+
+fn convert_value(value: toml::Value, options: &TomlOptions) -> ParseResult<Value> {
+    Ok(match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => match Number::from_f64(f) {
+            Some(n) => Value::Number(n),
+            None => Value::String(non_finite_float(f)),
+        },
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(format_datetime(&dt, options)?),
+        toml::Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| convert_value(item, options))
+                .collect::<ParseResult<Vec<_>>>()?,
+        ),
+        toml::Value::Table(table) => convert_table(table, options)?,
+    })
+}
+
+/// TOML spelling of a float JSON cannot represent
+fn non_finite_float(f: f64) -> String {
+    if f.is_nan() {
+        "nan".to_string()
+    } else if f.is_sign_negative() {
+        "-inf".to_string()
+    } else {
+        "inf".to_string()
+    }
+}
+
+/// Render a TOML date/time as a string, applying the configured format if any
+fn format_datetime(dt: &Datetime, options: &TomlOptions) -> ParseResult<String> {
+    let Some(format) = options.date_format.as_deref() else {
+        return Ok(dt.to_string());
+    };
+
+    let date = dt
+        .date
+        .and_then(|d| NaiveDate::from_ymd_opt(d.year as i32, d.month as u32, d.day as u32));
+    let time = dt.time.and_then(|t| {
+        NaiveTime::from_hms_nano_opt(
+            t.hour as u32,
+            t.minute as u32,
+            t.second as u32,
+            t.nanosecond,
+        )
+    });
+    let offset = dt.offset.and_then(|o| match o {
+        Offset::Z => FixedOffset::east_opt(0),
+        Offset::Custom { minutes } => FixedOffset::east_opt(minutes as i32 * 60),
+    });
+
+    let mut output = String::new();
+    let result = match (date, time, offset) {
+        (Some(date), Some(time), Some(offset)) => {
+            match offset.from_local_datetime(&NaiveDateTime::new(date, time)) {
+                chrono::LocalResult::Single(datetime) => {
+                    write!(output, "{}", datetime.format(format))
+                }
+                _ => Err(std::fmt::Error),
+            }
+        }
+        (Some(date), Some(time), None) => {
+            write!(output, "{}", NaiveDateTime::new(date, time).format(format))
+        }
+        (Some(date), None, _) => write!(output, "{}", date.format(format)),
+        (None, Some(time), _) => write!(output, "{}", time.format(format)),
+        _ => Err(std::fmt::Error),
+    };
+
+    result.map(|_| output).map_err(|_| {
+        ParseError::new(
+            format!("Date format '{}' cannot render TOML value {}", format, dt),
+            None,
+        )
+    })
+}
+
+/// Convert a byte offset into a 1-based (line, column) pair
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |p| p + 1) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use serde_json::json;
+
+    #[test]
+    fn test_tables_keep_file_order() {
+        let input = "[package]\nname = \"toonconv\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+        let value = parse_toml(input, &TomlOptions::default()).unwrap();
+        let keys: Vec<_> = value["package"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["name", "version", "edition"]);
+    }
+
+    #[test]
+    fn test_array_of_tables_becomes_table() {
+        let input = r#"
+[[package]]
+name = "serde"
+version = "1.0.210"
+
+[[package]]
+name = "toml"
+version = "0.8.23"
+"#;
+        let value = parse_toml(input, &TomlOptions::default()).unwrap();
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert_eq!(
+            toon.content,
+            "package[2]{name,version}:\n  serde,1.0.210\n  toml,0.8.23"
+        );
+    }
+
+    #[test]
+    fn test_dates_default_to_rfc3339_strings() {
+        let input = "released = 2024-05-01T12:30:00Z\nday = 2024-05-01\nat = 07:45:00\n";
+        let value = parse_toml(input, &TomlOptions::default()).unwrap();
+        assert_eq!(
+            value,
+            json!({"released": "2024-05-01T12:30:00Z", "day": "2024-05-01", "at": "07:45:00"})
+        );
+    }
+
+    #[test]
+    fn test_custom_date_format() {
+        let options = TomlOptions::new().with_date_format("%d/%m/%Y");
+        let input = "released = 2024-05-01T12:30:00+02:00\nday = 2024-05-02\n";
+        let value = parse_toml(input, &options).unwrap();
+        assert_eq!(
+            value,
+            json!({"released": "01/05/2024", "day": "02/05/2024"})
+        );
+    }
+
+    #[test]
+    fn test_date_format_not_applicable() {
+        let options = TomlOptions::new().with_date_format("%H:%M");
+        let err = parse_toml("day = 2024-05-02\n", &options).unwrap_err();
+        assert!(err.message.contains("cannot render"));
+    }
+
+    #[test]
+    fn test_non_finite_floats_become_strings() {
+        let value = parse_toml("a = inf\nb = nan\nc = 1.5\n", &TomlOptions::default()).unwrap();
+        assert_eq!(value, json!({"a": "inf", "b": "nan", "c": 1.5}));
+    }
+
+    #[test]
+    fn test_invalid_toml_reports_location() {
+        let err = parse_toml("a = 1\nb = \n", &TomlOptions::default()).unwrap_err();
+        assert!(err.message.starts_with("Invalid TOML"));
+        assert_eq!(err.location.map(|(line, _)| line), Some(2));
+    }
+}
@@ -0,0 +1,324 @@
+//! XML input parsing
+//!
+//! XML is mapped onto a JSON value so it can run through the regular
+//! `ToonFormatter`. The mapping is:
+//!
+//! - The document element becomes a single-key root object: `<a>..</a>` → `{"a": ..}`
+//! - Attributes become keys with a prefix (default `@`): `id="1"` → `"@id": "1"`
+//! - Child elements become keys named after the element
+//! - Repeated child elements with the same name become an array, in document order
+//! - An element with only text becomes that string; an empty element becomes `null`
+//! - Text next to attributes or children goes under a text key (default `#text`)
+//! - CDATA is treated as text; comments, processing instructions and the
+//!   DOCTYPE are dropped
+//!
+//! All text and attribute values stay strings; names keep any namespace prefix.
+
+use crate::error::{ParseError, ParseResult};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+
+/// Options controlling the XML to JSON mapping
+#[derive(Debug, Clone)]
+pub struct XmlOptions {
+    /// Prefix added to attribute names
+    pub attribute_prefix: String,
+    /// Key holding text content of elements that also have attributes or children
+    pub text_key: String,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        Self {
+            attribute_prefix: "@".to_string(),
+            text_key: "#text".to_string(),
+        }
+    }
+}
+
+impl XmlOptions {
+    /// Create options with the default mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attribute name prefix
+    pub fn with_attribute_prefix(mut self, prefix: &str) -> Self {
+        self.attribute_prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the key used for text content
+    pub fn with_text_key(mut self, key: &str) -> Self {
+        self.text_key = key.to_string();
+        self
+    }
+}
+
+/// An element whose end tag has not been reached yet
+struct OpenElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl OpenElement {
+    /// Convert the finished element to its JSON value
+    fn into_value(mut self, options: &XmlOptions) -> Value {
+        if self.fields.is_empty() {
+            if self.text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(self.text)
+            }
+        } else {
+            if !self.text.is_empty() {
+                self.fields
+                    .insert(options.text_key.clone(), Value::String(self.text));
+            }
+            Value::Object(self.fields)
+        }
+    }
+}
+
+/// Parse an XML document into a JSON value using the documented mapping
+pub fn parse_xml(content: &str, options: &XmlOptions) -> ParseResult<Value> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut stack: Vec<OpenElement> = Vec::new();
+    let mut root: Option<(String, Value)> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| xml_error(format!("Invalid XML: {}", e), &reader, content))?;
+
+        match event {
+            Event::Start(start) => {
+                let element = open_element(&start, options)
+                    .map_err(|message| xml_error(message, &reader, content))?;
+                stack.push(element);
+            }
+            Event::Empty(start) => {
+                let element = open_element(&start, options)
+                    .map_err(|message| xml_error(message, &reader, content))?;
+                let name = element.name.clone();
+                close_element(name, element.into_value(options), &mut stack, &mut root)
+                    .map_err(|message| xml_error(message, &reader, content))?;
+            }
+            Event::End(_) => {
+                // quick-xml verifies that end tags match their start tags
+                if let Some(element) = stack.pop() {
+                    let name = element.name.clone();
+                    close_element(name, element.into_value(options), &mut stack, &mut root)
+                        .map_err(|message| xml_error(message, &reader, content))?;
+                }
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| xml_error(format!("Invalid XML text: {}", e), &reader, content))?;
+                append_text(&mut stack, &text);
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8(data.into_inner().into_owned()).map_err(|_| {
+                    xml_error("CDATA is not valid UTF-8".to_string(), &reader, content)
+                })?;
+                append_text(&mut stack, &text);
+            }
+            Event::Eof => break,
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(xml_error(
+            format!("Unclosed element <{}>", open.name),
+            &reader,
+            content,
+        ));
+    }
+
+    match root {
+        Some((name, value)) => {
+            let mut object = Map::new();
+            object.insert(name, value);
+            Ok(Value::Object(object))
+        }
+        None => Err(ParseError::new(
+            "XML document has no root element".to_string(),
+            None,
+        )),
+    }
+}
+
+/// Start a new element, collecting its attributes as prefixed keys
+fn open_element(start: &BytesStart, options: &XmlOptions) -> Result<OpenElement, String> {
+    let name = utf8(start.name().as_ref())?;
+    let mut fields = Map::new();
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| format!("Invalid XML attribute: {}", e))?;
+        let key = format!(
+            "{}{}",
+            options.attribute_prefix,
+            utf8(attribute.key.as_ref())?
+        );
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| format!("Invalid XML attribute value: {}", e))?;
+        fields.insert(key, Value::String(value.into_owned()));
+    }
+
+    Ok(OpenElement {
+        name,
+        fields,
+        text: String::new(),
+    })
+}
+
+/// Attach a finished element to its parent, turning repeated names into arrays
+fn close_element(
+    name: String,
+    value: Value,
+    stack: &mut [OpenElement],
+    root: &mut Option<(String, Value)>,
+) -> Result<(), String> {
+    let Some(parent) = stack.last_mut() else {
+        if root.is_some() {
+            return Err(format!(
+                "XML document has more than one root element (found <{}>)",
+                name
+            ));
+        }
+        *root = Some((name, value));
+        return Ok(());
+    };
+
+    match parent.fields.get_mut(&name) {
+        // Element values are never arrays themselves, so an existing array
+        // always comes from an earlier repetition of this element.
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            parent.fields.insert(name, value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Append text to the innermost open element (text outside the root is ignored)
+fn append_text(stack: &mut [OpenElement], text: &str) {
+    if let Some(element) = stack.last_mut() {
+        if !element.text.is_empty() && !text.is_empty() {
+            element.text.push(' ');
+        }
+        element.text.push_str(text);
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, String> {
+    std::str::from_utf8(bytes)
+        .map(|s| s.to_string())
+        .map_err(|_| "XML name is not valid UTF-8".to_string())
+}
+
+/// Build a parse error located at the reader's current position
+fn xml_error(message: String, reader: &Reader<&[u8]>, content: &str) -> ParseError {
+    let position = reader.buffer_position().min(content.len());
+    let before = &content.as_bytes()[..position];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let column = position
+        - before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |p| p + 1)
+        + 1;
+
+    ParseError::new(message, Some((line, column)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{convert_json_to_toon, ConversionConfig};
+    use serde_json::json;
+
+    fn parse(content: &str) -> Value {
+        parse_xml(content, &XmlOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_simple_elements() {
+        let value = parse("<?xml version=\"1.0\"?><user><name>Alice</name><age>30</age></user>");
+        assert_eq!(value, json!({"user": {"name": "Alice", "age": "30"}}));
+    }
+
+    #[test]
+    fn test_attributes_and_text() {
+        let value = parse(r#"<price currency="USD">9.99</price>"#);
+        assert_eq!(
+            value,
+            json!({"price": {"@currency": "USD", "#text": "9.99"}})
+        );
+    }
+
+    #[test]
+    fn test_repeated_children_become_array() {
+        let rss = r#"
+            <rss version="2.0">
+              <channel>
+                <title>News</title>
+                <item><title>A</title><link>http://a</link></item>
+                <item><title>B</title><link>http://b</link></item>
+              </channel>
+            </rss>"#;
+        let value = parse(rss);
+        assert_eq!(
+            value["rss"]["channel"]["item"],
+            json!([
+                {"title": "A", "link": "http://a"},
+                {"title": "B", "link": "http://b"}
+            ])
+        );
+
+        let toon = convert_json_to_toon(&value, &ConversionConfig::default()).unwrap();
+        assert!(toon.content.contains("item[2]{title,link}:"));
+    }
+
+    #[test]
+    fn test_empty_elements_cdata_and_entities() {
+        let value = parse("<r><a/><b></b><c><![CDATA[x < y]]></c><d>&amp;</d></r>");
+        assert_eq!(
+            value,
+            json!({"r": {"a": null, "b": null, "c": "x < y", "d": "&"}})
+        );
+    }
+
+    #[test]
+    fn test_custom_prefix_and_text_key() {
+        let options = XmlOptions::new()
+            .with_attribute_prefix("_")
+            .with_text_key("value");
+        let value = parse_xml(r#"<n unit="kg">5</n>"#, &options).unwrap();
+        assert_eq!(value, json!({"n": {"_unit": "kg", "value": "5"}}));
+    }
+
+    #[test]
+    fn test_mismatched_tag_reports_location() {
+        let err = parse_xml("<a>\n  <b></c>\n</a>", &XmlOptions::default()).unwrap_err();
+        assert!(err.message.starts_with("Invalid XML"));
+        assert_eq!(err.location.map(|(line, _)| line), Some(2));
+    }
+
+    #[test]
+    fn test_missing_root() {
+        assert!(parse_xml("<!-- only a comment -->", &XmlOptions::default()).is_err());
+    }
+}
@@ -1,7 +1,9 @@
 //! TOON validation module
 
 pub mod circular_refs;
+pub mod schema;
 pub mod toon_compliance;
 
 pub use circular_refs::CircularRefDetector;
+pub use schema::SchemaValidator;
 pub use toon_compliance::ToonValidator;
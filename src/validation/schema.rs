@@ -0,0 +1,126 @@
+//! JSON Schema validation of decoded TOON
+//!
+//! Checks a decoded TOON document against a JSON Schema. Each violation is
+//! reported with the JSON Pointer of the offending value and the TOON line it
+//! was decoded from, so contract failures in model output can be traced back
+//! to the response text.
+
+use crate::decoder::DecodedToon;
+use crate::error::{FormattingError, FormattingResult};
+use crate::validation::toon_compliance::ValidationReport;
+use serde_json::Value;
+
+/// Validator for a compiled JSON Schema
+pub struct SchemaValidator {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    /// Compile a JSON Schema
+    pub fn new(schema: &Value) -> FormattingResult<Self> {
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|e| FormattingError::schema(format!("Invalid JSON Schema: {}", e)))?;
+        Ok(Self { validator })
+    }
+
+    /// Validate a decoded TOON document
+    ///
+    /// Decode warnings are carried over as report warnings.
+    pub fn validate(&self, decoded: &DecodedToon) -> ValidationReport {
+        let mut report = ValidationReport::new();
+
+        for warning in &decoded.warnings {
+            report.add_warning_at(&warning.kind.to_string(), warning.line);
+        }
+
+        for error in self.validator.iter_errors(&decoded.value) {
+            let pointer = error.instance_path.as_str();
+            report.add_error_at(&error.to_string(), pointer, decoded.line_of(pointer));
+        }
+
+        report.structure_valid = report.is_valid();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::{decode_toon, DecodeOptions};
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["users"],
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "name"],
+                        "properties": {
+                            "id": {"type": "integer"},
+                            "name": {"type": "string"}
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let decoded = decode_toon(
+            "users[2]{id,name}:\n  1,Ann\n  2,Bo\n",
+            &DecodeOptions::strict(),
+        )
+        .unwrap();
+        let report = SchemaValidator::new(&schema()).unwrap().validate(&decoded);
+        assert!(report.is_valid());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_violations_have_pointer_and_line() {
+        let decoded = decode_toon(
+            "users[2]{id,name}:\n  1,Ann\n  two,Bo\n",
+            &DecodeOptions::strict(),
+        )
+        .unwrap();
+        let report = SchemaValidator::new(&schema()).unwrap().validate(&decoded);
+
+        assert_eq!(report.error_count(), 1);
+        let issue = &report.issues[0];
+        assert_eq!(issue.path.as_deref(), Some("/users/1/id"));
+        assert_eq!(issue.line, Some(3));
+        assert!(issue.to_string().starts_with("/users/1/id: "));
+        assert!(issue.to_string().ends_with("(line 3)"));
+    }
+
+    #[test]
+    fn test_missing_property_points_at_object() {
+        let decoded = decode_toon("users[1]:\n  - id: 1\n", &DecodeOptions::strict()).unwrap();
+        let report = SchemaValidator::new(&schema()).unwrap().validate(&decoded);
+
+        assert_eq!(report.error_count(), 1);
+        assert_eq!(report.issues[0].path.as_deref(), Some("/users/0"));
+        assert_eq!(report.issues[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_decode_warnings_are_report_warnings() {
+        let decoded =
+            decode_toon("users[3]{id,name}:\n  1,Ann\n", &DecodeOptions::lenient()).unwrap();
+        let report = SchemaValidator::new(&schema()).unwrap().validate(&decoded);
+
+        assert!(report.is_valid());
+        assert_eq!(report.warning_count(), 1);
+        assert_eq!(report.issues[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(SchemaValidator::new(&json!({"type": 12})).is_err());
+    }
+}
@@ -104,16 +104,12 @@ impl ToonValidator {
             // Track brackets outside strings
             match ch {
                 '{' => brace_stack.push(i),
-                '}' => {
-                    if brace_stack.pop().is_none() {
-                        report.add_error(&format!("Unmatched closing brace at position {}", i));
-                    }
+                '}' if brace_stack.pop().is_none() => {
+                    report.add_error(&format!("Unmatched closing brace at position {}", i));
                 }
                 '[' => bracket_stack.push(i),
-                ']' => {
-                    if bracket_stack.pop().is_none() {
-                        report.add_error(&format!("Unmatched closing bracket at position {}", i));
-                    }
+                ']' if bracket_stack.pop().is_none() => {
+                    report.add_error(&format!("Unmatched closing bracket at position {}", i));
                 }
                 _ => {}
             }
@@ -329,6 +325,18 @@ impl ValidationReport {
         self.issues.push(ValidationIssue {
             severity: IssueSeverity::Error,
             message: message.to_string(),
+            path: None,
+            line: None,
+        });
+    }
+
+    /// Add an error located at a JSON Pointer and TOON line
+    pub fn add_error_at(&mut self, message: &str, path: &str, line: Option<usize>) {
+        self.issues.push(ValidationIssue {
+            severity: IssueSeverity::Error,
+            message: message.to_string(),
+            path: Some(path.to_string()),
+            line,
         });
     }
 
@@ -337,6 +345,18 @@ impl ValidationReport {
         self.issues.push(ValidationIssue {
             severity: IssueSeverity::Warning,
             message: message.to_string(),
+            path: None,
+            line: None,
+        });
+    }
+
+    /// Add a warning located at a TOON line
+    pub fn add_warning_at(&mut self, message: &str, line: usize) {
+        self.issues.push(ValidationIssue {
+            severity: IssueSeverity::Warning,
+            message: message.to_string(),
+            path: None,
+            line: Some(line),
         });
     }
 
@@ -370,6 +390,24 @@ impl ValidationReport {
 pub struct ValidationIssue {
     pub severity: IssueSeverity,
     pub message: String,
+    /// JSON Pointer of the offending value, if known
+    pub path: Option<String>,
+    /// Line of the TOON document the issue comes from, if known
+    pub line: Option<usize>,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            let path = if path.is_empty() { "/" } else { path };
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(line) = self.line {
+            write!(f, " (line {})", line)?;
+        }
+        Ok(())
+    }
 }
 
 /// Issue severity
@@ -43,7 +43,7 @@ mod file_conversion_tests {
         let (stdout, stderr) = run_toonconv(&args).unwrap();
 
         assert!(stderr.is_empty(), "No error expected: {}", stderr);
-        assert!(stdout.contains("Found 2 JSON files") || stdout.contains("Found 1 JSON files") || stdout.contains("Converted"));
+        assert!(stdout.contains("Found 2 input files") || stdout.contains("Found 1 input files") || stdout.contains("Converted"));
 
         // Check that output files were created
         let out1 = output_dir.path().join("a.toon");
@@ -7,6 +7,9 @@
 //! - User Story 4: Complex JSON structures
 //! - Cross-cutting concerns: CLI options, error handling, performance
 
+// Float fixtures deliberately use literals like 3.14 rather than consts::PI
+#![allow(clippy::approx_constant)]
+
 use serde_json::json;
 use std::fs;
 use std::io::Write;
@@ -542,6 +545,44 @@ mod cli_options {
         assert!(content.contains("    ") || content.contains("\t"));
     }
 
+    #[test]
+    fn test_project_config_files() {
+        let tmp = tempdir().unwrap();
+        create_test_json_file(&tmp, "in/toonconv.toml", "indent = 4\n");
+        create_test_json_file(&tmp, "in/sub/toonconv.toml", "delimiter = \"pipe\"\n");
+        create_test_json_file(&tmp, "in/top.json", r#"{"a": {"b": [1, 2]}}"#);
+        create_test_json_file(&tmp, "in/sub/inner.json", r#"{"a": {"b": [1, 2]}}"#);
+        let input_dir = tmp.path().join("in");
+        let output_dir = tmp.path().join("out");
+
+        let output = run_toonconv(&[
+            input_dir.to_str().unwrap(),
+            "-o",
+            output_dir.to_str().unwrap(),
+            "--recursive",
+        ]);
+        assert!(output.status.success());
+        assert_eq!(
+            fs::read_to_string(output_dir.join("top.toon")).unwrap(),
+            "a:\n    b[2]: 1,2"
+        );
+        assert_eq!(
+            fs::read_to_string(output_dir.join("sub/inner.toon")).unwrap(),
+            "a:\n    b[2]: 1|2"
+        );
+        // The settings files themselves are not converted as data
+        assert!(!output_dir.join("toonconv.toon").exists());
+        assert!(!output_dir.join("sub/toonconv.toon").exists());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Files:      2"));
+
+        // Flags override the config file, and --no-config ignores it
+        let top = input_dir.join("top.json");
+        let output = run_toonconv(&[top.to_str().unwrap(), "--indent", "2"]);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a:\n  b[2]: 1,2\n");
+        let output = run_toonconv(&[top.to_str().unwrap(), "--no-config", "--plain"]);
+        assert!(output.status.success());
+    }
+
     #[test]
     fn test_plain_output_option() {
         let output = run_toonconv_with_stdin(&["--stdin", "--plain"], r#"{"key": "value"}"#);
@@ -585,6 +626,26 @@ mod cli_options {
 
         assert!(!output.status.success());
     }
+
+    #[test]
+    fn test_validate_stdin_uses_input_format() {
+        let toml = "name = \"a\"\n";
+        let output = run_toonconv_with_stdin(
+            &["--stdin", "--input-format", "toml", "--validate-only"],
+            toml,
+        );
+        assert!(output.status.success());
+        // Without --input-format, standard input is read as JSON
+        let output = run_toonconv_with_stdin(&["--stdin", "--validate-only"], toml);
+        assert!(!output.status.success());
+
+        let output = run_toonconv_with_stdin(
+            &["--stdin", "--input-format", "csv", "validate", "-"],
+            "a,b\n1\n",
+        );
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid CSV record"));
+    }
 }
 
 // ============================================================================
@@ -708,6 +769,763 @@ mod data_integrity {
     }
 }
 
+// ============================================================================
+// Input Formats
+// ============================================================================
+
+mod input_formats {
+    use super::*;
+
+    #[test]
+    fn test_csv_file_to_tabular() {
+        let tmp = tempdir().unwrap();
+        let input_path =
+            create_test_json_file(&tmp, "users.csv", "id,name,active\n1,Alice,true\n2,Bob,\n");
+
+        let output = run_toonconv(&[input_path.to_str().unwrap()]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout.trim_end(),
+            "[2]{id,name,active}:\n  1,Alice,true\n  2,Bob,null"
+        );
+    }
+
+    #[test]
+    fn test_tsv_via_stdin_with_input_format() {
+        let output = run_toonconv_with_stdin(
+            &["--stdin", "--input-format", "tsv", "--no-header"],
+            "a\tb\nc\td\n",
+        );
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.starts_with("[2]{col1,col2}:"));
+    }
+
+    #[test]
+    fn test_ndjson_file_collapses_to_table() {
+        let tmp = tempdir().unwrap();
+        let input_path = create_test_json_file(
+            &tmp,
+            "events.jsonl",
+            "{\"id\":1,\"level\":\"info\"}\n{\"id\":2,\"level\":\"warn\"}\n",
+        );
+
+        let output = run_toonconv(&[input_path.to_str().unwrap()]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.trim_end(), "[2]{id,level}:\n  1,info\n  2,warn");
+    }
+
+    #[test]
+    fn test_ndjson_bad_line_reports_line_number() {
+        let output = run_toonconv_with_stdin(
+            &["--stdin", "--input-format", "ndjson"],
+            "{\"id\":1}\n{\"id\":2,}\n",
+        );
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("line 2"));
+    }
+
+    #[test]
+    fn test_csv_ragged_row_error() {
+        let tmp = tempdir().unwrap();
+        let input_path = create_test_json_file(&tmp, "bad.csv", "a,b\n1,2\n3\n");
+
+        let output = run_toonconv(&[input_path.to_str().unwrap()]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("line 3"));
+    }
+
+    #[test]
+    fn test_xml_repeated_elements_become_table() {
+        let tmp = tempdir().unwrap();
+        let input_path = create_test_json_file(
+            &tmp,
+            "feed.xml",
+            "<feed><item id=\"1\"><title>A</title></item><item id=\"2\"><title>B</title></item></feed>",
+        );
+
+        let output = run_toonconv(&[input_path.to_str().unwrap(), "--xml-attr-prefix", "_"]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout.trim_end(),
+            "feed:\n  item[2]{_id,title}:\n    \"1\",A\n    \"2\",B"
+        );
+    }
+
+    #[test]
+    fn test_toml_date_format_via_stdin() {
+        let output = run_toonconv_with_stdin(
+            &[
+                "--stdin",
+                "--input-format",
+                "toml",
+                "--toml-date-format",
+                "%Y/%m/%d",
+            ],
+            "[release]\nversion = \"1.2.0\"\ndate = 2024-05-01\n",
+        );
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout.trim_end(),
+            "release:\n  version: 1.2.0\n  date: 2024/05/01"
+        );
+    }
+
+    #[test]
+    fn test_directory_conversion_includes_toml() {
+        let tmp = tempdir().unwrap();
+        let input_dir = tmp.path().join("input");
+        let output_dir = tmp.path().join("output");
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(input_dir.join("Cargo.lock"), "[[package]]\nname = \"a\"\n").unwrap();
+        fs::write(
+            input_dir.join("deps.toml"),
+            "[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\n\n[[package]]\nname = \"toml\"\nversion = \"0.8.23\"\n",
+        )
+        .unwrap();
+
+        let output = run_toonconv(&[
+            input_dir.to_str().unwrap(),
+            "--output",
+            output_dir.to_str().unwrap(),
+        ]);
+
+        assert!(output.status.success());
+        let toon = fs::read_to_string(output_dir.join("deps.toon")).unwrap();
+        assert_eq!(
+            toon,
+            "package[2]{name,version}:\n  serde,1.0.210\n  toml,0.8.23"
+        );
+        assert!(!output_dir.join("Cargo.toon").exists());
+    }
+
+    #[test]
+    fn test_directory_csv_streams_past_memory_limit() {
+        let tmp = tempdir().unwrap();
+        let input_dir = tmp.path().join("input");
+        let output_dir = tmp.path().join("output");
+        fs::create_dir_all(&input_dir).unwrap();
+        let rows: String = (0..500).map(|n| format!("{},user{}\n", n, n)).collect();
+        fs::write(input_dir.join("users.csv"), format!("id,name\n{}", rows)).unwrap();
+
+        let output = run_toonconv(&[
+            input_dir.to_str().unwrap(),
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--extension",
+            "csv",
+            "--memory-limit",
+            "2KB",
+        ]);
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let toon = fs::read_to_string(output_dir.join("users.toon")).unwrap();
+        assert!(toon.starts_with("[500]{id,name}:\n  0,user0\n"));
+        assert!(toon.ends_with("  499,user499"));
+    }
+
+    #[test]
+    fn test_msgpack_file_detected_by_extension() {
+        let tmp = tempdir().unwrap();
+        let input_path = tmp.path().join("cache.msgpack");
+        let value = rmpv::Value::Map(vec![
+            (rmpv::Value::from("id"), rmpv::Value::from(1)),
+            (
+                rmpv::Value::from("raw"),
+                rmpv::Value::Binary(b"hi".to_vec()),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &value).unwrap();
+        fs::write(&input_path, bytes).unwrap();
+
+        let output = run_toonconv(&[input_path.to_str().unwrap()]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.trim_end(), "id: 1\nraw: \"base64:aGk=\"");
+    }
+
+    #[test]
+    fn test_lenient_input_warns_about_extensions() {
+        let input = "{\n  // comment\n  name: 'app',\n  ports: [0x50, 443,],\n}";
+
+        let strict = run_toonconv_with_stdin(&["--stdin"], input);
+        assert!(!strict.status.success());
+
+        let output = run_toonconv_with_stdin(&["--stdin", "--lenient"], input);
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.trim_end(), "name: app\nports[2]: 80,443");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(
+            "Accepted non-standard JSON: comments (line 2), trailing commas (line 4), \
+             single-quoted strings (line 3), unquoted keys (line 3), hexadecimal numbers (line 4)"
+        ));
+    }
+
+    #[test]
+    fn test_repair_truncated_llm_output() {
+        let input = "[{\"id\":1,\"ok\":true},{\"id\":2,\"ok\":false},{\"id\":3,\"o";
+
+        let output = run_toonconv_with_stdin(&["--stdin", "--repair"], input);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.trim_end(), "[2]{id,ok}:\n  1,true\n  2,false");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Repaired: Dropped incomplete last element at line 1, column 41"));
+        assert!(stderr.contains("Repaired: Closed unterminated array at line 1, column 51"));
+    }
+
+    #[test]
+    fn test_jsonc_file_detected_by_extension() {
+        let tmp = tempdir().unwrap();
+        let input_path = create_test_json_file(
+            &tmp,
+            "settings.jsonc",
+            "{\n  /* editor */\n  \"tabSize\": 2,\n}\n",
+        );
+
+        let output = run_toonconv(&[input_path.to_str().unwrap(), "--quiet"]);
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            "tabSize: 2"
+        );
+        assert!(output.stderr.is_empty());
+    }
+}
+
+// ============================================================================
+// TOON Subcommands
+// ============================================================================
+
+mod toon_subcommands {
+    use super::*;
+
+    const USERS_SCHEMA: &str = r#"{
+        "type": "object",
+        "required": ["users"],
+        "properties": {
+            "users": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["id", "name"],
+                    "properties": {"id": {"type": "integer"}}
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_check_reports_pointer_and_line() {
+        let tmp = tempdir().unwrap();
+        let schema = create_test_json_file(&tmp, "schema.json", USERS_SCHEMA);
+        let response = create_test_json_file(
+            &tmp,
+            "response.toon",
+            "users[2]{id,name}:\n  1,Ann\n  two,Bo\n",
+        );
+
+        let output = run_toonconv(&[
+            "check",
+            "--schema",
+            schema.to_str().unwrap(),
+            response.to_str().unwrap(),
+        ]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("✗ /users/1/id: \"two\" is not of type \"integer\" (line 3)"));
+        assert!(stderr.contains("1 schema violation(s)"));
+    }
+
+    #[test]
+    fn test_template_renders_table_skeleton() {
+        let tmp = tempdir().unwrap();
+        let schema = create_test_json_file(
+            &tmp,
+            "schema.json",
+            r#"{
+                "type": "object",
+                "properties": {
+                    "summary": {"type": "string"},
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "sku": {"type": "string"},
+                                "qty": {"type": "integer"},
+                                "status": {"enum": ["in_stock", "backorder"]}
+                            }
+                        }
+                    }
+                }
+            }"#,
+        );
+
+        let output = run_toonconv(&["template", schema.to_str().unwrap()]);
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            "summary: <string>\nitems[N]{sku,qty,status}:\n  <string>,<integer>,<in_stock|backorder>"
+        );
+    }
+
+    #[test]
+    fn test_check_lenient_accepts_miscounted_header() {
+        let tmp = tempdir().unwrap();
+        let schema = create_test_json_file(&tmp, "schema.json", USERS_SCHEMA);
+        let input = "users[3]{id,name}:\n  1,Ann\n  2,Bo\n";
+
+        let strict =
+            run_toonconv_with_stdin(&["check", "--schema", schema.to_str().unwrap(), "-"], input);
+        assert!(!strict.status.success());
+
+        let output = run_toonconv_with_stdin(
+            &[
+                "check",
+                "--lenient",
+                "--schema",
+                schema.to_str().unwrap(),
+                "-",
+            ],
+            input,
+        );
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("⚠ Array header declares 3 entries but 2 were found (line 1)"));
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains("✓ standard input matches the schema")
+        );
+    }
+
+    #[test]
+    fn test_source_map_sidecar() {
+        let tmp = tempdir().unwrap();
+        let input = create_test_json_file(
+            &tmp,
+            "users.json",
+            r#"{"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]}"#,
+        );
+        let output_path = tmp.path().join("users.toon");
+
+        let output = run_toonconv(&[
+            input.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--source-map",
+        ]);
+        assert!(output.status.success());
+
+        let map: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(tmp.path().join("users.toon.map")).unwrap())
+                .unwrap();
+        assert_eq!(map["lines"][0]["pointer"], "/users");
+        assert_eq!(map["lines"][2]["line"], 3);
+        assert_eq!(map["lines"][2]["cells"][1]["pointer"], "/users/1/name");
+
+        let without_output = run_toonconv(&[input.to_str().unwrap(), "--source-map"]);
+        assert!(!without_output.status.success());
+    }
+
+    #[test]
+    fn test_set_rewrites_only_the_edited_value() {
+        let tmp = tempdir().unwrap();
+        let fixture = create_test_json_file(
+            &tmp,
+            "fixture.toon",
+            "prompt:   \"Be brief\"\n\nusers[2|]{id|name}:\n    1 | Ann\n    2 | Bo\n",
+        );
+
+        let output = run_toonconv(&["set", fixture.to_str().unwrap(), "users.1.name", "Cy"]);
+        assert!(output.status.success());
+        let output = run_toonconv(&[
+            "set",
+            fixture.to_str().unwrap(),
+            "/prompt",
+            "\"Be very brief\"",
+        ]);
+        assert!(output.status.success());
+
+        assert_eq!(
+            fs::read_to_string(&fixture).unwrap(),
+            "prompt:   \"Be very brief\"\n\nusers[2|]{id|name}:\n    1 | Ann\n    2 | Cy\n"
+        );
+
+        let missing = run_toonconv(&["set", fixture.to_str().unwrap(), "/nope/deep", "1"]);
+        assert!(!missing.status.success());
+    }
+}
+
+// ============================================================================
+// Validate, Benchmark and Examples Subcommands
+// ============================================================================
+
+mod cli_subcommands {
+    use super::*;
+
+    #[test]
+    fn test_validate_directory_reports_each_file() {
+        let tmp = tempdir().unwrap();
+        create_test_json_file(&tmp, "good.json", r#"{"ok": true}"#);
+        create_test_json_file(&tmp, "bad.json", "{ not valid");
+        let report = tmp.path().join("report.txt");
+
+        let output = run_toonconv(&[
+            "validate",
+            tmp.path().to_str().unwrap(),
+            "--output",
+            report.to_str().unwrap(),
+        ]);
+        assert!(!output.status.success());
+
+        let report = fs::read_to_string(report).unwrap();
+        assert!(report.contains("✓ good.json"));
+        assert!(report.contains("✗ bad.json"));
+    }
+
+    #[test]
+    fn test_directory_reads_each_file_by_format() {
+        let tmp = tempdir().unwrap();
+        let data = tmp.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        create_test_json_file(&tmp, "data/users.csv", "id,name\n1,a\n2,b\n");
+        create_test_json_file(&tmp, "data/pairs.tsv", "a\tb\n1\t2\n");
+        // {"a": 1} as MessagePack
+        fs::write(data.join("record.mpk"), [0x81, 0xa1, b'a', 0x01]).unwrap();
+        let out = tmp.path().join("out");
+
+        let output = run_toonconv(&[
+            data.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+            "--extension",
+            "csv,tsv,mpk",
+        ]);
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Found 3 input files"));
+        assert_eq!(
+            fs::read_to_string(out.join("users.toon")).unwrap(),
+            "[2]{id,name}:\n  1,a\n  2,b"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("pairs.toon")).unwrap(),
+            "[1]{a,b}:\n  1,2"
+        );
+        assert_eq!(fs::read_to_string(out.join("record.toon")).unwrap(), "a: 1");
+
+        create_test_json_file(&tmp, "data/ragged.csv", "a,b\n1\n");
+        let output = run_toonconv(&["validate", data.to_str().unwrap(), "--extension", "csv,mpk"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("1 of 3 input(s) failed validation"));
+        assert!(stderr.contains("ragged.csv: Invalid CSV record"));
+    }
+
+    #[test]
+    fn test_directory_filters_apply_to_validate_and_convert() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("data/old")).unwrap();
+        create_test_json_file(&tmp, "data/a.json", r#"{"a": 1}"#);
+        create_test_json_file(&tmp, "data/old/b.json", "{ not valid");
+        create_test_json_file(&tmp, "data/points.geojson", r#"{"type": "Point"}"#);
+        create_test_json_file(&tmp, "data/skipped.json", "{ not valid");
+        create_test_json_file(&tmp, "data/.toonignore", "skipped.json\n");
+        let data = tmp.path().join("data");
+        let filters = ["--recursive", "--exclude", "old", "--extension", "geojson"];
+
+        let mut validate = vec!["validate", data.to_str().unwrap()];
+        validate.extend(filters);
+        assert!(run_toonconv(&validate).status.success());
+
+        let out = tmp.path().join("out");
+        let mut convert = vec![data.to_str().unwrap(), "-o", out.to_str().unwrap()];
+        convert.extend(filters);
+        assert!(run_toonconv(&convert).status.success());
+        assert!(out.join("a.toon").exists());
+        assert!(out.join("points.toon").exists());
+        assert!(!out.join("old").exists());
+    }
+
+    #[test]
+    fn test_json_report_for_validate_and_convert() {
+        let tmp = tempdir().unwrap();
+        let data = tmp.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        create_test_json_file(
+            &tmp,
+            "data/good.json",
+            r#"{"users": [{"id": 1}, {"id": 2}]}"#,
+        );
+        create_test_json_file(&tmp, "data/bad.json", "{\n  \"a\": \n}");
+
+        let output = run_toonconv(&[
+            "validate",
+            data.to_str().unwrap(),
+            "--report-format",
+            "json",
+        ]);
+        assert!(!output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["command"], "validate");
+        assert_eq!(report["files"][0]["input"], "bad.json");
+        assert_eq!(report["files"][0]["error"]["kind"], "json_parse");
+        assert_eq!(report["files"][0]["error"]["location"]["line"], 3);
+
+        let out = tmp.path().join("out");
+        let output = run_toonconv(&[
+            data.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+            "--continue-on-error",
+            "--report-format",
+            "json",
+        ]);
+        assert!(!output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["success"], false);
+        let good = &report["files"][1];
+        assert_eq!(good["input"], "good.json");
+        assert_eq!(good["output"], out.join("good.toon").to_str().unwrap());
+        assert_eq!(good["metadata"]["schema_info"]["array_count"], 1);
+        assert_eq!(report["statistics"]["file_count"], 1);
+    }
+
+    #[test]
+    fn test_directory_run_summary() {
+        let tmp = tempdir().unwrap();
+        let data = tmp.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        create_test_json_file(
+            &tmp,
+            "data/table.json",
+            r#"{"rows": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 3, "name": "c"}]}"#,
+        );
+        create_test_json_file(&tmp, "data/flat.json", r#"{"id": 1}"#);
+        let out = tmp.path().join("out");
+
+        let output = run_toonconv(&[
+            data.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+            "--target-reduction",
+            "99",
+        ]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Run summary:"));
+        assert!(stdout.contains("Files:      2"));
+        assert!(stdout.contains("Worst-compressing files:"));
+        assert!(stdout.contains("(target: 99.0%)"));
+    }
+
+    #[test]
+    fn test_junit_and_sarif_validation_reports() {
+        let tmp = tempdir().unwrap();
+        let data = tmp.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        create_test_json_file(&tmp, "data/good.json", r#"{"ok": true}"#);
+        create_test_json_file(&tmp, "data/bad.json", "{\n  \"a\": \n}");
+        create_test_json_file(&tmp, "data/bad.toml", "a = 1\nb = \n");
+
+        let junit = tmp.path().join("reports/report.xml");
+        let output = run_toonconv(&[
+            "validate",
+            data.to_str().unwrap(),
+            "--output",
+            junit.to_str().unwrap(),
+            "--report-format",
+            "junit",
+        ]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        let xml = fs::read_to_string(&junit).unwrap();
+        assert!(xml.contains(r#"tests="3" failures="2""#));
+        let bad = data.join("bad.json");
+        assert!(xml.contains(&format!("{}:3:1: ", bad.display())));
+        assert!(xml.contains(&format!("{}:2:5: ", data.join("bad.toml").display())));
+
+        let sarif = tmp.path().join("reports/report.sarif");
+        let output = run_toonconv(&[
+            "validate",
+            data.to_str().unwrap(),
+            "--output",
+            sarif.to_str().unwrap(),
+            "--report-format",
+            "sarif",
+        ]);
+        assert_eq!(output.status.code(), Some(1));
+        let log: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            bad.to_str().unwrap().replace('\\', "/")
+        );
+        assert_eq!(location["region"]["startLine"], 3);
+
+        // Conversion has no JUnit or SARIF report
+        let output = run_toonconv(&[bad.to_str().unwrap(), "--report-format", "sarif"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("only supported by validate"));
+    }
+
+    #[test]
+    fn test_savings_thresholds_exit_code() {
+        let tmp = tempdir().unwrap();
+        let data = tmp.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        create_test_json_file(
+            &tmp,
+            "data/table.json",
+            r#"{"rows": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 3, "name": "c"}]}"#,
+        );
+        let flat = create_test_json_file(&tmp, "data/flat.json", r#"{"id": 1}"#);
+        let out = tmp.path().join("out");
+
+        let output = run_toonconv(&[
+            data.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+            "--min-reduction",
+            "90",
+        ]);
+        assert_eq!(output.status.code(), Some(3));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("✗ flat.json: Token reduction:"));
+        assert!(stderr.contains("2 of 2 file(s) below threshold"));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Savings thresholds: not met"));
+        // Files are still written when they miss a threshold
+        assert!(out.join("flat.toon").exists());
+
+        let output = run_toonconv(&[
+            data.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+            "--min-reduction",
+            "1",
+            "--quiet",
+        ]);
+        assert!(output.status.success());
+
+        let output = run_toonconv(&[flat.to_str().unwrap(), "--max-output-tokens", "1"]);
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Output tokens:"));
+
+        // Streamed CSV counts tokens as it goes, from a file or standard input
+        let csv = "id,name\n1,Ann\n2,Bo\n";
+        let table = create_test_json_file(&tmp, "table.csv", csv);
+        let output = run_toonconv(&[table.to_str().unwrap(), "--max-output-tokens", "1"]);
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Output tokens:"));
+        let output = run_toonconv(&[table.to_str().unwrap(), "--max-output-tokens", "100"]);
+        assert!(output.status.success());
+        let output = run_toonconv_with_stdin(
+            &["--stdin", "--input-format", "csv", "--min-reduction", "90"],
+            csv,
+        );
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Token reduction:"));
+
+        // Conversion errors keep the general exit code
+        let broken = create_test_json_file(&tmp, "broken.json", "{");
+        let output = run_toonconv(&[broken.to_str().unwrap(), "--min-reduction", "1"]);
+        assert_eq!(output.status.code(), Some(1));
+    }
+
+    #[test]
+    fn test_validate_valid_file() {
+        let tmp = tempdir().unwrap();
+        let input = create_test_json_file(&tmp, "good.json", r#"{"ok": true}"#);
+
+        let output = run_toonconv(&["validate", input.to_str().unwrap(), "--quiet"]);
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_profiles_and_profile_flag() {
+        let output = run_toonconv(&["profiles"]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for name in ["speed", "memory", "balanced", "llm-min", "readable"] {
+            assert!(stdout.contains(&format!("{}: ", name)), "{}", name);
+        }
+        assert!(!stdout.contains("length"));
+
+        let tmp = tempdir().unwrap();
+        let input = create_test_json_file(&tmp, "data.json", r#"{"a": {"b": 1}}"#);
+        let output = run_toonconv(&["--profile", "llm-min", input.to_str().unwrap()]);
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            "a:\n b: 1"
+        );
+
+        let input = create_test_json_file(&tmp, "users.json", r#"{"users": [{"id": 1}]}"#);
+        let output = run_toonconv(&["--profile", "readable", input.to_str().unwrap()]);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            "users[1]{id}:\n    1"
+        );
+    }
+
+    #[test]
+    fn test_benchmark_reports_iterations() {
+        let tmp = tempdir().unwrap();
+        let input = create_test_json_file(&tmp, "data.json", r#"{"items": [1, 2, 3]}"#);
+
+        let output = run_toonconv(&["benchmark", input.to_str().unwrap(), "--iterations", "3"]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Iterations: 3"));
+        assert!(stdout.contains("| TOON | "));
+    }
+
+    #[test]
+    fn test_examples_writes_pairs() {
+        let tmp = tempdir().unwrap();
+        let output_dir = tmp.path().join("examples");
+
+        let output = run_toonconv(&[
+            "examples",
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+            "--count",
+            "1",
+        ]);
+        assert!(output.status.success());
+        assert!(output_dir.join("employees.json").exists());
+        assert!(output_dir.join("employees.toon").exists());
+        assert!(output_dir.join("README.md").exists());
+    }
+}
+
 // ============================================================================
 // Performance Sanity Checks
 // ============================================================================
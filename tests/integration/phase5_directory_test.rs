@@ -15,7 +15,7 @@ use tempfile::tempdir;
 
 fn run_toonconv(args: &[&str]) -> Result<(String, String, bool), String> {
     let mut cmd = Command::new("cargo");
-    cmd.args(&["run", "--bin", "toonconv", "--quiet", "--"])
+    cmd.args(["run", "--bin", "toonconv", "--quiet", "--"])
         .args(args)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped());
@@ -68,7 +68,7 @@ fn test_phase5_directory_conversion_basic() {
 
     assert!(success, "Command should succeed: stderr={}", stderr);
     assert!(
-        stdout.contains("Found 3 JSON files"),
+        stdout.contains("Found 3 input files"),
         "Should find 3 files: {}",
         stdout
     );
@@ -129,7 +129,7 @@ fn test_phase5_file_filtering() {
 
     assert!(success, "Command should succeed");
     assert!(
-        stdout.contains("Found 1 JSON files"),
+        stdout.contains("Found 1 input files"),
         "Should find only 1 JSON file: {}",
         stdout
     );
@@ -221,7 +221,7 @@ fn test_phase5_empty_directory() {
 
     assert!(success, "Should succeed on empty directory");
     assert!(
-        stdout.contains("No JSON files found") || stdout.contains("Found 0"),
+        stdout.contains("No input files found") || stdout.contains("Found 0"),
         "Should report no files: {}",
         stdout
     );
@@ -259,7 +259,7 @@ fn test_phase5_non_recursive_mode() {
 
     assert!(success, "Command should succeed");
     assert!(
-        stdout.contains("Found 1 JSON files"),
+        stdout.contains("Found 1 input files"),
         "Should find only top-level file: {}",
         stdout
     );
@@ -46,9 +46,10 @@ struct TestOptions {
 }
 
 fn load_fixture(path: &Path) -> FixtureFile {
-    let content =
-        fs::read_to_string(path).expect(&format!("Failed to read fixture file: {:?}", path));
-    serde_json::from_str(&content).expect(&format!("Failed to parse fixture file: {:?}", path))
+    let content = fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Failed to read fixture file: {:?}", path));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|_| panic!("Failed to parse fixture file: {:?}", path))
 }
 
 fn run_fixture_tests(fixture_path: &str) {